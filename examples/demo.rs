@@ -15,7 +15,7 @@ use image::{
     DynamicImage
 };
 
-use orbrs::{
+use bye_orb_rs::{
     orb, fast
 };
 
@@ -35,12 +35,12 @@ fn test1() {
     let n_keypoints = 50;
 
     // 计算第一张图像的关键点
-    let img1_keypoints = orbrs::orb::orb(&mut img1, n_keypoints).unwrap();
+    let img1_keypoints = bye_orb_rs::orb::orb(&img1, n_keypoints).unwrap();
     // 计算第二张图像的关键点
-    let img2_keypoints = orbrs::orb::orb(&mut img2, n_keypoints).unwrap();
+    let img2_keypoints = bye_orb_rs::orb::orb(&img2, n_keypoints).unwrap();
 
     // 匹配两张图像的关键点
-    let pair_indices = orbrs::orb::match_brief(&img1_keypoints, &img2_keypoints);
+    let pair_indices = bye_orb_rs::orb::match_brief(img1_keypoints.descriptors(), img2_keypoints.descriptors()).unwrap();
 
     // 打印匹配的关键点对
     println!("pair_indices:{:?}", pair_indices);
@@ -49,7 +49,7 @@ fn test2() {
     let mut img = image::open("./assets/money3.jpg").unwrap();
     let mut gray_img: ImageBuffer<Luma<u8>, Vec<u8>> = img.to_luma8();
 
-    let fast_keypoints = orbrs::fast::fast(&gray_img, Some(orbrs::fast::FastType::TYPE_9_16), None).unwrap();
+    let fast_keypoints = bye_orb_rs::fast::fast(&gray_img, Some(bye_orb_rs::fast::FastType::TYPE_9_16), None, None, None).unwrap();
 
     // 在图像上绘制关键点
     // 创建一个具有相同尺寸的RGB图像
@@ -67,6 +67,6 @@ fn test2() {
         rgb_img.put_pixel(x, y, Rgb([gray_value, gray_value, gray_value]));
     }
     
-    orbrs::fast::draw_moments(&mut rgba_img, &fast_keypoints);
+    bye_orb_rs::fast::draw_moments(&mut rgba_img, &fast_keypoints);
     img.save_with_format("fast_output.png", image::ImageFormat::Png);
 }
\ No newline at end of file
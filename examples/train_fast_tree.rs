@@ -0,0 +1,147 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+//! 训练FAST决策树并生成Rust源码
+//!
+//! ```sh
+//! cargo run --release --example train_fast_tree -- [--type 5_8|7_12|9_16] [--threshold 20] [--out fast_tree.rs] 图像...
+//! ```
+//!
+//! 没有给出图像时使用 `assets` 中的示例图像。训练后在每张图像上验证决策树与 `fast::fast` 的结果一致，
+//! 并统计平均比较次数。只有给出 `--out` 时才写出生成的源码，不带参数运行不会修改工作目录
+
+use std::cell::Cell;
+use std::env;
+use std::fs;
+
+use image::GrayImage;
+
+use bye_orb_rs::fast::{self, FastType};
+use bye_orb_rs::fast_tree::{FastTree, FastTreeTrainer};
+
+// 常量
+const DEFAULT_IMAGES:[&str; 3] = ["./assets/money1.jpg", "./assets/money2.jpg", "./assets/money3.jpg"];
+const VERIFY_THRESHOLDS:[i32; 3] = [10, 20, 50]; // 验证时使用的阈值
+
+/// 线性分段测试的比较次数：先比较四个等分点，未排除时再比较所有圆周像素
+fn linear_tests(img: &GrayImage, offsets: &[(i32, i32)], threshold: i32, x: i32, y: i32) -> usize {
+    let len = offsets.len();
+    let center = img.get_pixel(x as u32, y as u32).0[0] as i32;
+    let (mut brighter, mut darker) = (0, 0);
+    for i in (0..len).step_by(len / 4) {
+        let (dx, dy) = offsets[i];
+        let d = img.get_pixel((x + dx) as u32, (y + dy) as u32).0[0] as i32 - center;
+        brighter += (d > threshold) as usize;
+        darker += (d < -threshold) as usize;
+    }
+    if brighter < 2 && darker < 2 { 4 } else { 4 + len }
+}
+
+/// 在图像上验证决策树，返回 `(决策树的平均比较次数, 线性分段测试的平均比较次数)`
+fn verify(tree: &FastTree, img: &GrayImage, threshold: i32) -> (f64, f64) {
+    let expected = fast::fast(img, Some(tree.fast_type()), Some(threshold), None, Some(false)).unwrap();
+    let actual = tree.detect(img, Some(threshold), None, Some(false)).unwrap();
+    let key = |k: &fast::FastKeypoint| (k.location, k.score.to_bits());
+    assert_eq!(expected.iter().map(key).collect::<Vec<_>>(), actual.iter().map(key).collect::<Vec<_>>(),
+        "decision tree differs from fast::fast at threshold {}", threshold);
+
+    let offsets = circle(tree.fast_type());
+    let radius = offsets.iter().map(|&(dx, dy)| dx.abs().max(dy.abs())).max().unwrap() + 1;
+    let (mut tree_tests, mut linear, mut pixels) = (0usize, 0usize, 0usize);
+    for y in radius..img.height() as i32 - radius {
+        for x in radius..img.width() as i32 - radius {
+            let center = img.get_pixel(x as u32, y as u32).0[0] as i32;
+            let tests = Cell::new(0);
+            tree.is_corner(|i| {
+                tests.set(tests.get() + 1);
+                let (dx, dy) = offsets[i];
+                img.get_pixel((x + dx) as u32, (y + dy) as u32).0[0] as i32 - center
+            }, threshold);
+            tree_tests += tests.get();
+            linear += linear_tests(img, &offsets, threshold, x, y);
+            pixels += 1;
+        }
+    }
+    (tree_tests as f64 / pixels as f64, linear as f64 / pixels as f64)
+}
+
+/// 圆周偏移量，与 `fast` 模块中的顺序一致
+fn circle(fast_type: FastType) -> Vec<(i32, i32)> {
+    match fast_type {
+        FastType::TYPE_5_8 => vec![(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)],
+        FastType::TYPE_7_12 => vec![
+            (0, -2), (1, -2), (2, -1), (2, 0), (2, 1), (1, 2),
+            (0, 2), (-1, 2), (-2, 1), (-2, 0), (-2, -1), (-1, -2)
+        ],
+        FastType::TYPE_9_16 => vec![
+            (0, -3), (1, -3), (2, -2), (3, -1), (3, 0), (3, 1), (2, 2), (1, 3),
+            (0, 3), (-1, 3), (-2, 2), (-3, 1), (-3, 0), (-3, -1), (-2, -2), (-1, -3)
+        ]
+    }
+}
+
+fn main() {
+    let mut fast_type = FastType::TYPE_9_16;
+    let mut threshold = None;
+    let mut output = None;
+    let mut paths = vec![];
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--type" => {
+                fast_type = match args.next().as_deref() {
+                    Some("5_8") => FastType::TYPE_5_8,
+                    Some("7_12") => FastType::TYPE_7_12,
+                    Some("9_16") => FastType::TYPE_9_16,
+                    other => panic!("unknown FAST type {:?}, expected 5_8, 7_12 or 9_16", other)
+                };
+            },
+            "--threshold" => threshold = Some(args.next().and_then(|t| t.parse().ok()).expect("--threshold needs an integer")),
+            "--out" => output = Some(args.next().expect("--out needs a path")),
+            _ => paths.push(arg)
+        }
+    }
+    if paths.is_empty() {
+        paths = DEFAULT_IMAGES.iter().map(|p| p.to_string()).collect();
+    }
+
+    let images:Vec<GrayImage> = paths.iter().map(|p| image::open(p).unwrap().to_luma8()).collect();
+
+    let mut trainer = FastTreeTrainer::new(Some(fast_type), threshold).unwrap();
+    for img in &images {
+        trainer.add_image(img).unwrap();
+    }
+    let tree = trainer.train();
+    println!("{:?}: {} pixels, {} patterns, {} nodes, depth {}",
+        fast_type, tree.n_samples(), trainer.n_patterns(), tree.node_count(), tree.depth());
+
+    for t in VERIFY_THRESHOLDS {
+        let (mut tree_tests, mut linear) = (0.0, 0.0);
+        for img in &images {
+            let (a, b) = verify(&tree, img, t);
+            tree_tests += a;
+            linear += b;
+        }
+        println!("threshold {}: matches fast::fast, {:.2} tests per pixel (linear {:.2})",
+            t, tree_tests / images.len() as f64, linear / images.len() as f64);
+    }
+
+    let source = tree.to_rust_source("is_corner");
+    match output {
+        Some(output) => {
+            fs::write(&output, source).unwrap();
+            println!("wrote {}", output);
+        },
+        None => println!("generated {} lines of source, pass --out to write them", source.lines().count())
+    }
+}
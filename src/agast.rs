@@ -0,0 +1,558 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use image::{DynamicImage, GrayImage};
+
+use crate::{common, fast, feature, error};
+use common::Point;
+use fast::{FastKeypoint, ScoreType};
+use error::OrbError;
+use feature::{KeyPoint, FeatureDetector};
+
+// 常量
+const DEFAULT_AGAST_THRESHOLD:i32 = 10; // 默认的AGAST阈值，与OpenCV一致
+const MASK_COUNT:usize = 4; // 模板的数量
+const ROLLOUT_CANDIDATES:usize = 4; // 构造决策树时每个节点估计期望比较次数的候选比较数
+/// 构造两棵决策树时假设的圆周像素状态分布，依次为更暗、相近、更亮的概率
+const HOMOGENEOUS_PROBABILITIES:[f64; 3] = [0.1, 0.8, 0.1]; // 均匀区域中大多数像素与中心相近
+const STRUCTURED_PROBABILITIES:[f64; 3] = [0.4, 0.2, 0.4]; // 纹理区域中大多数像素更亮或更暗
+
+#[allow(non_camel_case_types)]
+/// 表示AGAST的圆周模板，与OpenCV的 `AgastFeatureDetector` 的四种类型一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SegmentMask {
+    AGAST_5_8, // 半径1的8像素方形圆周，连续5个
+    AGAST_7_12d, // 半径3的12像素菱形圆周，连续7个
+    AGAST_7_12s, // 半径2的12像素方形圆周，连续7个
+    #[default]
+    OAST_9_16 // 半径3的16像素圆周，连续9个，与FAST-9-16相同
+}
+
+impl SegmentMask {
+    /// 获取模板的圆周偏移量
+    pub fn offsets(&self) -> Vec<Point> {
+        match self {
+            SegmentMask::AGAST_5_8 => vec![
+                (-1,  0), (-1, -1), ( 0, -1), ( 1, -1),
+                ( 1,  0), ( 1,  1), ( 0,  1), (-1,  1)
+            ],
+            SegmentMask::AGAST_7_12d => vec![
+                (-3,  0), (-2, -1), (-1, -2), ( 0, -3),
+                ( 1, -2), ( 2, -1), ( 3,  0), ( 2,  1),
+                ( 1,  2), ( 0,  3), (-1,  2), (-2,  1)
+            ],
+            SegmentMask::AGAST_7_12s => vec![
+                (-2,  0), (-2, -1), (-1, -2), ( 0, -2),
+                ( 1, -2), ( 2, -1), ( 2,  0), ( 2,  1),
+                ( 1,  2), ( 0,  2), (-1,  2), (-2,  1)
+            ],
+            SegmentMask::OAST_9_16 => vec![
+                (-3,  0), (-3, -1), (-2, -2), (-1, -3),
+                ( 0, -3), ( 1, -3), ( 2, -2), ( 3, -1),
+                ( 3,  0), ( 3,  1), ( 2,  2), ( 1,  3),
+                ( 0,  3), (-1,  3), (-2,  2), (-3,  1)
+            ]
+        }
+    }
+
+    /// 连续弧段的最小长度
+    pub fn arc_length(&self) -> usize {
+        match self {
+            SegmentMask::AGAST_5_8 => 5,
+            SegmentMask::AGAST_7_12d | SegmentMask::AGAST_7_12s => 7,
+            SegmentMask::OAST_9_16 => 9
+        }
+    }
+
+    /// 关键点的直径，等于圆周的直径
+    pub fn keypoint_size(&self) -> f32 {
+        match self {
+            SegmentMask::AGAST_5_8 => 3.0,
+            SegmentMask::AGAST_7_12s => 5.0,
+            SegmentMask::AGAST_7_12d | SegmentMask::OAST_9_16 => 7.0
+        }
+    }
+
+    /// 圆周半径加一个像素，检测时跳过的图像边界
+    fn border(&self) -> u32 {
+        self.offsets().iter().map(|&(dx, dy)| dx.abs().max(dy.abs())).max().unwrap_or(0) as u32 + 1
+    }
+
+    /// 获取模板的两棵决策树，第一次使用时构造并缓存
+    fn trees(&self) -> &'static AgastTrees {
+        static TREES:[OnceLock<AgastTrees>; MASK_COUNT] = [const { OnceLock::new() }; MASK_COUNT];
+        TREES[*self as usize].get_or_init(|| AgastTrees::new(*self))
+    }
+}
+
+/// 决策树针对的区域类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Region {
+    Homogeneous, // 均匀区域，圆周像素大多与中心相近
+    Structured // 纹理区域，圆周像素大多更亮或更暗
+}
+
+/// 表示决策树的节点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AgastNode {
+    Leaf {
+        corner: bool, // 是否为角点
+        next: Region // 下一个像素使用的决策树
+    }, // 叶节点
+    Test {
+        pixel: usize, // 比较的圆周像素序号
+        brighter: bool, // `true` 时比较是否亮于中心加阈值，否则比较是否暗于中心减阈值
+        yes: usize, // 比较成立时进入的节点
+        no: usize // 比较不成立时进入的节点
+    } // 内部节点
+}
+
+/// 表示决策树某个节点上每个圆周像素仍然可能的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PixelStates {
+    brighter: u16, // 可能更亮的像素
+    similar: u16, // 可能相近的像素
+    darker: u16 // 可能更暗的像素
+}
+
+impl PixelStates {
+    /// 确定更亮的像素
+    fn sure_brighter(&self) -> u16 {
+        self.brighter & !self.similar & !self.darker
+    }
+
+    /// 确定更暗的像素
+    fn sure_darker(&self) -> u16 {
+        self.darker & !self.similar & !self.brighter
+    }
+
+    /// 比较第 `i` 个像素是否更亮（`brighter` 为 `true`）或更暗之后，成立与不成立时的状态
+    fn split(&self, i: usize, brighter: bool) -> (PixelStates, PixelStates) {
+        let bit = 1 << i;
+        if brighter {
+            (PixelStates { similar: self.similar & !bit, darker: self.darker & !bit, ..*self },
+             PixelStates { brighter: self.brighter & !bit, ..*self })
+        } else {
+            (PixelStates { brighter: self.brighter & !bit, similar: self.similar & !bit, ..*self },
+             PixelStates { darker: self.darker & !bit, ..*self })
+        }
+    }
+}
+
+/// 表示一个模板的两棵AGAST决策树
+///
+/// 两棵树共用节点数组，分别针对均匀区域和纹理区域选择比较顺序。
+/// 叶节点记录下一个像素应该使用哪棵树，检测时按扫描顺序在两棵树之间切换
+#[derive(Debug, Clone)]
+struct AgastTrees {
+    nodes: Vec<AgastNode>, // 两棵树的节点
+    roots: [usize; 2], // 均匀区域和纹理区域的根节点
+    offsets: Vec<Point>, // 圆周偏移量
+    border: u32 // 检测时跳过的图像边界
+}
+
+impl AgastTrees {
+    /// 为模板构造两棵决策树
+    fn new(mask: SegmentMask) -> AgastTrees {
+        let offsets = mask.offsets();
+        let mut nodes = vec![];
+        let roots = [HOMOGENEOUS_PROBABILITIES, STRUCTURED_PROBABILITIES].map(|probabilities| {
+            TreeBuilder::new(offsets.len(), mask.arc_length(), probabilities).build_tree(&mut nodes)
+        });
+
+        AgastTrees { nodes, roots, offsets, border: mask.border() }
+    }
+
+    /// 从 `region` 对应的根节点开始沿决策树判断一个像素是否为角点
+    ///
+    /// # 参数
+    ///
+    /// * `region` - 使用的决策树
+    /// * `pixel` - 返回第 `i` 个圆周像素与中心像素的差，只会对需要比较的像素调用
+    /// * `threshold` - AGAST阈值
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `(是否为角点, 下一个像素使用的决策树)`
+    fn test(&self, region: Region, pixel: impl Fn(usize) -> i32, threshold: i32) -> (bool, Region) {
+        let mut index = self.roots[region as usize];
+        loop {
+            match self.nodes[index] {
+                AgastNode::Leaf { corner, next } => return (corner, next),
+                AgastNode::Test { pixel: i, brighter, yes, no } => {
+                    let d = pixel(i);
+                    let holds = if brighter { d > threshold } else { d < -threshold };
+                    index = if holds { yes } else { no };
+                }
+            }
+        }
+    }
+}
+
+/// 按假设的像素状态分布构造期望比较次数尽量少的决策树
+///
+/// 每次比较一个圆周像素是否更亮或是否更暗，圆周像素的状态假设相互独立、服从给定的分布。
+/// 只有已比较的像素已经能确定分段测试的结果时才生成叶节点，
+/// 因此两棵树对任意输入都与分段测试完全一致，分布只影响比较的顺序。相同的状态共用节点
+struct TreeBuilder {
+    len: usize, // 圆周像素数
+    n: usize, // 连续弧段的最小长度
+    through: Vec<u16>, // 经过第 `i` 个像素的长度为 `n` 的弧段的起点
+    probabilities: [f64; 3], // 更暗、相近、更亮的概率
+    memo: HashMap<PixelStates, usize>, // 已构造的状态对应的节点
+    costs: HashMap<PixelStates, f64> // 按启发式比较时各状态的期望比较次数
+}
+
+impl TreeBuilder {
+    fn new(len: usize, n: usize, probabilities: [f64; 3]) -> TreeBuilder {
+        let through = (0..len)
+            .map(|i| (0..n).fold(0u16, |starts, k| starts | 1 << ((i + len - k) % len)))
+            .collect();
+        TreeBuilder { len, n, through, probabilities, memo: HashMap::new(), costs: HashMap::new() }
+    }
+
+    /// 构造整棵树并返回根节点
+    fn build_tree(&mut self, nodes: &mut Vec<AgastNode>) -> usize {
+        let all = ((1u32 << self.len) - 1) as u16;
+        self.build(PixelStates { brighter: all, similar: all, darker: all }, nodes)
+    }
+
+    fn build(&mut self, states: PixelStates, nodes: &mut Vec<AgastNode>) -> usize {
+        if let Some(&index) = self.memo.get(&states) {
+            return index;
+        }
+
+        let index = nodes.len();
+        if let Some(corner) = self.decide(&states) {
+            let next = if corner { Region::Structured } else { self.next_region(&states) };
+            nodes.push(AgastNode::Leaf { corner, next });
+        } else {
+            let (pixel, brighter) = self.choose_test(&states);
+            let (holds, fails) = states.split(pixel, brighter);
+            // 先占位，子节点构造完成后再填入
+            nodes.push(AgastNode::Leaf { corner: false, next: Region::Homogeneous });
+            let yes = self.build(holds, nodes);
+            let no = self.build(fails, nodes);
+            nodes[index] = AgastNode::Test { pixel, brighter, yes, no };
+        }
+
+        self.memo.insert(states, index);
+        index
+    }
+
+    /// 已比较的像素能确定分段测试的结果时返回是否为角点，否则返回 `None`
+    fn decide(&self, states: &PixelStates) -> Option<bool> {
+        let has_arc = |set: u16| fast::has_arc(set, self.len, self.n);
+        if has_arc(states.sure_brighter()) || has_arc(states.sure_darker()) {
+            Some(true)
+        } else if !has_arc(states.brighter) && !has_arc(states.darker) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// 在启发式排序最靠前的 `ROLLOUT_CANDIDATES` 个比较中选择期望比较次数最小的比较
+    ///
+    /// 比较之后的子树按 `heuristic_test` 估计期望比较次数。候选中总是包括启发式本身的选择，
+    /// 因此构造出的树的期望比较次数不超过只用启发式构造的树
+    fn choose_test(&mut self, states: &PixelStates) -> (usize, bool) {
+        let mut candidates = self.heuristic_gains(states);
+        // 稳定排序，增益相同时保持像素序号的顺序
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut best = candidates[0].0;
+        let mut best_cost = f64::INFINITY;
+        for &((i, brighter), _) in candidates.iter().take(ROLLOUT_CANDIDATES) {
+            let [pd, _, pb] = self.pixel_probabilities(states, i);
+            let p_holds = if brighter { pb } else { pd };
+            let (holds, fails) = states.split(i, brighter);
+            let cost = p_holds * self.heuristic_cost(holds) + (1.0 - p_holds) * self.heuristic_cost(fails);
+            if cost < best_cost {
+                best = (i, brighter);
+                best_cost = cost;
+            }
+        }
+        best
+    }
+
+    /// 从 `states` 开始一直按 `heuristic_test` 比较时的期望比较次数
+    fn heuristic_cost(&mut self, states: PixelStates) -> f64 {
+        if let Some(&cost) = self.costs.get(&states) {
+            return cost;
+        }
+
+        let cost = if self.decide(&states).is_some() {
+            0.0
+        } else {
+            let (i, brighter) = self.heuristic_test(&states);
+            let [pd, _, pb] = self.pixel_probabilities(&states, i);
+            let p_holds = if brighter { pb } else { pd };
+            let (holds, fails) = states.split(i, brighter);
+            1.0 + p_holds * self.heuristic_cost(holds) + (1.0 - p_holds) * self.heuristic_cost(fails)
+        };
+        self.costs.insert(states, cost);
+        cost
+    }
+
+    /// 选择期望排除的候选弧段最多的比较，相同时选择序号最小的像素
+    fn heuristic_test(&self, states: &PixelStates) -> (usize, bool) {
+        let mut best = ((0, true), -1.0);
+        for (test, gain) in self.heuristic_gains(states) {
+            if gain > best.1 {
+                best = (test, gain);
+            }
+        }
+        best.0
+    }
+
+    /// 所有结果未确定的比较及其期望排除的候选弧段数，按像素序号排列
+    ///
+    /// 比较“是否更亮”成立时排除经过该像素的全暗弧段，不成立时排除经过该像素的全亮弧段，比较“是否更暗”反之
+    fn heuristic_gains(&self, states: &PixelStates) -> Vec<((usize, bool), f64)> {
+        let (brighter_starts, darker_starts) = (self.arc_starts(states.brighter), self.arc_starts(states.darker));
+
+        let mut gains = vec![];
+        for i in 0..self.len {
+            let [pd, _, pb] = self.pixel_probabilities(states, i);
+            let brighter_arcs = (brighter_starts & self.through[i]).count_ones() as f64;
+            let darker_arcs = (darker_starts & self.through[i]).count_ones() as f64;
+
+            for (brighter, p_holds, killed_if_holds, killed_if_fails) in [(true, pb, darker_arcs, brighter_arcs), (false, pd, brighter_arcs, darker_arcs)] {
+                if p_holds == 0.0 || p_holds == 1.0 {
+                    continue;
+                }
+                gains.push(((i, brighter), p_holds * killed_if_holds + (1.0 - p_holds) * killed_if_fails));
+            }
+        }
+        gains
+    }
+
+    /// 完全位于集合内的长度为 `n` 的弧段的起点，与 `fast::has_arc` 的方法相同
+    fn arc_starts(&self, set: u16) -> u16 {
+        let mut runs = set as u32 | (set as u32) << self.len;
+        for _ in 1..self.n {
+            runs &= runs >> 1;
+        }
+        (runs & ((1 << self.len) - 1)) as u16
+    }
+
+    /// 第 `i` 个像素更暗、相近、更亮的概率
+    fn pixel_probabilities(&self, states: &PixelStates, i: usize) -> [f64; 3] {
+        let bit = 1 << i;
+        let mut p = [0.0; 3];
+        for (k, set) in [states.darker, states.similar, states.brighter].into_iter().enumerate() {
+            if set & bit != 0 {
+                p[k] = self.probabilities[k];
+            }
+        }
+        let total:f64 = p.iter().sum();
+        p.map(|p| p / total)
+    }
+
+    /// 非角点叶节点之后使用的决策树：确定更亮或更暗的像素不少于已比较像素的一半时认为处于纹理区域
+    fn next_region(&self, states: &PixelStates) -> Region {
+        let all = ((1u32 << self.len) - 1) as u16;
+        let examined = !(states.brighter & states.similar & states.darker) & all;
+        let sure = states.sure_brighter() | states.sure_darker();
+        if 2 * sure.count_ones() >= examined.count_ones() { Region::Structured } else { Region::Homogeneous }
+    }
+}
+
+/// 执行AGAST算法以检测图像中的关键点
+///
+/// 角点的定义与 `fast::fast` 相同：圆周上存在至少 `n` 个连续像素全部亮于中心像素加阈值或全部暗于中心像素减阈值。
+/// 每个模板有两棵决策树，分别针对均匀区域和纹理区域选择比较顺序，叶节点决定同一行的下一个像素使用哪棵树，
+/// 每行从均匀区域的树开始。决策树不是OpenCV中预先生成的代码，而是在第一次使用时按模板构造并缓存，结果与比较顺序无关。
+/// 得分、非极大值抑制和排序的含义与 `fast::fast` 一致
+///
+/// # 参数
+///
+/// * `img` - 输入图像
+/// * `mask` - 圆周模板，默认为 `OAST_9_16`
+/// * `threshold` - AGAST阈值，默认为10
+/// * `score_type` - 关键点评分方式，默认为Harris响应
+/// * `nonmax_suppression` - 是否执行3x3非极大值抑制，默认开启
+///
+/// # 返回值
+///
+/// 图像的宽或高小于 `2 * radius + 1` 时返回 `OrbError::ImageTooSmall`，阈值为负数时返回 `OrbError::InvalidParameter`
+pub fn agast(img: &GrayImage, mask: Option<SegmentMask>, threshold: Option<i32>, score_type: Option<ScoreType>, nonmax_suppression: Option<bool>) -> Result<Vec<FastKeypoint>, OrbError> {
+    let mut keypoints = agast_corners(img, mask, threshold, score_type, nonmax_suppression)?;
+    fast::compute_default_moments(img, &mut keypoints);
+    Ok(keypoints)
+}
+
+/// 与 `agast` 相同但不计算矩，供先筛选关键点、再按需计算矩的调用方使用
+pub(crate) fn agast_corners(img: &GrayImage, mask: Option<SegmentMask>, threshold: Option<i32>, score_type: Option<ScoreType>, nonmax_suppression: Option<bool>) -> Result<Vec<FastKeypoint>, OrbError> {
+    let threshold = threshold.unwrap_or(DEFAULT_AGAST_THRESHOLD);
+    if threshold < 0 {
+        return Err(OrbError::invalid_parameter("threshold", format!("must not be negative, got {}", threshold)));
+    }
+
+    let trees = mask.unwrap_or_default().trees();
+    let border = trees.border;
+    fast::detect_segment(img, border, score_type.unwrap_or_default(), nonmax_suppression.unwrap_or(true), |y, found| {
+        let mut region = Region::Homogeneous;
+        for x in border..img.width() - border {
+            let center = img.get_pixel(x, y).0[0] as i32;
+            let pixel = |i: usize| {
+                let (dx, dy) = trees.offsets[i];
+                img.get_pixel((x as i32 + dx) as u32, (y as i32 + dy) as u32).0[0] as i32 - center
+            };
+
+            let (is_corner, next) = trees.test(region, pixel, threshold);
+            region = next;
+
+            if is_corner {
+                // 得分需要所有圆周像素
+                let score = (0..trees.offsets.len()).map(|i| pixel(i).abs()).filter(|&d| d > threshold).sum();
+                found.push(((x as i32, y as i32), score));
+            }
+        }
+    })
+}
+
+/// 单尺度的AGAST检测器，参数含义与 `agast` 一致，`None` 时使用 `agast` 的默认值
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AgastDetector {
+    pub mask: Option<SegmentMask>, // 圆周模板
+    pub threshold: Option<i32>, // AGAST阈值
+    pub score_type: Option<ScoreType>, // 关键点评分方式
+    pub nonmax_suppression: Option<bool> // 是否执行3x3非极大值抑制
+}
+
+impl AgastDetector {
+    /// 创建AGAST检测器
+    pub fn new(mask: Option<SegmentMask>, threshold: Option<i32>, score_type: Option<ScoreType>, nonmax_suppression: Option<bool>) -> AgastDetector {
+        AgastDetector { mask, threshold, score_type, nonmax_suppression }
+    }
+}
+
+impl FeatureDetector for AgastDetector {
+    fn detect(&self, img: &DynamicImage) -> Result<Vec<KeyPoint>, OrbError> {
+        let mask = self.mask.unwrap_or_default();
+        let keypoints = agast(&img.to_luma8(), Some(mask), self.threshold, self.score_type, self.nonmax_suppression)?;
+
+        Ok(keypoints.into_iter()
+            .map(|k| KeyPoint {
+                pt: k.position,
+                size: mask.keypoint_size(),
+                angle: k.moment.angle,
+                response: k.score,
+                octave: 0,
+                class_id: -1
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    const MASKS:[SegmentMask; MASK_COUNT] = [SegmentMask::AGAST_5_8, SegmentMask::AGAST_7_12d, SegmentMask::AGAST_7_12s, SegmentMask::OAST_9_16];
+
+    /// 圆周像素与中心像素的差为 `diffs` 时分段测试的结果
+    fn segment_test(diffs: &[i32], n: usize, threshold: i32) -> bool {
+        let set = |f: &dyn Fn(i32) -> bool| diffs.iter().enumerate().fold(0u16, |set, (i, &d)| if f(d) { set | 1 << i } else { set });
+        fast::has_arc(set(&|d| d > threshold), diffs.len(), n) || fast::has_arc(set(&|d| d < -threshold), diffs.len(), n)
+    }
+
+    /// 圆周像素按给定分布独立取值时，从 `index` 开始的期望比较次数
+    fn expected_cost(trees: &AgastTrees, index: usize, states: PixelStates, builder: &TreeBuilder) -> f64 {
+        match trees.nodes[index] {
+            AgastNode::Leaf { .. } => 0.0,
+            AgastNode::Test { pixel, brighter, yes, no } => {
+                let [pd, _, pb] = builder.pixel_probabilities(&states, pixel);
+                let p_holds = if brighter { pb } else { pd };
+                let (holds, fails) = states.split(pixel, brighter);
+                1.0 + p_holds * expected_cost(trees, yes, holds, builder) + (1.0 - p_holds) * expected_cost(trees, no, fails, builder)
+            }
+        }
+    }
+
+    #[test]
+    fn trees_agree_with_segment_test() {
+        let threshold = 10;
+        let mut rng = StdRng::seed_from_u64(5);
+        for mask in MASKS {
+            let trees = mask.trees();
+            let (len, n) = (mask.offsets().len(), mask.arc_length());
+            let check = |diffs: &[i32]| {
+                let expected = segment_test(diffs, n, threshold);
+                for region in [Region::Homogeneous, Region::Structured] {
+                    let (corner, next) = trees.test(region, |i| diffs[i], threshold);
+                    assert_eq!(corner, expected, "{:?} {:?} tree disagrees on {:?}", mask, region, diffs);
+                    assert!(!corner || next == Region::Structured);
+                }
+            };
+
+            // 12个像素以内枚举所有状态组合，16个像素时随机抽样并偏向出现长弧段
+            let mut diffs = vec![0; len];
+            if len <= 12 {
+                for code in 0..3usize.pow(len as u32) {
+                    for (i, d) in diffs.iter_mut().enumerate() {
+                        *d = [-20, 0, 20][code / 3usize.pow(i as u32) % 3];
+                    }
+                    check(&diffs);
+                }
+            } else {
+                for _ in 0..200_000 {
+                    let bias = rng.gen_range(0.0..1.0);
+                    for d in diffs.iter_mut() {
+                        *d = if rng.gen_bool(bias) { 20 } else { rng.gen_range(-20..=20) };
+                    }
+                    check(&diffs);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn trees_need_fewer_comparisons_than_heuristic_order() {
+        for mask in MASKS {
+            let trees = mask.trees();
+            let len = mask.offsets().len();
+            let all = ((1u32 << len) - 1) as u16;
+            let root = PixelStates { brighter: all, similar: all, darker: all };
+
+            for (region, probabilities) in [(Region::Homogeneous, HOMOGENEOUS_PROBABILITIES), (Region::Structured, STRUCTURED_PROBABILITIES)] {
+                let mut builder = TreeBuilder::new(len, mask.arc_length(), probabilities);
+                let cost = expected_cost(trees, trees.roots[region as usize], root, &builder);
+                let heuristic = builder.heuristic_cost(root);
+                assert!(cost <= heuristic + 1e-9, "{:?} {:?}: {} comparisons, heuristic order needs {}", mask, region, cost, heuristic);
+            }
+        }
+    }
+
+    #[test]
+    fn leaves_switch_between_regions() {
+        let trees = SegmentMask::OAST_9_16.trees();
+        // 均匀区域中只需比较少数像素即可排除，之后继续使用均匀区域的树
+        assert_eq!(trees.test(Region::Structured, |_| 0, 10), (false, Region::Homogeneous));
+        // 亮暗交替的纹理排除时已经确定了大部分像素的状态
+        assert_eq!(trees.test(Region::Homogeneous, |i| if i % 2 == 0 { 50 } else { -50 }, 10), (false, Region::Structured));
+        assert_eq!(trees.test(Region::Homogeneous, |_| 50, 10), (true, Region::Structured));
+    }
+
+    #[test]
+    fn negative_threshold_is_rejected() {
+        let img = GrayImage::new(16, 16);
+        assert!(matches!(agast(&img, None, Some(-1), None, None), Err(OrbError::InvalidParameter { name: "threshold", .. })));
+        assert!(matches!(agast(&GrayImage::new(6, 6), None, None, None, None), Err(OrbError::ImageTooSmall { .. })));
+    }
+}
@@ -1 +1,437 @@
-pub const OFFSETS:[((f32, f32), (f32, f32));256] = [((1.0, -8.0), (-4.0, 1.0)), ((-2.0, -0.0), (2.0, 2.0)), ((3.0, -1.0), (-2.0, -1.0)), ((-5.0, -5.0), (1.0, -2.0)), ((-2.0, 5.0), (5.0, 5.0)), ((-0.0, -1.0), (2.0, 1.0)), ((-1.0, -1.0), (-4.0, -12.0)), ((1.0, 1.0), (3.0, 3.0)), ((-1.0, 1.0), (6.0, 1.0)), ((-1.0, 1.0), (-2.0, 7.0)), ((-1.0, -4.0), (3.0, 6.0)), ((4.0, -5.0), (6.0, -3.0)), ((-11.0, 1.0), (3.0, 8.0)), ((-6.0, -1.0), (4.0, 1.0)), ((-0.0, -6.0), (1.0, -4.0)), ((-1.0, -4.0), (-8.0, 2.0)), ((3.0, 4.0), (2.0, -3.0)), ((-1.0, -6.0), (-2.0, 4.0)), ((-4.0, -5.0), (3.0, 2.0)), ((-8.0, 2.0), (-7.0, 1.0)), ((1.0, 9.0), (3.0, -4.0)), ((-7.0, 2.0), (-5.0, -2.0)), ((4.0, 2.0), (-7.0, 3.0)), ((3.0, -1.0), (1.0, 5.0)), ((1.0, -9.0), (-2.0, 3.0)), ((0.0, -5.0), (-11.0, -1.0)), ((3.0, 0.0), (-5.0, 7.0)), ((-1.0, -0.0), (2.0, 1.0)), ((2.0, -1.0), (2.0, 4.0)), ((6.0, -2.0), (2.0, 9.0)), ((3.0, -2.0), (-4.0, -7.0)), ((5.0, -1.0), (-2.0, 2.0)), ((-4.0, -8.0), (-1.0, 2.0)), ((6.0, -1.0), (-3.0, 5.0)), ((-3.0, 2.0), (4.0, 7.0)), ((3.0, 7.0), (3.0, 3.0)), ((-2.0, 5.0), (2.0, -4.0)), ((-3.0, 1.0), (9.0, 5.0)), ((-5.0, -3.0), (10.0, -6.0)), ((-1.0, 8.0), (-4.0, 6.0)), ((7.0, -1.0), (-0.0, -3.0)), ((5.0, 7.0), (6.0, 2.0)), ((-5.0, 2.0), (-4.0, 2.0)), ((3.0, -0.0), (-1.0, 1.0)), ((-1.0, 4.0), (1.0, 0.0)), ((3.0, -4.0), (2.0, 4.0)), ((5.0, -1.0), (-6.0, -4.0)), ((-5.0, 2.0), (1.0, 1.0)), ((-5.0, -1.0), (1.0, -1.0)), ((2.0, 2.0), (6.0, -10.0)), ((-6.0, 2.0), (-9.0, -1.0)), ((4.0, -1.0), (2.0, 10.0)), ((2.0, 0.0), (-6.0, -7.0)), ((7.0, -6.0), (-2.0, 7.0)), ((2.0, 7.0), (-6.0, -3.0)), ((-0.0, 2.0), (-4.0, -9.0)), ((2.0, 7.0), (2.0, 0.0)), ((3.0, -9.0), (-2.0, -2.0)), ((2.0, -1.0), (-0.0, -2.0)), ((0.0, -3.0), (4.0, -3.0)), ((2.0, 8.0), (-2.0, -4.0)), ((5.0, -4.0), (8.0, 3.0)), ((4.0, 2.0), (4.0, -2.0)), ((9.0, 3.0), (-0.0, 2.0)), ((-2.0, -4.0), (-2.0, 3.0)), ((4.0, -0.0), (3.0, 0.0)), ((1.0, -7.0), (5.0, -3.0)), ((-6.0, -5.0), (-2.0, -0.0)), ((-4.0, -5.0), (5.0, 4.0)), ((-12.0, 7.0), (0.0, -7.0)), ((2.0, -4.0), (7.0, -2.0)), ((-4.0, 4.0), (3.0, 2.0)), ((-5.0, 5.0), (3.0, 3.0)), ((2.0, 1.0), (3.0, 6.0)), ((3.0, 1.0), (1.0, 6.0)), ((-3.0, 0.0), (0.0, 3.0)), ((3.0, 4.0), (-0.0, -3.0)), ((0.0, -2.0), (2.0, -0.0)), ((1.0, -1.0), (1.0, 1.0)), ((-2.0, 11.0), (0.0, 1.0)), ((-1.0, 0.0), (-3.0, -2.0)), ((-3.0, 4.0), (-12.0, 10.0)), ((7.0, 4.0), (1.0, -2.0)), ((-9.0, 5.0), (-2.0, 3.0)), ((1.0, 2.0), (-5.0, -1.0)), ((6.0, -4.0), (2.0, 5.0)), ((4.0, -3.0), (3.0, -1.0)), ((-6.0, 1.0), (3.0, -4.0)), ((-7.0, -3.0), (0.0, 0.0)), ((2.0, -2.0), (0.0, -1.0)), ((-1.0, 4.0), (-1.0, 3.0)), ((-6.0, 0.0), (3.0, 0.0)), ((0.0, 6.0), (1.0, -5.0)), ((-1.0, -3.0), (0.0, 5.0)), ((2.0, -2.0), (3.0, -1.0)), ((3.0, -2.0), (0.0, 1.0)), ((2.0, -0.0), (5.0, 4.0)), ((-0.0, -1.0), (-2.0, 1.0)), ((4.0, 2.0), (-7.0, -6.0)), ((2.0, 3.0), (-5.0, 1.0)), ((2.0, -1.0), (5.0, 1.0)), ((1.0, -1.0), (-1.0, 0.0)), ((1.0, 6.0), (-9.0, -2.0)), ((7.0, -4.0), (2.0, -3.0)), ((4.0, -2.0), (-0.0, -3.0)), ((3.0, -2.0), (-5.0, -2.0)), ((1.0, -6.0), (2.0, -5.0)), ((-7.0, 3.0), (4.0, 1.0)), ((-3.0, 2.0), (-6.0, 2.0)), ((-6.0, 5.0), (1.0, -2.0)), ((1.0, 7.0), (-9.0, -5.0)), ((-4.0, 1.0), (-7.0, 6.0)), ((0.0, -3.0), (-2.0, -1.0)), ((5.0, 2.0), (-4.0, -1.0)), ((-4.0, -0.0), (1.0, 7.0)), ((3.0, -3.0), (3.0, -0.0)), ((2.0, -1.0), (-7.0, -6.0)), ((-1.0, 0.0), (8.0, -4.0)), ((-1.0, 5.0), (3.0, -0.0)), ((-2.0, -1.0), (-1.0, -2.0)), ((0.0, -6.0), (-2.0, -4.0)), ((-5.0, 0.0), (5.0, 2.0)), ((-2.0, -1.0), (4.0, 6.0)), ((4.0, -2.0), (-2.0, -3.0)), ((0.0, -2.0), (-4.0, 5.0)), ((1.0, -1.0), (10.0, 8.0)), ((-1.0, 2.0), (7.0, 4.0)), ((6.0, -3.0), (3.0, 1.0)), ((7.0, -0.0), (-7.0, -1.0)), ((3.0, 3.0), (-6.0, 4.0)), ((-3.0, 1.0), (2.0, 2.0)), ((2.0, -3.0), (-5.0, -2.0)), ((10.0, 4.0), (-1.0, 7.0)), ((-3.0, 1.0), (-0.0, 4.0)), ((1.0, -2.0), (0.0, 2.0)), ((-3.0, -0.0), (-0.0, 1.0)), ((-2.0, 4.0), (-2.0, -1.0)), ((-0.0, 3.0), (-0.0, -1.0)), ((5.0, -4.0), (-1.0, -0.0)), ((-4.0, 1.0), (8.0, 6.0)), ((-10.0, -1.0), (4.0, -3.0)), ((-1.0, -1.0), (-7.0, 0.0)), ((3.0, -0.0), (-7.0, -3.0)), ((-2.0, 5.0), (1.0, -6.0)), ((8.0, -1.0), (2.0, 1.0)), ((1.0, 2.0), (-1.0, 0.0)), ((-8.0, 5.0), (4.0, -6.0)), ((3.0, 0.0), (7.0, -3.0)), ((0.0, -3.0), (-5.0, 1.0)), ((3.0, 1.0), (-3.0, -1.0)), ((1.0, -7.0), (-2.0, -6.0)), ((-6.0, -5.0), (-0.0, 3.0)), ((-3.0, -1.0), (2.0, 6.0)), ((6.0, 3.0), (-4.0, 4.0)), ((-0.0, -1.0), (-3.0, 5.0)), ((5.0, 7.0), (2.0, 6.0)), ((6.0, -5.0), (-8.0, 1.0)), ((0.0, -2.0), (1.0, -5.0)), ((-6.0, -1.0), (3.0, -1.0)), ((-6.0, -6.0), (-3.0, 7.0)), ((-10.0, 2.0), (-5.0, -5.0)), ((-5.0, 4.0), (1.0, 0.0)), ((-7.0, -0.0), (-3.0, 3.0)), ((3.0, 5.0), (3.0, -8.0)), ((0.0, 6.0), (4.0, -4.0)), ((-0.0, -3.0), (1.0, 3.0)), ((-5.0, -0.0), (6.0, 3.0)), ((2.0, 1.0), (3.0, 1.0)), ((2.0, 7.0), (7.0, -2.0)), ((-3.0, 10.0), (-3.0, 2.0)), ((0.0, -1.0), (5.0, -4.0)), ((6.0, -4.0), (1.0, -3.0)), ((-2.0, 3.0), (-0.0, 5.0)), ((-6.0, 4.0), (-5.0, -1.0)), ((-1.0, 0.0), (7.0, 2.0)), ((-2.0, 5.0), (1.0, 2.0)), ((-2.0, -3.0), (-7.0, 7.0)), ((5.0, -1.0), (-2.0, 0.0)), ((0.0, 2.0), (1.0, -4.0)), ((-3.0, -2.0), (1.0, 3.0)), ((4.0, -10.0), (-6.0, 7.0)), ((3.0, 0.0), (-1.0, 1.0)), ((-1.0, -3.0), (-1.0, 5.0)), ((-3.0, 3.0), (6.0, 1.0)), ((-1.0, -2.0), (-0.0, -5.0)), ((-6.0, 8.0), (-9.0, -1.0)), ((2.0, -8.0), (2.0, -9.0)), ((-1.0, -0.0), (-2.0, 7.0)), ((-3.0, -6.0), (-1.0, -4.0)), ((-4.0, -1.0), (3.0, 2.0)), ((1.0, -5.0), (-2.0, -4.0)), ((1.0, 1.0), (10.0, 2.0)), ((8.0, 3.0), (-3.0, 4.0)), ((1.0, 3.0), (-1.0, 8.0)), ((-1.0, 1.0), (-3.0, -2.0)), ((4.0, -2.0), (2.0, 2.0)), ((-1.0, 1.0), (-6.0, -4.0)), ((-4.0, -3.0), (8.0, -3.0)), ((2.0, -1.0), (1.0, 1.0)), ((3.0, -2.0), (7.0, 0.0)), ((0.0, -2.0), (-3.0, -2.0)), ((4.0, 1.0), (-4.0, -1.0)), ((-1.0, -2.0), (-6.0, 8.0)), ((-1.0, 3.0), (-1.0, 3.0)), ((9.0, -1.0), (-3.0, -1.0)), ((-0.0, 3.0), (-2.0, 1.0)), ((-3.0, -3.0), (-2.0, 0.0)), ((1.0, 2.0), (6.0, -1.0)), ((-5.0, -0.0), (-1.0, 7.0)), ((3.0, -6.0), (-3.0, -1.0)), ((-4.0, -1.0), (-1.0, -7.0)), ((4.0, 4.0), (1.0, -4.0)), ((4.0, -7.0), (-1.0, 8.0)), ((-2.0, 0.0), (4.0, 3.0)), ((8.0, -4.0), (2.0, -5.0)), ((-2.0, 5.0), (0.0, -4.0)), ((1.0, -3.0), (4.0, -4.0)), ((-3.0, -2.0), (2.0, 2.0)), ((5.0, 1.0), (3.0, 4.0)), ((-2.0, -1.0), (-0.0, -3.0)), ((-3.0, -2.0), (4.0, -6.0)), ((-1.0, 2.0), (2.0, 8.0)), ((5.0, 6.0), (-1.0, -5.0)), ((-3.0, -2.0), (-1.0, -5.0)), ((-1.0, 1.0), (5.0, 3.0)), ((0.0, 1.0), (-2.0, 1.0)), ((-4.0, -4.0), (1.0, 3.0)), ((-1.0, -6.0), (4.0, 3.0)), ((4.0, 3.0), (8.0, 6.0)), ((-0.0, -5.0), (-4.0, -2.0)), ((3.0, 0.0), (2.0, 4.0)), ((1.0, -2.0), (1.0, 2.0)), ((-5.0, 1.0), (-2.0, 5.0)), ((-3.0, -3.0), (-1.0, 2.0)), ((-0.0, 9.0), (3.0, 1.0)), ((-3.0, -2.0), (5.0, -3.0)), ((1.0, 3.0), (1.0, 0.0)), ((-4.0, 0.0), (6.0, -2.0)), ((-4.0, 1.0), (-1.0, -5.0)), ((5.0, -6.0), (-6.0, -7.0)), ((7.0, -8.0), (5.0, 8.0)), ((4.0, 2.0), (3.0, -1.0)), ((5.0, -0.0), (-3.0, 8.0)), ((-2.0, -1.0), (-7.0, 3.0)), ((-2.0, -8.0), (2.0, 2.0)), ((1.0, 2.0), (1.0, 5.0)), ((1.0, -2.0), (1.0, 2.0)), ((5.0, 9.0), (-1.0, -0.0)), ((2.0, 2.0), (1.0, 2.0)), ((-1.0, -3.0), (2.0, -3.0)), ((-1.0, -3.0), (-2.0, 1.0)), ((3.0, 11.0), (6.0, 8.0)), ((6.0, 3.0), (2.0, 3.0)), ((5.0, 0.0), (-2.0, 6.0)), ((-0.0, -3.0), (-4.0, -0.0)), ((-2.0, -2.0), (2.0, 1.0))];
\ No newline at end of file
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use std::fmt;
+use std::f32::consts::PI;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::{descriptor, error};
+use descriptor::Descriptor256;
+use error::OrbError;
+
+/// BRIEF采样点对 `((x0, y0), (x1, y1))`，坐标相对于关键点中心
+pub type SamplePair = ((f32, f32), (f32, f32));
+
+// 常量
+pub const PATCH_SIZE:u32 = 31; // rBRIEF图像块边长
+pub const HALF_PATCH_SIZE:i32 = (PATCH_SIZE / 2) as i32; // rBRIEF图像块半径
+pub const LEARNED_PATTERN_LENGTH:usize = 256; // 学习模式的点对数量
+pub const MAX_PATTERN_LENGTH:usize = Descriptor256::BITS; // 描述子可容纳的最大点对数量
+const ISOTROPIC_RADII:[f32; 6] = [2.0, 4.0, 6.0, 8.0, 10.0, 12.0]; // 各向同性模式的同心圆半径
+const MAX_SAMPLE_ATTEMPTS:usize = 1 << 20; // 随机生成模式时的最大尝试次数
+
+/// ORB论文中学习得到的、去相关的rBRIEF测试模式（31x31图像块，256个点对）
+///
+/// 与OpenCV的 `bit_pattern_31_` 一致，第 `i` 个点对对应描述子的第 `i` 位。
+pub const LEARNED_PATTERN:[SamplePair; LEARNED_PATTERN_LENGTH] = [
+    ((8.0, -3.0), (9.0, 5.0)), ((4.0, 2.0), (7.0, -12.0)), ((-11.0, 9.0), (-8.0, 2.0)), ((7.0, -12.0), (12.0, -13.0)),
+    ((2.0, -13.0), (2.0, 12.0)), ((1.0, -7.0), (1.0, 6.0)), ((-2.0, -10.0), (-2.0, -4.0)), ((-13.0, -13.0), (-11.0, -8.0)),
+    ((-13.0, -3.0), (-12.0, -9.0)), ((10.0, 4.0), (11.0, 9.0)), ((-13.0, -8.0), (-8.0, -9.0)), ((-11.0, 7.0), (-8.0, 12.0)),
+    ((7.0, 7.0), (12.0, 6.0)), ((-4.0, -5.0), (-3.0, 0.0)), ((-13.0, 2.0), (-12.0, -3.0)), ((-9.0, 0.0), (-7.0, 5.0)),
+    ((12.0, -6.0), (12.0, -1.0)), ((-3.0, 6.0), (-2.0, 12.0)), ((-6.0, -13.0), (-4.0, -8.0)), ((11.0, -13.0), (12.0, -8.0)),
+    ((4.0, 7.0), (5.0, 1.0)), ((5.0, -3.0), (10.0, -3.0)), ((3.0, -7.0), (6.0, 12.0)), ((-8.0, -7.0), (-6.0, -2.0)),
+    ((-2.0, 11.0), (-1.0, -10.0)), ((-13.0, 12.0), (-8.0, 10.0)), ((-7.0, 3.0), (-5.0, -3.0)), ((-4.0, 2.0), (-3.0, 7.0)),
+    ((-10.0, -12.0), (-6.0, 11.0)), ((5.0, -12.0), (6.0, -7.0)), ((5.0, -6.0), (7.0, -1.0)), ((1.0, 0.0), (4.0, -5.0)),
+    ((9.0, 11.0), (11.0, -13.0)), ((4.0, 7.0), (4.0, 12.0)), ((2.0, -1.0), (4.0, 4.0)), ((-4.0, -12.0), (-2.0, 7.0)),
+    ((-8.0, -5.0), (-7.0, -10.0)), ((4.0, 11.0), (9.0, 12.0)), ((0.0, -8.0), (1.0, -13.0)), ((-13.0, -2.0), (-8.0, 2.0)),
+    ((-3.0, -2.0), (-2.0, 3.0)), ((-6.0, 9.0), (-4.0, -9.0)), ((8.0, 12.0), (10.0, 7.0)), ((0.0, 9.0), (1.0, 3.0)),
+    ((7.0, -5.0), (11.0, -10.0)), ((-13.0, -6.0), (-11.0, 0.0)), ((10.0, 7.0), (12.0, 1.0)), ((-6.0, -3.0), (-6.0, 12.0)),
+    ((10.0, -9.0), (12.0, -4.0)), ((-13.0, 8.0), (-8.0, -12.0)), ((-13.0, 0.0), (-8.0, -4.0)), ((3.0, 3.0), (7.0, 8.0)),
+    ((5.0, 7.0), (10.0, -7.0)), ((-1.0, 7.0), (1.0, -12.0)), ((3.0, -10.0), (5.0, 6.0)), ((2.0, -4.0), (3.0, -10.0)),
+    ((-13.0, 0.0), (-13.0, 5.0)), ((-13.0, -7.0), (-12.0, 12.0)), ((-13.0, 3.0), (-11.0, 8.0)), ((-7.0, 12.0), (-4.0, 7.0)),
+    ((6.0, -10.0), (12.0, 8.0)), ((-9.0, -1.0), (-7.0, -6.0)), ((-2.0, -5.0), (0.0, 12.0)), ((-12.0, 5.0), (-7.0, 5.0)),
+    ((3.0, -10.0), (8.0, -13.0)), ((-7.0, -7.0), (-4.0, 5.0)), ((-3.0, -2.0), (-1.0, -7.0)), ((2.0, 9.0), (5.0, -11.0)),
+    ((-11.0, -13.0), (-5.0, -13.0)), ((-1.0, 6.0), (0.0, -1.0)), ((5.0, -3.0), (5.0, 2.0)), ((-4.0, -13.0), (-4.0, 12.0)),
+    ((-9.0, -6.0), (-9.0, 6.0)), ((-12.0, -10.0), (-8.0, -4.0)), ((10.0, 2.0), (12.0, -3.0)), ((7.0, 12.0), (12.0, 12.0)),
+    ((-7.0, -13.0), (-6.0, 5.0)), ((-4.0, 9.0), (-3.0, 4.0)), ((7.0, -1.0), (12.0, 2.0)), ((-7.0, 6.0), (-5.0, 1.0)),
+    ((-13.0, 11.0), (-12.0, 5.0)), ((-3.0, 7.0), (-2.0, -6.0)), ((7.0, -8.0), (12.0, -7.0)), ((-13.0, -7.0), (-11.0, -12.0)),
+    ((1.0, -3.0), (12.0, 12.0)), ((2.0, -6.0), (3.0, 0.0)), ((-4.0, 3.0), (-2.0, -13.0)), ((-1.0, -13.0), (1.0, 9.0)),
+    ((7.0, 1.0), (8.0, -6.0)), ((1.0, -1.0), (3.0, 12.0)), ((9.0, 1.0), (12.0, 6.0)), ((-1.0, -9.0), (-1.0, 3.0)),
+    ((-13.0, -13.0), (-10.0, 5.0)), ((7.0, 7.0), (10.0, 12.0)), ((12.0, -5.0), (12.0, 9.0)), ((6.0, 3.0), (7.0, 11.0)),
+    ((5.0, -13.0), (6.0, 10.0)), ((2.0, -12.0), (2.0, 3.0)), ((3.0, 8.0), (4.0, -6.0)), ((2.0, 6.0), (12.0, -13.0)),
+    ((9.0, -12.0), (10.0, 3.0)), ((-8.0, 4.0), (-7.0, 9.0)), ((-11.0, 12.0), (-4.0, -6.0)), ((1.0, 12.0), (2.0, -8.0)),
+    ((6.0, -9.0), (7.0, -4.0)), ((2.0, 3.0), (3.0, -2.0)), ((6.0, 3.0), (11.0, 0.0)), ((3.0, -3.0), (8.0, -8.0)),
+    ((7.0, 8.0), (9.0, 3.0)), ((-11.0, -5.0), (-6.0, -4.0)), ((-10.0, 11.0), (-5.0, 10.0)), ((-5.0, -8.0), (-3.0, 12.0)),
+    ((-10.0, 5.0), (-9.0, 0.0)), ((8.0, -1.0), (12.0, -6.0)), ((4.0, -6.0), (6.0, -11.0)), ((-10.0, 12.0), (-8.0, 7.0)),
+    ((4.0, -2.0), (6.0, 7.0)), ((-2.0, 0.0), (-2.0, 12.0)), ((-5.0, -8.0), (-5.0, 2.0)), ((7.0, -6.0), (10.0, 12.0)),
+    ((-9.0, -13.0), (-8.0, -8.0)), ((-5.0, -13.0), (-5.0, -2.0)), ((8.0, -8.0), (9.0, -13.0)), ((-9.0, -11.0), (-9.0, 0.0)),
+    ((1.0, -8.0), (1.0, -2.0)), ((7.0, -4.0), (9.0, 1.0)), ((-2.0, 1.0), (-1.0, -4.0)), ((11.0, -6.0), (12.0, -11.0)),
+    ((-12.0, -9.0), (-6.0, 4.0)), ((3.0, 7.0), (7.0, 12.0)), ((5.0, 5.0), (10.0, 8.0)), ((0.0, -4.0), (2.0, 8.0)),
+    ((-9.0, 12.0), (-5.0, -13.0)), ((0.0, 7.0), (2.0, 12.0)), ((-1.0, 2.0), (1.0, 7.0)), ((5.0, 11.0), (7.0, -9.0)),
+    ((3.0, 5.0), (6.0, -8.0)), ((-13.0, -4.0), (-8.0, 9.0)), ((-5.0, 9.0), (-3.0, -3.0)), ((-4.0, -7.0), (-3.0, -12.0)),
+    ((6.0, 5.0), (8.0, 0.0)), ((-7.0, 6.0), (-6.0, 12.0)), ((-13.0, 6.0), (-5.0, -2.0)), ((1.0, -10.0), (3.0, 10.0)),
+    ((4.0, 1.0), (8.0, -4.0)), ((-2.0, -2.0), (2.0, -13.0)), ((2.0, -12.0), (12.0, 12.0)), ((-2.0, -13.0), (0.0, -6.0)),
+    ((4.0, 1.0), (9.0, 3.0)), ((-6.0, -10.0), (-3.0, -5.0)), ((-3.0, -13.0), (-1.0, 1.0)), ((7.0, 5.0), (12.0, -11.0)),
+    ((4.0, -2.0), (5.0, -7.0)), ((-13.0, 9.0), (-9.0, -5.0)), ((7.0, 1.0), (8.0, 6.0)), ((7.0, -8.0), (7.0, 6.0)),
+    ((-7.0, -4.0), (-7.0, 1.0)), ((-8.0, 11.0), (-7.0, -8.0)), ((-13.0, 6.0), (-12.0, -8.0)), ((2.0, 4.0), (3.0, 9.0)),
+    ((10.0, -5.0), (12.0, 3.0)), ((-6.0, -5.0), (-6.0, 7.0)), ((8.0, -3.0), (9.0, -8.0)), ((2.0, -12.0), (2.0, 8.0)),
+    ((-11.0, -2.0), (-10.0, 3.0)), ((-12.0, -13.0), (-7.0, -9.0)), ((-11.0, 0.0), (-10.0, -5.0)), ((5.0, -3.0), (11.0, 8.0)),
+    ((-2.0, -13.0), (-1.0, 12.0)), ((-1.0, -8.0), (0.0, 9.0)), ((-13.0, -11.0), (-12.0, -5.0)), ((-10.0, -2.0), (-10.0, 11.0)),
+    ((-3.0, 9.0), (-2.0, -13.0)), ((2.0, -3.0), (3.0, 2.0)), ((-9.0, -13.0), (-4.0, 0.0)), ((-4.0, 6.0), (-3.0, -10.0)),
+    ((-4.0, 12.0), (-2.0, -7.0)), ((-6.0, -11.0), (-4.0, 9.0)), ((6.0, -3.0), (6.0, 11.0)), ((-13.0, 11.0), (-5.0, 5.0)),
+    ((11.0, 11.0), (12.0, 6.0)), ((7.0, -5.0), (12.0, -2.0)), ((-1.0, 12.0), (0.0, 7.0)), ((-4.0, -8.0), (-3.0, -2.0)),
+    ((-7.0, 1.0), (-6.0, 7.0)), ((-13.0, -12.0), (-8.0, -13.0)), ((-7.0, -2.0), (-6.0, -8.0)), ((-8.0, 5.0), (-6.0, -9.0)),
+    ((-5.0, -1.0), (-4.0, 5.0)), ((-13.0, 7.0), (-8.0, 10.0)), ((1.0, 5.0), (5.0, -13.0)), ((1.0, 0.0), (10.0, -13.0)),
+    ((9.0, 12.0), (10.0, -1.0)), ((5.0, -8.0), (10.0, -9.0)), ((-1.0, 11.0), (1.0, -13.0)), ((-9.0, -3.0), (-6.0, 2.0)),
+    ((-1.0, -10.0), (1.0, 12.0)), ((-13.0, 1.0), (-8.0, -10.0)), ((8.0, -11.0), (10.0, -6.0)), ((2.0, -13.0), (3.0, -6.0)),
+    ((7.0, -13.0), (12.0, -9.0)), ((-10.0, -10.0), (-5.0, -7.0)), ((-10.0, -8.0), (-8.0, -13.0)), ((4.0, -6.0), (8.0, 5.0)),
+    ((3.0, 12.0), (8.0, -13.0)), ((-4.0, 2.0), (-3.0, -3.0)), ((5.0, -13.0), (10.0, -12.0)), ((4.0, -13.0), (5.0, -1.0)),
+    ((-9.0, 9.0), (-4.0, 3.0)), ((0.0, 3.0), (3.0, -9.0)), ((-12.0, 1.0), (-6.0, 1.0)), ((3.0, 2.0), (4.0, -8.0)),
+    ((-10.0, -10.0), (-10.0, 9.0)), ((8.0, -13.0), (12.0, 12.0)), ((-8.0, -12.0), (-6.0, -5.0)), ((2.0, 2.0), (3.0, 7.0)),
+    ((10.0, 6.0), (11.0, -8.0)), ((6.0, 8.0), (8.0, -12.0)), ((-7.0, 10.0), (-6.0, 5.0)), ((-3.0, -9.0), (-3.0, 9.0)),
+    ((-1.0, -13.0), (-1.0, 5.0)), ((-3.0, -7.0), (-3.0, 4.0)), ((-8.0, -2.0), (-8.0, 3.0)), ((4.0, 2.0), (12.0, 12.0)),
+    ((2.0, -5.0), (3.0, 11.0)), ((6.0, -9.0), (11.0, -13.0)), ((3.0, -1.0), (7.0, 12.0)), ((11.0, -1.0), (12.0, 4.0)),
+    ((-3.0, 0.0), (-3.0, 6.0)), ((4.0, -11.0), (4.0, 12.0)), ((2.0, -4.0), (2.0, 1.0)), ((-10.0, -6.0), (-8.0, 1.0)),
+    ((-13.0, 7.0), (-11.0, 1.0)), ((-13.0, 12.0), (-11.0, -13.0)), ((6.0, 0.0), (11.0, -13.0)), ((0.0, -1.0), (1.0, 4.0)),
+    ((-13.0, 3.0), (-9.0, -2.0)), ((-9.0, 8.0), (-6.0, -3.0)), ((-13.0, -6.0), (-8.0, -2.0)), ((5.0, -9.0), (8.0, 10.0)),
+    ((2.0, 7.0), (3.0, -9.0)), ((-1.0, -6.0), (-1.0, -1.0)), ((9.0, 5.0), (11.0, -2.0)), ((11.0, -3.0), (12.0, -8.0)),
+    ((3.0, 0.0), (3.0, 5.0)), ((-1.0, 4.0), (0.0, 10.0)), ((3.0, -6.0), (4.0, 5.0)), ((-13.0, 0.0), (-10.0, 5.0)),
+    ((5.0, 8.0), (12.0, 11.0)), ((8.0, 9.0), (9.0, -6.0)), ((7.0, -4.0), (8.0, -12.0)), ((-10.0, 4.0), (-10.0, 9.0)),
+    ((7.0, 3.0), (12.0, 4.0)), ((9.0, -7.0), (10.0, -2.0)), ((7.0, 0.0), (12.0, -2.0)), ((-1.0, -6.0), (0.0, -11.0)),
+];
+
+/// 早期版本使用的高斯随机模式
+///
+/// 其中包含重复和退化的点对，无法通过 [`validate_pattern`]，仅为兼容而保留。
+pub const OFFSETS:[SamplePair; 256] = [
+    ((1.0, -8.0), (-4.0, 1.0)), ((-2.0, -0.0), (2.0, 2.0)), ((3.0, -1.0), (-2.0, -1.0)), ((-5.0, -5.0), (1.0, -2.0)),
+    ((-2.0, 5.0), (5.0, 5.0)), ((-0.0, -1.0), (2.0, 1.0)), ((-1.0, -1.0), (-4.0, -12.0)), ((1.0, 1.0), (3.0, 3.0)),
+    ((-1.0, 1.0), (6.0, 1.0)), ((-1.0, 1.0), (-2.0, 7.0)), ((-1.0, -4.0), (3.0, 6.0)), ((4.0, -5.0), (6.0, -3.0)),
+    ((-11.0, 1.0), (3.0, 8.0)), ((-6.0, -1.0), (4.0, 1.0)), ((-0.0, -6.0), (1.0, -4.0)), ((-1.0, -4.0), (-8.0, 2.0)),
+    ((3.0, 4.0), (2.0, -3.0)), ((-1.0, -6.0), (-2.0, 4.0)), ((-4.0, -5.0), (3.0, 2.0)), ((-8.0, 2.0), (-7.0, 1.0)),
+    ((1.0, 9.0), (3.0, -4.0)), ((-7.0, 2.0), (-5.0, -2.0)), ((4.0, 2.0), (-7.0, 3.0)), ((3.0, -1.0), (1.0, 5.0)),
+    ((1.0, -9.0), (-2.0, 3.0)), ((0.0, -5.0), (-11.0, -1.0)), ((3.0, 0.0), (-5.0, 7.0)), ((-1.0, -0.0), (2.0, 1.0)),
+    ((2.0, -1.0), (2.0, 4.0)), ((6.0, -2.0), (2.0, 9.0)), ((3.0, -2.0), (-4.0, -7.0)), ((5.0, -1.0), (-2.0, 2.0)),
+    ((-4.0, -8.0), (-1.0, 2.0)), ((6.0, -1.0), (-3.0, 5.0)), ((-3.0, 2.0), (4.0, 7.0)), ((3.0, 7.0), (3.0, 3.0)),
+    ((-2.0, 5.0), (2.0, -4.0)), ((-3.0, 1.0), (9.0, 5.0)), ((-5.0, -3.0), (10.0, -6.0)), ((-1.0, 8.0), (-4.0, 6.0)),
+    ((7.0, -1.0), (-0.0, -3.0)), ((5.0, 7.0), (6.0, 2.0)), ((-5.0, 2.0), (-4.0, 2.0)), ((3.0, -0.0), (-1.0, 1.0)),
+    ((-1.0, 4.0), (1.0, 0.0)), ((3.0, -4.0), (2.0, 4.0)), ((5.0, -1.0), (-6.0, -4.0)), ((-5.0, 2.0), (1.0, 1.0)),
+    ((-5.0, -1.0), (1.0, -1.0)), ((2.0, 2.0), (6.0, -10.0)), ((-6.0, 2.0), (-9.0, -1.0)), ((4.0, -1.0), (2.0, 10.0)),
+    ((2.0, 0.0), (-6.0, -7.0)), ((7.0, -6.0), (-2.0, 7.0)), ((2.0, 7.0), (-6.0, -3.0)), ((-0.0, 2.0), (-4.0, -9.0)),
+    ((2.0, 7.0), (2.0, 0.0)), ((3.0, -9.0), (-2.0, -2.0)), ((2.0, -1.0), (-0.0, -2.0)), ((0.0, -3.0), (4.0, -3.0)),
+    ((2.0, 8.0), (-2.0, -4.0)), ((5.0, -4.0), (8.0, 3.0)), ((4.0, 2.0), (4.0, -2.0)), ((9.0, 3.0), (-0.0, 2.0)),
+    ((-2.0, -4.0), (-2.0, 3.0)), ((4.0, -0.0), (3.0, 0.0)), ((1.0, -7.0), (5.0, -3.0)), ((-6.0, -5.0), (-2.0, -0.0)),
+    ((-4.0, -5.0), (5.0, 4.0)), ((-12.0, 7.0), (0.0, -7.0)), ((2.0, -4.0), (7.0, -2.0)), ((-4.0, 4.0), (3.0, 2.0)),
+    ((-5.0, 5.0), (3.0, 3.0)), ((2.0, 1.0), (3.0, 6.0)), ((3.0, 1.0), (1.0, 6.0)), ((-3.0, 0.0), (0.0, 3.0)),
+    ((3.0, 4.0), (-0.0, -3.0)), ((0.0, -2.0), (2.0, -0.0)), ((1.0, -1.0), (1.0, 1.0)), ((-2.0, 11.0), (0.0, 1.0)),
+    ((-1.0, 0.0), (-3.0, -2.0)), ((-3.0, 4.0), (-12.0, 10.0)), ((7.0, 4.0), (1.0, -2.0)), ((-9.0, 5.0), (-2.0, 3.0)),
+    ((1.0, 2.0), (-5.0, -1.0)), ((6.0, -4.0), (2.0, 5.0)), ((4.0, -3.0), (3.0, -1.0)), ((-6.0, 1.0), (3.0, -4.0)),
+    ((-7.0, -3.0), (0.0, 0.0)), ((2.0, -2.0), (0.0, -1.0)), ((-1.0, 4.0), (-1.0, 3.0)), ((-6.0, 0.0), (3.0, 0.0)),
+    ((0.0, 6.0), (1.0, -5.0)), ((-1.0, -3.0), (0.0, 5.0)), ((2.0, -2.0), (3.0, -1.0)), ((3.0, -2.0), (0.0, 1.0)),
+    ((2.0, -0.0), (5.0, 4.0)), ((-0.0, -1.0), (-2.0, 1.0)), ((4.0, 2.0), (-7.0, -6.0)), ((2.0, 3.0), (-5.0, 1.0)),
+    ((2.0, -1.0), (5.0, 1.0)), ((1.0, -1.0), (-1.0, 0.0)), ((1.0, 6.0), (-9.0, -2.0)), ((7.0, -4.0), (2.0, -3.0)),
+    ((4.0, -2.0), (-0.0, -3.0)), ((3.0, -2.0), (-5.0, -2.0)), ((1.0, -6.0), (2.0, -5.0)), ((-7.0, 3.0), (4.0, 1.0)),
+    ((-3.0, 2.0), (-6.0, 2.0)), ((-6.0, 5.0), (1.0, -2.0)), ((1.0, 7.0), (-9.0, -5.0)), ((-4.0, 1.0), (-7.0, 6.0)),
+    ((0.0, -3.0), (-2.0, -1.0)), ((5.0, 2.0), (-4.0, -1.0)), ((-4.0, -0.0), (1.0, 7.0)), ((3.0, -3.0), (3.0, -0.0)),
+    ((2.0, -1.0), (-7.0, -6.0)), ((-1.0, 0.0), (8.0, -4.0)), ((-1.0, 5.0), (3.0, -0.0)), ((-2.0, -1.0), (-1.0, -2.0)),
+    ((0.0, -6.0), (-2.0, -4.0)), ((-5.0, 0.0), (5.0, 2.0)), ((-2.0, -1.0), (4.0, 6.0)), ((4.0, -2.0), (-2.0, -3.0)),
+    ((0.0, -2.0), (-4.0, 5.0)), ((1.0, -1.0), (10.0, 8.0)), ((-1.0, 2.0), (7.0, 4.0)), ((6.0, -3.0), (3.0, 1.0)),
+    ((7.0, -0.0), (-7.0, -1.0)), ((3.0, 3.0), (-6.0, 4.0)), ((-3.0, 1.0), (2.0, 2.0)), ((2.0, -3.0), (-5.0, -2.0)),
+    ((10.0, 4.0), (-1.0, 7.0)), ((-3.0, 1.0), (-0.0, 4.0)), ((1.0, -2.0), (0.0, 2.0)), ((-3.0, -0.0), (-0.0, 1.0)),
+    ((-2.0, 4.0), (-2.0, -1.0)), ((-0.0, 3.0), (-0.0, -1.0)), ((5.0, -4.0), (-1.0, -0.0)), ((-4.0, 1.0), (8.0, 6.0)),
+    ((-10.0, -1.0), (4.0, -3.0)), ((-1.0, -1.0), (-7.0, 0.0)), ((3.0, -0.0), (-7.0, -3.0)), ((-2.0, 5.0), (1.0, -6.0)),
+    ((8.0, -1.0), (2.0, 1.0)), ((1.0, 2.0), (-1.0, 0.0)), ((-8.0, 5.0), (4.0, -6.0)), ((3.0, 0.0), (7.0, -3.0)),
+    ((0.0, -3.0), (-5.0, 1.0)), ((3.0, 1.0), (-3.0, -1.0)), ((1.0, -7.0), (-2.0, -6.0)), ((-6.0, -5.0), (-0.0, 3.0)),
+    ((-3.0, -1.0), (2.0, 6.0)), ((6.0, 3.0), (-4.0, 4.0)), ((-0.0, -1.0), (-3.0, 5.0)), ((5.0, 7.0), (2.0, 6.0)),
+    ((6.0, -5.0), (-8.0, 1.0)), ((0.0, -2.0), (1.0, -5.0)), ((-6.0, -1.0), (3.0, -1.0)), ((-6.0, -6.0), (-3.0, 7.0)),
+    ((-10.0, 2.0), (-5.0, -5.0)), ((-5.0, 4.0), (1.0, 0.0)), ((-7.0, -0.0), (-3.0, 3.0)), ((3.0, 5.0), (3.0, -8.0)),
+    ((0.0, 6.0), (4.0, -4.0)), ((-0.0, -3.0), (1.0, 3.0)), ((-5.0, -0.0), (6.0, 3.0)), ((2.0, 1.0), (3.0, 1.0)),
+    ((2.0, 7.0), (7.0, -2.0)), ((-3.0, 10.0), (-3.0, 2.0)), ((0.0, -1.0), (5.0, -4.0)), ((6.0, -4.0), (1.0, -3.0)),
+    ((-2.0, 3.0), (-0.0, 5.0)), ((-6.0, 4.0), (-5.0, -1.0)), ((-1.0, 0.0), (7.0, 2.0)), ((-2.0, 5.0), (1.0, 2.0)),
+    ((-2.0, -3.0), (-7.0, 7.0)), ((5.0, -1.0), (-2.0, 0.0)), ((0.0, 2.0), (1.0, -4.0)), ((-3.0, -2.0), (1.0, 3.0)),
+    ((4.0, -10.0), (-6.0, 7.0)), ((3.0, 0.0), (-1.0, 1.0)), ((-1.0, -3.0), (-1.0, 5.0)), ((-3.0, 3.0), (6.0, 1.0)),
+    ((-1.0, -2.0), (-0.0, -5.0)), ((-6.0, 8.0), (-9.0, -1.0)), ((2.0, -8.0), (2.0, -9.0)), ((-1.0, -0.0), (-2.0, 7.0)),
+    ((-3.0, -6.0), (-1.0, -4.0)), ((-4.0, -1.0), (3.0, 2.0)), ((1.0, -5.0), (-2.0, -4.0)), ((1.0, 1.0), (10.0, 2.0)),
+    ((8.0, 3.0), (-3.0, 4.0)), ((1.0, 3.0), (-1.0, 8.0)), ((-1.0, 1.0), (-3.0, -2.0)), ((4.0, -2.0), (2.0, 2.0)),
+    ((-1.0, 1.0), (-6.0, -4.0)), ((-4.0, -3.0), (8.0, -3.0)), ((2.0, -1.0), (1.0, 1.0)), ((3.0, -2.0), (7.0, 0.0)),
+    ((0.0, -2.0), (-3.0, -2.0)), ((4.0, 1.0), (-4.0, -1.0)), ((-1.0, -2.0), (-6.0, 8.0)), ((-1.0, 3.0), (-1.0, 3.0)),
+    ((9.0, -1.0), (-3.0, -1.0)), ((-0.0, 3.0), (-2.0, 1.0)), ((-3.0, -3.0), (-2.0, 0.0)), ((1.0, 2.0), (6.0, -1.0)),
+    ((-5.0, -0.0), (-1.0, 7.0)), ((3.0, -6.0), (-3.0, -1.0)), ((-4.0, -1.0), (-1.0, -7.0)), ((4.0, 4.0), (1.0, -4.0)),
+    ((4.0, -7.0), (-1.0, 8.0)), ((-2.0, 0.0), (4.0, 3.0)), ((8.0, -4.0), (2.0, -5.0)), ((-2.0, 5.0), (0.0, -4.0)),
+    ((1.0, -3.0), (4.0, -4.0)), ((-3.0, -2.0), (2.0, 2.0)), ((5.0, 1.0), (3.0, 4.0)), ((-2.0, -1.0), (-0.0, -3.0)),
+    ((-3.0, -2.0), (4.0, -6.0)), ((-1.0, 2.0), (2.0, 8.0)), ((5.0, 6.0), (-1.0, -5.0)), ((-3.0, -2.0), (-1.0, -5.0)),
+    ((-1.0, 1.0), (5.0, 3.0)), ((0.0, 1.0), (-2.0, 1.0)), ((-4.0, -4.0), (1.0, 3.0)), ((-1.0, -6.0), (4.0, 3.0)),
+    ((4.0, 3.0), (8.0, 6.0)), ((-0.0, -5.0), (-4.0, -2.0)), ((3.0, 0.0), (2.0, 4.0)), ((1.0, -2.0), (1.0, 2.0)),
+    ((-5.0, 1.0), (-2.0, 5.0)), ((-3.0, -3.0), (-1.0, 2.0)), ((-0.0, 9.0), (3.0, 1.0)), ((-3.0, -2.0), (5.0, -3.0)),
+    ((1.0, 3.0), (1.0, 0.0)), ((-4.0, 0.0), (6.0, -2.0)), ((-4.0, 1.0), (-1.0, -5.0)), ((5.0, -6.0), (-6.0, -7.0)),
+    ((7.0, -8.0), (5.0, 8.0)), ((4.0, 2.0), (3.0, -1.0)), ((5.0, -0.0), (-3.0, 8.0)), ((-2.0, -1.0), (-7.0, 3.0)),
+    ((-2.0, -8.0), (2.0, 2.0)), ((1.0, 2.0), (1.0, 5.0)), ((1.0, -2.0), (1.0, 2.0)), ((5.0, 9.0), (-1.0, -0.0)),
+    ((2.0, 2.0), (1.0, 2.0)), ((-1.0, -3.0), (2.0, -3.0)), ((-1.0, -3.0), (-2.0, 1.0)), ((3.0, 11.0), (6.0, 8.0)),
+    ((6.0, 3.0), (2.0, 3.0)), ((5.0, 0.0), (-2.0, 6.0)), ((-0.0, -3.0), (-4.0, -0.0)), ((-2.0, -2.0), (2.0, 1.0)),
+];
+
+/// 表示BRIEF采样模式的类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PatternType {
+    Learned, // ORB论文中学习得到的rBRIEF模式
+    Uniform { seed: u64 }, // 图像块内均匀随机采样（BRIEF G I）
+    Gaussian { seed: u64 }, // 以中心为均值的各向同性高斯随机采样（BRIEF G II）
+    Isotropic // 中心点与同心圆上均匀分布的点比较（BRIEF G V）
+}
+
+/// 表示采样模式校验失败的原因，通过 `OrbError::Pattern` 返回
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternError {
+    Empty, // 模式中没有点对
+    TooLong { length: usize, max: usize }, // 点对数量超过模式可提供或描述子可容纳的数量
+    OutsideSquare { index: usize }, // 点对超出以中心为原点、边长为图像块边长的正方形
+    Degenerate { index: usize }, // 点对的两个点相同
+    Duplicate { first: usize, second: usize }, // 两个点对重复（包括交换顺序后重复）
+    Exhausted { generated: usize, requested: usize } // 随机生成器在尝试次数内产生的不重复点对不足
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Empty => write!(f, "BRIEF pattern is empty"),
+            PatternError::TooLong { length, max } =>
+                write!(f, "BRIEF pattern length {} exceeds the maximum of {}", length, max),
+            PatternError::OutsideSquare { index } =>
+                write!(f, "BRIEF pair {} lies outside the square sampling patch", index),
+            PatternError::Degenerate { index } =>
+                write!(f, "BRIEF pair {} compares a point with itself", index),
+            PatternError::Duplicate { first, second } =>
+                write!(f, "BRIEF pairs {} and {} are duplicates", first, second),
+            PatternError::Exhausted { generated, requested } =>
+                write!(f, "BRIEF pattern generator produced only {} unique pairs out of {} requested", generated, requested)
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// 经过校验的BRIEF采样模式
+#[derive(Debug, Clone, PartialEq)]
+pub struct BriefPattern {
+    pairs: Vec<SamplePair>, // 点对
+    patch_size: u32 // 图像块边长
+}
+
+impl Default for BriefPattern {
+    fn default() -> Self {
+        BriefPattern {
+            pairs: LEARNED_PATTERN.to_vec(),
+            patch_size: PATCH_SIZE
+        }
+    }
+}
+
+impl BriefPattern {
+    /// 根据模式类型生成采样模式
+    ///
+    /// # 参数
+    ///
+    /// * `pattern_type` - 模式类型
+    /// * `length` - 点对数量，默认为256，最多为256
+    ///
+    /// # 返回值
+    ///
+    /// 返回31x31图像块上的采样模式；长度超过上限时返回 `PatternError::TooLong`，
+    /// 随机模式在尝试次数内生成的不重复点对不足时返回 `PatternError::Exhausted`
+    pub fn new(pattern_type: PatternType, length: Option<usize>) -> Result<BriefPattern, OrbError> {
+        let length = length.unwrap_or(LEARNED_PATTERN_LENGTH);
+        if length == 0 {
+            return Err(PatternError::Empty.into());
+        }
+        if length > MAX_PATTERN_LENGTH {
+            return Err(PatternError::TooLong { length, max: MAX_PATTERN_LENGTH }.into());
+        }
+
+        let pairs = match pattern_type {
+            PatternType::Learned => {
+                if length > LEARNED_PATTERN_LENGTH {
+                    return Err(PatternError::TooLong { length, max: LEARNED_PATTERN_LENGTH }.into());
+                }
+                LEARNED_PATTERN[..length].to_vec()
+            },
+            PatternType::Uniform { seed } => random_pairs(length, seed, |rng| {
+                let half = HALF_PATCH_SIZE as f32;
+                (rng.gen_range(-half..=half), rng.gen_range(-half..=half))
+            })?,
+            PatternType::Gaussian { seed } => random_pairs(length, seed, |rng| {
+                // 方差取 S^2/25，与BRIEF论文一致
+                let sigma = PATCH_SIZE as f32 / 5.0;
+                gaussian_point(rng, sigma)
+            })?,
+            PatternType::Isotropic => isotropic_pairs(length)
+        };
+
+        BriefPattern::from_pairs(pairs, None)
+    }
+
+    /// 使用自定义点对创建采样模式
+    ///
+    /// # 参数
+    ///
+    /// * `pairs` - 点对，坐标相对于关键点中心
+    /// * `patch_size` - 图像块边长，默认为31
+    ///
+    /// # 返回值
+    ///
+    /// 校验通过时返回采样模式，否则返回校验失败的原因
+    pub fn from_pairs(pairs: Vec<SamplePair>, patch_size: Option<u32>) -> Result<BriefPattern, OrbError> {
+        let patch_size = patch_size.unwrap_or(PATCH_SIZE);
+        validate_pattern(&pairs, patch_size)?;
+
+        Ok(BriefPattern { pairs, patch_size })
+    }
+
+    /// 将采样模式缩放到另一个图像块边长
+    ///
+    /// # 参数
+    ///
+    /// * `patch_size` - 新的图像块边长
+    ///
+    /// # 返回值
+    ///
+    /// 返回坐标按两个图像块半径之比缩放后的模式，缩放后出现退化或重复点对时返回错误
+    pub fn rescaled(&self, patch_size: u32) -> Result<BriefPattern, OrbError> {
+        let ratio = (patch_size / 2) as f32 / (self.patch_size / 2).max(1) as f32;
+        let pairs = self.pairs.iter()
+            .map(|&((x0, y0), (x1, y1))| ((x0 * ratio, y0 * ratio), (x1 * ratio, y1 * ratio)))
+            .collect();
+
+        BriefPattern::from_pairs(pairs, Some(patch_size))
+    }
+
+    /// 获取点对
+    pub fn pairs(&self) -> &[SamplePair] {
+        &self.pairs
+    }
+
+    /// 获取点对数量，即描述子的位数
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// 判断模式是否为空
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// 获取图像块边长
+    pub fn patch_size(&self) -> u32 {
+        self.patch_size
+    }
+}
+
+/// 校验采样模式
+///
+/// # 参数
+///
+/// * `pairs` - 点对
+/// * `patch_size` - 图像块边长
+///
+/// # 返回值
+///
+/// 点对数量不超过描述子位数、所有点都位于图像块的正方形内、没有退化或重复的点对时返回 `Ok(())`
+///
+/// 这里有意检查正方形 `|x|, |y| <= patch_size / 2` 而不是内切圆：ORB学习得到的模式本身就有点位于
+/// 半径15的圆之外（最远约18.4个像素）。旋转后的采样范围由 `SteeredPattern` 按点到中心的实际最大距离计算，
+/// 边界过滤据此进行，因此这些点不会越出图像
+pub fn validate_pattern(pairs: &[SamplePair], patch_size: u32) -> Result<(), OrbError> {
+    if pairs.is_empty() {
+        return Err(PatternError::Empty.into());
+    }
+    if pairs.len() > MAX_PATTERN_LENGTH {
+        return Err(PatternError::TooLong { length: pairs.len(), max: MAX_PATTERN_LENGTH }.into());
+    }
+
+    let half = (patch_size / 2) as f32;
+    let inside = |(x, y): (f32, f32)| x.is_finite() && y.is_finite() && x.abs() <= half && y.abs() <= half;
+
+    for (i, &(p0, p1)) in pairs.iter().enumerate() {
+        if !inside(p0) || !inside(p1) {
+            return Err(PatternError::OutsideSquare { index: i }.into());
+        }
+        if p0 == p1 {
+            return Err(PatternError::Degenerate { index: i }.into());
+        }
+        for (j, &(q0, q1)) in pairs.iter().enumerate().take(i) {
+            if (p0 == q0 && p1 == q1) || (p0 == q1 && p1 == q0) {
+                return Err(PatternError::Duplicate { first: j, second: i }.into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 使用给定的采样函数生成不重复的整数点对
+fn random_pairs<F>(length: usize, seed: u64, mut sample: F) -> Result<Vec<SamplePair>, PatternError>
+where
+    F: FnMut(&mut StdRng) -> (f32, f32)
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let half = HALF_PATCH_SIZE as f32;
+    let mut pairs:Vec<SamplePair> = Vec::with_capacity(length);
+
+    for _ in 0..MAX_SAMPLE_ATTEMPTS {
+        if pairs.len() == length {
+            break;
+        }
+
+        let (x0, y0) = sample(&mut rng);
+        let (x1, y1) = sample(&mut rng);
+        let pair = ((x0.round(), y0.round()), (x1.round(), y1.round()));
+        let ((x0, y0), (x1, y1)) = pair;
+
+        if x0.abs() > half || y0.abs() > half || x1.abs() > half || y1.abs() > half || pair.0 == pair.1 {
+            continue;
+        }
+        if pairs.iter().any(|&(q0, q1)| (q0, q1) == pair || (q1, q0) == pair) {
+            continue;
+        }
+
+        pairs.push(pair);
+    }
+
+    if pairs.len() < length {
+        return Err(PatternError::Exhausted { generated: pairs.len(), requested: length });
+    }
+
+    Ok(pairs)
+}
+
+/// 使用Box-Muller变换采样二维高斯点
+fn gaussian_point(rng: &mut StdRng, sigma: f32) -> (f32, f32) {
+    let u1:f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2:f32 = rng.gen();
+    let r = sigma * (-2.0 * u1.ln()).sqrt();
+    (r * (2.0 * PI * u2).cos(), r * (2.0 * PI * u2).sin())
+}
+
+/// 生成各向同性模式：第一个点为中心，第二个点均匀分布在同心圆上
+fn isotropic_pairs(length: usize) -> Vec<SamplePair> {
+    // 每个圆上的点数与半径成正比
+    let total_radius:f32 = ISOTROPIC_RADII.iter().sum();
+    let mut counts:Vec<usize> = ISOTROPIC_RADII.iter()
+        .map(|r| (length as f32 * r / total_radius).floor() as usize)
+        .collect();
+    let assigned:usize = counts.iter().sum();
+    let last = counts.len() - 1;
+    counts[last] += length - assigned;
+
+    let mut pairs:Vec<SamplePair> = Vec::with_capacity(length);
+    for (ring, (&radius, &count)) in ISOTROPIC_RADII.iter().zip(counts.iter()).enumerate() {
+        // 相邻圆之间错开半个角度步长
+        let step = 2.0 * PI / count.max(1) as f32;
+        let phase = if ring % 2 == 0 { 0.0 } else { step / 2.0 };
+        for k in 0..count {
+            let angle = phase + step * k as f32;
+            pairs.push(((0.0, 0.0), (radius * angle.cos(), radius * angle.sin())));
+        }
+    }
+
+    pairs
+}
@@ -12,9 +12,37 @@
 
 use bitvector::BitVector;
 
+use crate::error;
+use error::OrbError;
+
 pub type Point = (i32, i32);
+pub type PointF = (f32, f32); // 亚像素精度的坐标
 pub type IndexMatch = (usize, usize);
 
+/// 表示一对匹配的特征
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DMatch {
+    pub query_idx: usize, // 查询集合中的索引
+    pub train_idx: usize, // 训练集合中的索引
+    pub distance: usize // 两个特征之间的距离
+}
+
+/// 启用 `parallel` 特性时等价于 `Sync`，否则对所有类型成立
+///
+/// 只有并行实现需要在线程间共享的泛型参数使用该约束，串行构建不要求 `Sync`
+#[cfg(feature = "parallel")]
+pub trait MaybeSync: Sync {}
+#[cfg(feature = "parallel")]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+/// 启用 `parallel` 特性时等价于 `Sync`，否则对所有类型成立
+///
+/// 只有并行实现需要在线程间共享的泛型参数使用该约束，串行构建不要求 `Sync`
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "parallel"))]
+impl<T: ?Sized> MaybeSync for T {}
+
 /// 定义一个可匹配的特征，要求实现计算距离的方法
 pub trait Matchable {
     /// 计算两个特征之间的距离
@@ -29,94 +57,831 @@ pub trait Matchable {
     fn distance(&self, other: &Self) -> usize;
 }
 
+/// 表示一对一匹配的分配方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AssignmentMode {
+    #[default]
+    Optimal, // 匈牙利算法求总距离最小的分配，O(n^3)
+    Greedy // 按第一个向量的顺序依次取最近的未匹配特征，O(n^2)，结果依赖输入顺序
+}
+
 /// 匹配两个向量中的索引
 ///
+/// 两个向量的长度可以不同，较长一侧多出的特征不会被匹配。
+///
 /// # 参数
 ///
 /// * `vec1` - 第一个向量
 /// * `vec2` - 第二个向量
+/// * `mode` - 分配方式，默认为最优分配
+/// * `max_cost` - 单个匹配允许的最大距离。最优分配时，每个未匹配的特征计为该代价，
+///   因此只有距离不超过 `max_cost` 的配对会出现在结果中
 ///
 /// # 返回值
 ///
-/// 返回匹配的索引对的向量
-pub fn match_indices<T>(vec1: &Vec<T>, vec2: &Vec<T>) -> Vec<IndexMatch>
+/// 返回按 `vec1` 索引排列的一对一匹配及其距离
+pub fn match_indices<T>(vec1: &[T], vec2: &[T], mode: Option<AssignmentMode>, max_cost: Option<usize>) -> Vec<DMatch>
 where
     T: Matchable
 {
-    // assert_eq!(vec1.len(), vec2.len());
-    if vec1.len() != vec2.len(){
-        let nil : Vec<IndexMatch> = vec![];
-        return nil;
-    }
+    let mut matches = match mode.unwrap_or_default() {
+        AssignmentMode::Optimal => optimal_assignment(vec1, vec2, max_cost),
+        AssignmentMode::Greedy => greedy_assignment(vec1, vec2, max_cost)
+    };
 
+    matches.sort_by_key(|m| m.query_idx);
+    matches
+}
+
+/// 按 `vec1` 的顺序贪心分配
+fn greedy_assignment<T>(vec1: &[T], vec2: &[T], max_cost: Option<usize>) -> Vec<DMatch>
+where
+    T: Matchable
+{
     let mut index_vec = vec![];
-    let len = vec1.len();
-    let mut matched_indices = BitVector::new(len);
-
-    for i in 0..len {
-        let mut min_dist:usize = usize::MAX;
-        let mut matched_index:usize = 0;
-        for j in 0..len {
-            if matched_indices.contains(j) { 
+    let mut matched_indices = BitVector::new(vec2.len());
+
+    for (i, a) in vec1.iter().enumerate() {
+        let mut best:Option<(usize, usize)> = None;
+        for (j, b) in vec2.iter().enumerate() {
+            if matched_indices.contains(j) {
                 continue
             }
 
-            let dist = vec1[i].distance(&vec2[j]);
-            if dist < min_dist {
-                min_dist = dist;
-                matched_index = j;
+            let dist = a.distance(b);
+            if max_cost.is_some_and(|max| dist > max) {
+                continue
+            }
+            if best.is_none_or(|(min_dist, _)| dist < min_dist) {
+                best = Some((dist, j));
             }
         }
 
-        index_vec.push((i as usize, matched_index as usize));
-        matched_indices.insert(matched_index);
+        if let Some((distance, j)) = best {
+            index_vec.push(DMatch { query_idx: i, train_idx: j, distance });
+            matched_indices.insert(j);
+        }
     }
 
     index_vec
 }
 
-/// 自适应非最大抑制
+/// 使用匈牙利算法求总距离最小的分配
+fn optimal_assignment<T>(vec1: &[T], vec2: &[T], max_cost: Option<usize>) -> Vec<DMatch>
+where
+    T: Matchable
+{
+    let (n, m) = (vec1.len(), vec2.len());
+    if n == 0 || m == 0 {
+        return vec![];
+    }
+
+    let distances:Vec<usize> = vec1.iter()
+        .flat_map(|a| vec2.iter().map(move |b| a.distance(b)))
+        .collect();
+    let distance = |i: usize, j: usize| distances[i * m + j];
+
+    let pairs:Vec<(usize, usize)> = match max_cost {
+        // 为每一行增加一个代价为 `max_cost` 的虚拟列，表示该行不匹配
+        Some(cap) => {
+            let cap = cap as i64;
+            hungarian(n, m + n, |i, j| if j < m { (distance(i, j) as i64).min(cap + 1) } else { cap })
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, j)| j.filter(|&j| j < m).map(|j| (i, j)))
+                .filter(|&(i, j)| distance(i, j) as i64 <= cap)
+                .collect()
+        },
+        None if n <= m => hungarian(n, m, |i, j| distance(i, j) as i64)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, j)| j.map(|j| (i, j)))
+            .collect(),
+        None => hungarian(m, n, |j, i| distance(i, j) as i64)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(j, i)| i.map(|i| (i, j)))
+            .collect()
+    };
+
+    pairs.into_iter()
+        .map(|(i, j)| DMatch { query_idx: i, train_idx: j, distance: distance(i, j) })
+        .collect()
+}
+
+/// 求解 `rows x cols` 代价矩阵的最小代价分配（带势函数的匈牙利算法），要求 `rows <= cols`
+///
+/// # 返回值
+///
+/// 返回每一行分配到的列
+fn hungarian<F>(rows: usize, cols: usize, cost: F) -> Vec<Option<usize>>
+where
+    F: Fn(usize, usize) -> i64
+{
+    debug_assert!(rows <= cols);
+
+    // 下标从1开始，第0列作为哨兵
+    let mut u = vec![0i64; rows + 1];
+    let mut v = vec![0i64; cols + 1];
+    let mut p = vec![0usize; cols + 1];
+    let mut way = vec![0usize; cols + 1];
+
+    for i in 1..=rows {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![i64::MAX; cols + 1];
+        let mut used = vec![false; cols + 1];
+
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = i64::MAX;
+            let mut j1 = 0;
+
+            for j in 1..=cols {
+                if used[j] {
+                    continue;
+                }
+                let current = cost(i0 - 1, j - 1) - u[i0] - v[j];
+                if current < minv[j] {
+                    minv[j] = current;
+                    way[j] = j0;
+                }
+                if minv[j] < delta {
+                    delta = minv[j];
+                    j1 = j;
+                }
+            }
+
+            for j in 0..=cols {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+
+        // 沿增广路径更新分配
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assignment = vec![None; rows];
+    for j in 1..=cols {
+        if p[j] != 0 {
+            assignment[p[j] - 1] = Some(j - 1);
+        }
+    }
+
+    assignment
+}
+
+//
+// Adaptive Non-Maximal Suppression
+//
+
+// 常量
+const DEFAULT_C_ROBUST:f32 = 0.9; // Brown等人建议的鲁棒系数
+const DEFAULT_SUPPRESSION_TOLERANCE:f32 = 0.1; // 二分搜索可接受的特征数量相对误差
+
+/// 定义可参与空间抑制的特征
+pub trait Suppressible: Copy {
+    /// 获取特征的位置
+    fn location(&self) -> Point;
+
+    /// 获取特征的响应强度，越大越好
+    fn response(&self) -> f32;
+
+    /// 写入特征的抑制半径
+    fn set_nms_dist(&mut self, dist: usize);
+}
+
+/// 按响应强度从高到低排序特征的索引，响应相同时保持输入顺序
+fn indices_by_response<T: Suppressible>(vec: &[T]) -> Vec<usize> {
+    let mut order:Vec<usize> = (0..vec.len()).collect();
+    order.sort_by(|&a, &b| vec[b].response().total_cmp(&vec[a].response()));
+    order
+}
+
+fn squared_distance(a: Point, b: Point) -> i64 {
+    let (dx, dy) = ((a.0 - b.0) as i64, (a.1 - b.1) as i64);
+    dx * dx + dy * dy
+}
+
+/// 自适应非最大抑制（Brown等人，2005）
+///
+/// 每个特征的抑制半径是它到最近的、响应足够强的特征的距离，保留抑制半径最大的 `n` 个特征。
+/// 响应最强的特征的抑制半径为 `usize::MAX`。
+///
+/// 响应足够强指 `response < other - (1 - c_robust) * |other|`，即要求对方至少强出其绝对值的 `1 - c_robust` 倍。
+/// 响应为正时等价于论文中的 `response < c_robust * other`；Harris响应可能为负，此时论文的写法会让同样弱的邻居
+/// 抑制该特征，而这里仍然要求对方更强。
+///
+/// 按响应排序后，满足条件的特征恰好是排序结果的一个前缀。按响应顺序处理特征，把新进入前缀的特征插入均匀网格，
+/// 再从所在单元格向外逐圈查找最近邻，总时间复杂度约为 `O(n log n)`。
 ///
 /// # 参数
 ///
 /// * `vec` - 输入的特征向量
 /// * `n` - 需要保留的最大特征数
+/// * `c_robust` - 鲁棒系数，默认为0.9
 ///
 /// # 返回值
 ///
-/// 返回抑制后的特征向量
-pub fn adaptive_nonmax_suppression<T>(vec: &mut Vec<T>, n: usize) -> Vec<T> 
+/// 返回 `min(n, vec.len())` 个特征，按抑制半径从大到小排列，并写入各自的抑制半径；
+/// `c_robust` 不在 `(0, 1]` 内时返回 `OrbError::InvalidParameter`
+pub fn adaptive_nonmax_suppression<T>(vec: &[T], n: usize, c_robust: Option<f32>) -> Result<Vec<T>, OrbError>
 where
-    T: Matchable,
-    T: Copy
+    T: Suppressible
 {
-    if n>vec.len(){
-        let nil : Vec<T> = vec![];
-        return nil;
+    let c_robust = c_robust.unwrap_or(DEFAULT_C_ROBUST);
+    if !(c_robust > 0.0 && c_robust <= 1.0) {
+        return Err(OrbError::invalid_parameter("c_robust", format!("must be in (0, 1], got {}", c_robust)));
+    }
+    let order = indices_by_response(vec);
+    let points:Vec<Point> = vec.iter().map(|k| k.location()).collect();
+    let stronger_than = |other: f32| other - (1.0 - c_robust) * other.abs();
+
+    // 前缀中的特征都已插入网格
+    let mut grid = PointGrid::new(&points);
+    let mut prefix = 0;
+    let mut radii:Vec<(usize, i64)> = Vec::with_capacity(vec.len());
+    for (rank, &i) in order.iter().enumerate() {
+        let response = vec[i].response();
+        while prefix < order.len() && response < stronger_than(vec[order[prefix]].response()) {
+            grid.insert(points[order[prefix]]);
+            prefix += 1;
+        }
+
+        radii.push((rank, grid.nearest_squared_distance(points[i])));
+    }
+
+    // 半径相同时响应更强的特征优先
+    radii.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    Ok(radii.into_iter()
+        .take(n)
+        .map(|(rank, dist)| {
+            let mut keypoint = vec[order[rank]];
+            let radius = if dist == i64::MAX { usize::MAX } else { (dist as f64).sqrt() as usize };
+            keypoint.set_nms_dist(radius);
+            keypoint
+        })
+        .collect())
+}
+
+/// 按固定边长划分的点网格，支持逐个插入和最近邻查询
+struct PointGrid {
+    origin: Point, // 网格左上角
+    cell: i32, // 单元格边长
+    cols: i32, // 列数
+    rows: i32, // 行数
+    cells: Vec<Vec<Point>>, // 每个单元格中已插入的点
+    len: usize // 已插入的点数
+}
+
+impl PointGrid {
+    /// 创建覆盖所有点的空网格，单元格边长使每个单元格平均约有一个点
+    fn new(points: &[Point]) -> PointGrid {
+        let (min_x, min_y, max_x, max_y) = points.iter().fold((i32::MAX, i32::MAX, i32::MIN, i32::MIN), |(a, b, c, d), &(x, y)| {
+            (a.min(x), b.min(y), c.max(x), d.max(y))
+        });
+        if points.is_empty() {
+            return PointGrid { origin: (0, 0), cell: 1, cols: 1, rows: 1, cells: vec![vec![]], len: 0 };
+        }
+
+        let area = (max_x - min_x + 1) as f64 * (max_y - min_y + 1) as f64;
+        let cell = ((area / points.len() as f64).sqrt().ceil() as i32).max(1);
+        let (cols, rows) = ((max_x - min_x) / cell + 1, (max_y - min_y) / cell + 1);
+        PointGrid { origin: (min_x, min_y), cell, cols, rows, cells: vec![vec![]; (cols * rows) as usize], len: 0 }
+    }
+
+    /// 点所在的单元格
+    fn cell_of(&self, (x, y): Point) -> (i32, i32) {
+        ((x - self.origin.0) / self.cell, (y - self.origin.1) / self.cell)
     }
-    // assert!(n <= vec.len());
 
-    let mut maximal_keypoints:Vec<T> = vec![];
-    for i in 1..vec.len() - 1 {
-        let d1 = &vec[i];
-        let mut min_dist:usize = usize::MAX;
-        let mut min_idx:usize = 0;
+    /// 插入一个点，点必须位于创建网格时的范围内
+    fn insert(&mut self, point: Point) {
+        let (col, row) = self.cell_of(point);
+        self.cells[(row * self.cols + col) as usize].push(point);
+        self.len += 1;
+    }
+
+    /// 查询到最近的已插入点的距离平方，网格为空时返回 `i64::MAX`
+    ///
+    /// 从所在单元格向外逐圈扫描，第 `r` 圈之外的点距离都大于 `r * cell`，找到的距离不超过该值时即可停止
+    fn nearest_squared_distance(&self, center: Point) -> i64 {
+        let mut best = i64::MAX;
+        if self.len == 0 {
+            return best;
+        }
+
+        let (col, row) = self.cell_of(center);
+        let visit = |c: i32, r: i32, best: &mut i64| {
+            if c >= 0 && r >= 0 && c < self.cols && r < self.rows {
+                for &p in &self.cells[(r * self.cols + c) as usize] {
+                    *best = (*best).min(squared_distance(center, p));
+                }
+            }
+        };
+
+        for ring in 0..=self.cols.max(self.rows) {
+            for r in row - ring..=row + ring {
+                if r == row - ring || r == row + ring {
+                    for c in col - ring..=col + ring {
+                        visit(c, r, &mut best);
+                    }
+                } else {
+                    visit(col - ring, r, &mut best);
+                    visit(col + ring, r, &mut best);
+                }
+            }
 
-        for j in 0..i {
-            let d0 = &vec[j];
-            let dist = d0.distance(&d1);
-            if dist < min_dist {
-                min_dist = dist;
-                min_idx = j;
+            let reach = ring as i64 * self.cell as i64;
+            if best <= reach * reach {
+                break;
             }
         }
+        best
+    }
+}
 
-        vec.swap(i, min_idx);
+/// 检查抑制数量的相对误差
+fn check_tolerance(tolerance: f32) -> Result<(), OrbError> {
+    if !(tolerance.is_finite() && tolerance >= 0.0) {
+        return Err(OrbError::invalid_parameter("tolerance", format!("must be a non-negative number, got {}", tolerance)));
     }
+    Ok(())
+}
 
-    for k in 0..n {
-        maximal_keypoints.push(vec[k]);
+/// 二分搜索抑制半径，使按响应顺序贪心选出的特征数量不少于 `n`
+///
+/// `select(radius)` 返回在给定半径下选出的特征索引（按响应顺序），半径为0时选出所有特征。
+/// 返回的特征数量在 `[n, n * (1 + tolerance)]` 内时提前结束，最终截取响应最强的 `n` 个。
+fn search_suppression_radius<T, F>(vec: &[T], n: usize, tolerance: f32, mut select: F) -> Vec<T>
+where
+    T: Suppressible,
+    F: FnMut(u32) -> Vec<usize>
+{
+    let n = n.min(vec.len());
+    if n == 0 {
+        return vec![];
     }
 
-    maximal_keypoints
-}
\ No newline at end of file
+    let max_count = n + (n as f32 * tolerance.max(0.0)).round() as usize;
+    let (min_x, min_y, max_x, max_y) = vec.iter().fold((i32::MAX, i32::MAX, i32::MIN, i32::MIN), |(a, b, c, d), k| {
+        let (x, y) = k.location();
+        (a.min(x), b.min(y), c.max(x), d.max(y))
+    });
+
+    let mut low:u32 = 1;
+    let mut high:u32 = ((max_x - min_x).max(max_y - min_y) as u32 + 1).max(1);
+    let mut best:(u32, Vec<usize>) = (0, select(0));
+
+    while low <= high {
+        let radius = low + (high - low) / 2;
+        let selected = select(radius);
+
+        if selected.len() >= n {
+            best = (radius, selected);
+            if best.1.len() <= max_count {
+                break;
+            }
+            low = radius + 1;
+        } else {
+            high = radius - 1;
+        }
+    }
+
+    let (radius, selected) = best;
+    selected.into_iter()
+        .take(n)
+        .map(|i| {
+            let mut keypoint = vec[i];
+            keypoint.set_nms_dist(radius as usize);
+            keypoint
+        })
+        .collect()
+}
+
+/// 通过方格覆盖进行抑制（Suppression via Square Covering，Bailo等人，2018）
+///
+/// 以边长为抑制半径的网格近似圆形邻域，对每个候选半径按响应顺序贪心选出特征并覆盖其周围的网格，
+/// 通过二分搜索找到满足数量要求的最大半径。
+///
+/// # 参数
+///
+/// * `vec` - 输入的特征向量
+/// * `n` - 需要保留的最大特征数
+/// * `tolerance` - 可接受的特征数量相对误差，默认为0.1
+///
+/// # 返回值
+///
+/// 返回 `min(n, vec.len())` 个按响应从强到弱排列的特征，并写入最终的抑制半径；
+/// `tolerance` 为负数或非有限值时返回 `OrbError::InvalidParameter`
+pub fn ssc_suppression<T>(vec: &[T], n: usize, tolerance: Option<f32>) -> Result<Vec<T>, OrbError>
+where
+    T: Suppressible
+{
+    let tolerance = tolerance.unwrap_or(DEFAULT_SUPPRESSION_TOLERANCE);
+    check_tolerance(tolerance)?;
+    let order = indices_by_response(vec);
+    let (min_x, min_y) = vec.iter().fold((i32::MAX, i32::MAX), |(a, b), k| (a.min(k.location().0), b.min(k.location().1)));
+    let (max_x, max_y) = vec.iter().fold((i32::MIN, i32::MIN), |(a, b), k| (a.max(k.location().0), b.max(k.location().1)));
+
+    Ok(search_suppression_radius(vec, n, tolerance, |radius| {
+        if radius == 0 {
+            return order.clone();
+        }
+
+        let cell = (radius / 2).max(1) as i32;
+        let span = radius as i32 / cell;
+        let cols = ((max_x - min_x) / cell + 1) as usize;
+        let rows = ((max_y - min_y) / cell + 1) as usize;
+        let mut covered = vec![false; cols * rows];
+
+        let mut selected = vec![];
+        for &i in &order {
+            let (x, y) = vec[i].location();
+            let (col, row) = ((x - min_x) / cell, (y - min_y) / cell);
+            if covered[row as usize * cols + col as usize] {
+                continue;
+            }
+
+            selected.push(i);
+            for r in (row - span).max(0)..=(row + span).min(rows as i32 - 1) {
+                for c in (col - span).max(0)..=(col + span).min(cols as i32 - 1) {
+                    covered[r as usize * cols + c as usize] = true;
+                }
+            }
+        }
+
+        selected
+    }))
+}
+
+/// 基于k-d树范围查询的自适应非最大抑制（Bailo等人，2018）
+///
+/// 对每个候选半径按响应顺序贪心选出特征，并通过k-d树查询抑制其欧氏距离半径内的所有特征，
+/// 通过二分搜索找到满足数量要求的最大半径。
+///
+/// # 参数
+///
+/// * `vec` - 输入的特征向量
+/// * `n` - 需要保留的最大特征数
+/// * `tolerance` - 可接受的特征数量相对误差，默认为0.1
+///
+/// # 返回值
+///
+/// 返回 `min(n, vec.len())` 个按响应从强到弱排列的特征，并写入最终的抑制半径；
+/// `tolerance` 为负数或非有限值时返回 `OrbError::InvalidParameter`
+pub fn kdtree_suppression<T>(vec: &[T], n: usize, tolerance: Option<f32>) -> Result<Vec<T>, OrbError>
+where
+    T: Suppressible
+{
+    let tolerance = tolerance.unwrap_or(DEFAULT_SUPPRESSION_TOLERANCE);
+    check_tolerance(tolerance)?;
+    let order = indices_by_response(vec);
+    let points:Vec<Point> = vec.iter().map(|k| k.location()).collect();
+    let tree = KdTree::new(&points);
+    let mut neighbours:Vec<usize> = vec![];
+
+    Ok(search_suppression_radius(vec, n, tolerance, |radius| {
+        if radius == 0 {
+            return order.clone();
+        }
+
+        let mut suppressed = vec![false; vec.len()];
+        let mut selected = vec![];
+        for &i in &order {
+            if suppressed[i] {
+                continue;
+            }
+
+            selected.push(i);
+            neighbours.clear();
+            tree.within_radius(points[i], radius, &mut neighbours);
+            for &j in &neighbours {
+                suppressed[j] = true;
+            }
+        }
+
+        selected
+    }))
+}
+
+/// 二维k-d树，节点按中位数划分并存储在数组中
+struct KdTree {
+    points: Vec<(Point, usize)> // 按树结构排列的点及其原始索引
+}
+
+impl KdTree {
+    fn new(points: &[Point]) -> KdTree {
+        let mut points:Vec<(Point, usize)> = points.iter().copied().zip(0..).collect();
+        KdTree::build(&mut points, true);
+        KdTree { points }
+    }
+
+    fn build(points: &mut [(Point, usize)], split_x: bool) {
+        if points.len() <= 1 {
+            return;
+        }
+
+        let mid = points.len() / 2;
+        if split_x {
+            points.select_nth_unstable_by_key(mid, |p| p.0.0);
+        } else {
+            points.select_nth_unstable_by_key(mid, |p| p.0.1);
+        }
+
+        let (left, right) = points.split_at_mut(mid);
+        KdTree::build(left, !split_x);
+        KdTree::build(&mut right[1..], !split_x);
+    }
+
+    /// 查询与 `center` 的欧氏距离不超过 `radius` 的所有点的原始索引
+    fn within_radius(&self, center: Point, radius: u32, out: &mut Vec<usize>) {
+        let radius_sq = radius as i64 * radius as i64;
+        self.search(&self.points, true, center, radius as i32, radius_sq, out);
+    }
+
+    fn search(&self, points: &[(Point, usize)], split_x: bool, center: Point, radius: i32, radius_sq: i64, out: &mut Vec<usize>) {
+        if points.is_empty() {
+            return;
+        }
+
+        let mid = points.len() / 2;
+        let (point, index) = points[mid];
+        if squared_distance(point, center) <= radius_sq {
+            out.push(index);
+        }
+
+        let (split, value) = if split_x { (point.0, center.0) } else { (point.1, center.1) };
+        if value - radius <= split {
+            self.search(&points[..mid], !split_x, center, radius, radius_sq, out);
+        }
+        if value + radius >= split {
+            self.search(&points[mid + 1..], !split_x, center, radius, radius_sq, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    /// 代价矩阵中的一行或一列，距离取自矩阵
+    struct Entry<'a> {
+        index: usize, // 行或列的下标
+        is_row: bool, // 是否为行
+        costs: &'a [Vec<usize>] // 按行存放的代价矩阵
+    }
+
+    impl Matchable for Entry<'_> {
+        fn distance(&self, other: &Self) -> usize {
+            if self.is_row { self.costs[self.index][other.index] } else { self.costs[other.index][self.index] }
+        }
+    }
+
+    fn entries(costs: &[Vec<usize>]) -> (Vec<Entry<'_>>, Vec<Entry<'_>>) {
+        let rows = (0..costs.len()).map(|index| Entry { index, is_row: true, costs }).collect();
+        let cols = (0..costs[0].len()).map(|index| Entry { index, is_row: false, costs }).collect();
+        (rows, cols)
+    }
+
+    /// 枚举所有分配的最小总代价，`max_cost` 存在时每个未匹配的行计为 `max_cost`，否则较短一侧必须全部匹配
+    fn brute_force_cost(costs: &[Vec<usize>], row: usize, used: &mut Vec<bool>, max_cost: Option<usize>) -> Option<usize> {
+        if row == costs.len() {
+            let matched = used.iter().filter(|&&u| u).count();
+            let complete = max_cost.is_some() || matched == costs.len().min(used.len());
+            return complete.then_some(0);
+        }
+
+        let mut best = max_cost.and_then(|cap| brute_force_cost(costs, row + 1, used, max_cost).map(|rest| rest + cap));
+        if max_cost.is_none() && costs.len() > used.len() {
+            best = brute_force_cost(costs, row + 1, used, max_cost);
+        }
+        for col in 0..used.len() {
+            if used[col] || max_cost.is_some_and(|cap| costs[row][col] > cap) {
+                continue;
+            }
+            used[col] = true;
+            if let Some(rest) = brute_force_cost(costs, row + 1, used, max_cost) {
+                best = Some(best.map_or(rest + costs[row][col], |b| b.min(rest + costs[row][col])));
+            }
+            used[col] = false;
+        }
+        best
+    }
+
+    #[test]
+    fn optimal_assignment_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(21);
+        for (n, m) in [(1, 1), (2, 5), (5, 2), (4, 4), (3, 6), (6, 3), (5, 6), (6, 5)] {
+            for _ in 0..30 {
+                let costs:Vec<Vec<usize>> = (0..n).map(|_| (0..m).map(|_| rng.gen_range(0..50)).collect()).collect();
+                let (rows, cols) = entries(&costs);
+
+                for max_cost in [None, Some(10), Some(25)] {
+                    let matches = match_indices(&rows, &cols, None, max_cost);
+                    let unmatched = n - matches.len();
+                    let total = matches.iter().map(|m| m.distance).sum::<usize>() + unmatched * max_cost.unwrap_or(0);
+                    let expected = brute_force_cost(&costs, 0, &mut vec![false; m], max_cost).unwrap();
+                    assert_eq!(total, expected, "{}x{} max_cost {:?}: {:?}", n, m, max_cost, costs);
+
+                    // 一对一且距离与矩阵一致
+                    assert!(matches.windows(2).all(|w| w[0].query_idx < w[1].query_idx));
+                    let mut train:Vec<usize> = matches.iter().map(|m| m.train_idx).collect();
+                    train.sort_unstable();
+                    train.dedup();
+                    assert_eq!(train.len(), matches.len());
+                    assert!(matches.iter().all(|m| m.distance == costs[m.query_idx][m.train_idx]));
+                    assert!(matches.iter().all(|m| max_cost.is_none_or(|cap| m.distance <= cap)));
+                    if max_cost.is_none() {
+                        assert_eq!(matches.len(), n.min(m));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn max_cost_drops_pairs_instead_of_forcing_worse_assignment() {
+        let costs = vec![vec![1, 5], vec![5, 100]];
+        let (rows, cols) = entries(&costs);
+
+        // 不设上限时只能两两匹配，总代价为10
+        let all = match_indices(&rows, &cols, None, None);
+        assert_eq!(all, vec![DMatch { query_idx: 0, train_idx: 1, distance: 5 }, DMatch { query_idx: 1, train_idx: 0, distance: 5 }]);
+
+        // 上限为6时只保留代价为1的配对，第二行不匹配
+        let capped = match_indices(&rows, &cols, None, Some(6));
+        assert_eq!(capped, vec![DMatch { query_idx: 0, train_idx: 0, distance: 1 }]);
+    }
+
+    #[test]
+    fn greedy_assignment_respects_max_cost() {
+        let costs = vec![vec![3, 1], vec![2, 9]];
+        let (rows, cols) = entries(&costs);
+
+        let matches = match_indices(&rows, &cols, Some(AssignmentMode::Greedy), Some(5));
+        assert_eq!(matches, vec![DMatch { query_idx: 0, train_idx: 1, distance: 1 }, DMatch { query_idx: 1, train_idx: 0, distance: 2 }]);
+        assert_eq!(match_indices(&rows, &cols, Some(AssignmentMode::Greedy), Some(0)), vec![]);
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Corner {
+        location: Point,
+        response: f32,
+        nms_dist: usize
+    }
+
+    impl Suppressible for Corner {
+        fn location(&self) -> Point {
+            self.location
+        }
+
+        fn response(&self) -> f32 {
+            self.response
+        }
+
+        fn set_nms_dist(&mut self, dist: usize) {
+            self.nms_dist = dist;
+        }
+    }
+
+    fn corner(x: i32, y: i32, response: f32) -> Corner {
+        Corner { location: (x, y), response, nms_dist: 0 }
+    }
+
+    /// 在200x150的范围内生成随机特征，响应有正有负，部分位置和响应重复
+    fn random_corners(rng: &mut StdRng, len: usize) -> Vec<Corner> {
+        let mut corners:Vec<Corner> = (0..len)
+            .map(|_| corner(rng.gen_range(0..200), rng.gen_range(0..150), rng.gen_range(-50..100) as f32 / 4.0))
+            .collect();
+        for i in (0..len).step_by(17).skip(1) {
+            corners[i].location = corners[i - 1].location;
+        }
+        corners
+    }
+
+    /// 按定义逐对计算抑制半径，排序规则与 `adaptive_nonmax_suppression` 相同
+    fn brute_force_anms(vec: &[Corner], n: usize, c_robust: f32) -> Vec<Corner> {
+        let order = indices_by_response(vec);
+        let mut radii:Vec<(usize, i64)> = order.iter()
+            .enumerate()
+            .map(|(rank, &i)| {
+                let dist = vec.iter()
+                    .filter(|other| vec[i].response < other.response - (1.0 - c_robust) * other.response.abs())
+                    .map(|other| squared_distance(vec[i].location, other.location))
+                    .min()
+                    .unwrap_or(i64::MAX);
+                (rank, dist)
+            })
+            .collect();
+        radii.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        radii.into_iter()
+            .take(n)
+            .map(|(rank, dist)| Corner {
+                nms_dist: if dist == i64::MAX { usize::MAX } else { (dist as f64).sqrt() as usize },
+                ..vec[order[rank]]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn anms_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for len in [0, 1, 2, 10, 300, 1500] {
+            let corners = random_corners(&mut rng, len);
+            for c_robust in [0.5, 0.9, 1.0] {
+                for n in [1, 50, len] {
+                    let expected = brute_force_anms(&corners, n, c_robust);
+                    let result = adaptive_nonmax_suppression(&corners, n, Some(c_robust)).unwrap();
+                    assert_eq!(result, expected, "len {} n {} c_robust {}", len, n, c_robust);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn anms_negative_responses_need_a_stronger_neighbour() {
+        let corners = [corner(0, 0, -1.0), corner(3, 4, -1.0), corner(20, 0, -2.0)];
+        let result = adaptive_nonmax_suppression(&corners, 3, Some(0.9)).unwrap();
+
+        // 同样弱的负响应不会互相抑制；-2 < -1 - 0.1 * 1，被距离为20的邻居抑制
+        assert_eq!(result.iter().map(|k| (k.location, k.nms_dist)).collect::<Vec<_>>(),
+            vec![((0, 0), usize::MAX), ((3, 4), usize::MAX), ((20, 0), 17)]);
+    }
+
+    #[test]
+    fn anms_rejects_invalid_c_robust() {
+        let corners = [corner(0, 0, 1.0)];
+        assert!(adaptive_nonmax_suppression(&corners, 1, Some(0.0)).is_err());
+        assert!(adaptive_nonmax_suppression(&corners, 1, Some(1.5)).is_err());
+        assert!(adaptive_nonmax_suppression(&corners, 1, Some(f32::NAN)).is_err());
+    }
+
+    #[test]
+    fn suppression_returns_min_n_len() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let corners = random_corners(&mut rng, 400);
+
+        for n in [0, 1, 37, 399, 400, 1000] {
+            let expected = n.min(corners.len());
+            let anms = adaptive_nonmax_suppression(&corners, n, None).unwrap();
+            let ssc = ssc_suppression(&corners, n, None).unwrap();
+            let kdtree = kdtree_suppression(&corners, n, None).unwrap();
+            assert_eq!((anms.len(), ssc.len(), kdtree.len()), (expected, expected, expected), "n {}", n);
+
+            // 自适应非最大抑制按半径从大到小排列
+            assert!(anms.windows(2).all(|w| w[0].nms_dist >= w[1].nms_dist));
+            if let Some(first) = anms.first() {
+                assert_eq!(first.nms_dist, usize::MAX);
+            }
+
+            // 另两种方法按响应排列，并写入同一个最终半径
+            for selected in [&ssc, &kdtree] {
+                assert!(selected.windows(2).all(|w| w[0].response >= w[1].response));
+                assert!(selected.windows(2).all(|w| w[0].nms_dist == w[1].nms_dist));
+            }
+
+            // k-d树选出的特征之间的距离都大于该半径，半径为0时不抑制
+            if let Some(radius) = kdtree.first().map(|k| k.nms_dist as i64).filter(|&r| r > 0) {
+                for (i, a) in kdtree.iter().enumerate() {
+                    assert!(kdtree[i + 1..].iter().all(|b| squared_distance(a.location, b.location) > radius * radius));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn suppression_radius_spreads_features() {
+        // 左上角密集的强特征和分散的弱特征，较大的半径应当选出分散的特征
+        let mut corners:Vec<Corner> = (0..100).map(|i| corner(i % 10, i / 10, 100.0 + i as f32)).collect();
+        corners.extend((0..9).map(|i| corner(50 + 40 * (i % 3), 50 + 40 * (i / 3), i as f32)));
+
+        for selected in [ssc_suppression(&corners, 10, Some(0.0)).unwrap(), kdtree_suppression(&corners, 10, Some(0.0)).unwrap()] {
+            assert_eq!(selected.len(), 10);
+            assert!(selected[0].nms_dist > 10);
+            assert_eq!(selected.iter().filter(|k| k.location.0 < 10 && k.location.1 < 10).count(), 1);
+        }
+    }
+}
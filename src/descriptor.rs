@@ -0,0 +1,356 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use bitvector::BitVector;
+
+use crate::{common, error};
+use common::*;
+use error::OrbError;
+
+/// 按 `u64` 打包的定长二进制描述子，共 `W * 64` 位
+///
+/// 第 `i` 位存放在第 `i / 64` 个字的第 `i % 64` 位。按小端序展开后，
+/// 第 `i` 位位于第 `i / 8` 个字节的第 `i % 8` 位，与OpenCV的ORB描述子布局一致。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BinaryDescriptor<const W: usize> {
+    words: [u64; W] // 打包的位
+}
+
+/// 256位描述子，对应OpenCV的32字节ORB描述子
+pub type Descriptor256 = BinaryDescriptor<4>;
+
+impl<const W: usize> Default for BinaryDescriptor<W> {
+    fn default() -> Self {
+        BinaryDescriptor { words: [0; W] }
+    }
+}
+
+impl<const W: usize> BinaryDescriptor<W> {
+    pub const BITS: usize = W * 64; // 位数
+    pub const BYTES: usize = W * 8; // 字节数
+
+    /// 创建所有位都为0的描述子
+    pub fn zeros() -> Self {
+        Self::default()
+    }
+
+    /// 使用打包的字创建描述子
+    pub fn from_words(words: [u64; W]) -> Self {
+        BinaryDescriptor { words }
+    }
+
+    /// 获取打包的字
+    pub fn words(&self) -> &[u64; W] {
+        &self.words
+    }
+
+    /// 获取第 `bit` 位
+    pub fn get(&self, bit: usize) -> bool {
+        (self.words[bit / 64] >> (bit % 64)) & 1 == 1
+    }
+
+    /// 设置第 `bit` 位
+    pub fn set(&mut self, bit: usize, value: bool) {
+        let mask = 1u64 << (bit % 64);
+        if value {
+            self.words[bit / 64] |= mask;
+        } else {
+            self.words[bit / 64] &= !mask;
+        }
+    }
+
+    /// 计算为1的位数
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|w| w.count_ones()).sum()
+    }
+
+    /// 计算两个描述子之间的汉明距离
+    pub fn hamming_distance(&self, other: &Self) -> u32 {
+        hamming_kernel(&self.words, &other.words)
+    }
+
+    /// 计算两个描述子之间按2位分组的汉明距离，用于 `WTA_K` 为3或4的描述子
+    pub fn hamming2_distance(&self, other: &Self) -> u32 {
+        hamming2_kernel(&self.words, &other.words)
+    }
+
+    /// 按OpenCV的字节布局写出描述子
+    ///
+    /// # 参数
+    ///
+    /// * `out` - 输出缓冲区，长度必须为 `W * 8`
+    pub fn write_bytes(&self, out: &mut [u8]) {
+        assert_eq!(out.len(), Self::BYTES);
+        for (chunk, word) in out.chunks_exact_mut(8).zip(self.words.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    /// 按OpenCV的字节布局获取描述子
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; Self::BYTES];
+        self.write_bytes(&mut bytes);
+        bytes
+    }
+
+    /// 从OpenCV布局的字节创建描述子
+    ///
+    /// # 参数
+    ///
+    /// * `bytes` - 长度为 `W * 8` 的字节
+    ///
+    /// # 返回值
+    ///
+    /// 长度不匹配时返回 `OrbError::DescriptorLength`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, OrbError> {
+        if bytes.len() != Self::BYTES {
+            return Err(OrbError::DescriptorLength { expected: Self::BYTES, found: bytes.len() });
+        }
+
+        let mut words = [0u64; W];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut le = [0u8; 8];
+            le.copy_from_slice(chunk);
+            *word = u64::from_le_bytes(le);
+        }
+
+        Ok(BinaryDescriptor { words })
+    }
+
+    /// 从 `BitVector` 转换，超出 `W * 64` 的位会被忽略
+    pub fn from_bitvector(bits: &BitVector) -> Self {
+        let mut descriptor = Self::zeros();
+        for bit in bits.iter().filter(|&bit| bit < Self::BITS) {
+            descriptor.set(bit, true);
+        }
+        descriptor
+    }
+
+    /// 转换为 `W * 64` 位的 `BitVector`
+    pub fn to_bitvector(&self) -> BitVector {
+        let mut bits = BitVector::new(Self::BITS);
+        for bit in (0..Self::BITS).filter(|&bit| self.get(bit)) {
+            bits.insert(bit);
+        }
+        bits
+    }
+}
+
+impl<const W: usize> From<&BitVector> for BinaryDescriptor<W> {
+    fn from(bits: &BitVector) -> Self {
+        Self::from_bitvector(bits)
+    }
+}
+
+impl<const W: usize> From<&BinaryDescriptor<W>> for BitVector {
+    fn from(descriptor: &BinaryDescriptor<W>) -> Self {
+        descriptor.to_bitvector()
+    }
+}
+
+impl<const W: usize> Matchable for BinaryDescriptor<W> {
+    /// 计算两个描述子之间的汉明距离
+    fn distance(&self, other: &Self) -> usize {
+        self.hamming_distance(other) as usize
+    }
+}
+
+//
+// Hamming Distance
+//
+
+/// 计算两个打包位串之间的汉明距离
+///
+/// 启用 `simd` 特性时，在x86_64上按运行时检测到的CPU特性选择AVX2或POPCNT实现；
+/// 其他情况下使用可移植的异或加 `count_ones` 实现。
+///
+/// # 返回值
+///
+/// 两个位串的长度不同时返回 `OrbError::DescriptorLength`
+pub fn hamming_distance(a: &[u64], b: &[u64]) -> Result<u32, OrbError> {
+    check_lengths(a, b)?;
+    Ok(hamming_kernel(a, b))
+}
+
+/// 计算两个打包位串之间按2位分组的汉明距离
+///
+/// 每组2位存放一个 `WTA_K` 比较结果，只要组内任意一位不同即计为1，与OpenCV的 `NORM_HAMMING2` 一致
+///
+/// # 返回值
+///
+/// 两个位串的长度不同时返回 `OrbError::DescriptorLength`
+pub fn hamming2_distance(a: &[u64], b: &[u64]) -> Result<u32, OrbError> {
+    check_lengths(a, b)?;
+    Ok(hamming2_kernel(a, b))
+}
+
+fn check_lengths(a: &[u64], b: &[u64]) -> Result<(), OrbError> {
+    if a.len() != b.len() {
+        return Err(OrbError::DescriptorLength { expected: a.len() * 64, found: b.len() * 64 });
+    }
+    Ok(())
+}
+
+/// 汉明距离的实现选择，调用者需要保证两个位串等长
+fn hamming_kernel(a: &[u64], b: &[u64]) -> u32 {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return unsafe { hamming_distance_avx2(a, b) };
+        }
+        if is_x86_feature_detected!("popcnt") {
+            return unsafe { hamming_distance_popcnt(a, b) };
+        }
+    }
+
+    hamming_distance_portable(a, b)
+}
+
+/// 可移植的汉明距离实现
+fn hamming_distance_portable(a: &[u64], b: &[u64]) -> u32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (x ^ y).count_ones())
+        .sum()
+}
+
+/// 按2位分组的汉明距离实现，调用者需要保证两个位串等长
+fn hamming2_kernel(a: &[u64], b: &[u64]) -> u32 {
+    const LOW_BITS:u64 = 0x5555_5555_5555_5555; // 每组的低位
+
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| {
+            let diff = x ^ y;
+            ((diff | (diff >> 1)) & LOW_BITS).count_ones()
+        })
+        .sum()
+}
+
+/// 使用POPCNT指令的汉明距离实现
+///
+/// # Safety
+/// 调用者需要确保CPU支持POPCNT指令
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[target_feature(enable = "popcnt")]
+unsafe fn hamming_distance_popcnt(a: &[u64], b: &[u64]) -> u32 {
+    hamming_distance_portable(a, b)
+}
+
+/// 使用AVX2半字节查表的汉明距离实现，每次处理256位
+///
+/// # Safety
+/// 调用者需要确保CPU支持AVX2指令
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn hamming_distance_avx2(a: &[u64], b: &[u64]) -> u32 {
+    use std::arch::x86_64::*;
+
+    let len = a.len().min(b.len());
+    let blocks = len / 4;
+
+    let lookup = _mm256_setr_epi8(
+        0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+        0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4
+    );
+    let low_mask = _mm256_set1_epi8(0x0f);
+    let mut acc = _mm256_setzero_si256();
+
+    for block in 0..blocks {
+        let va = _mm256_loadu_si256(a.as_ptr().add(block * 4) as *const __m256i);
+        let vb = _mm256_loadu_si256(b.as_ptr().add(block * 4) as *const __m256i);
+        let v = _mm256_xor_si256(va, vb);
+
+        let lo = _mm256_and_si256(v, low_mask);
+        let hi = _mm256_and_si256(_mm256_srli_epi16(v, 4), low_mask);
+        let counts = _mm256_add_epi8(
+            _mm256_shuffle_epi8(lookup, lo),
+            _mm256_shuffle_epi8(lookup, hi)
+        );
+        acc = _mm256_add_epi64(acc, _mm256_sad_epu8(counts, _mm256_setzero_si256()));
+    }
+
+    let mut lanes = [0u64; 4];
+    _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, acc);
+    let simd_sum = lanes.iter().sum::<u64>() as u32;
+
+    simd_sum + hamming_distance_portable(&a[blocks * 4..len], &b[blocks * 4..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn bit_layout_matches_opencv_bytes() {
+        let mut descriptor = Descriptor256::zeros();
+        for bit in [0, 1, 7, 8, 63, 64, 130, 255] {
+            descriptor.set(bit, true);
+        }
+
+        assert_eq!(descriptor.words()[0], 1 | 1 << 1 | 1 << 7 | 1 << 8 | 1 << 63);
+        assert_eq!(descriptor.words()[1], 1);
+        assert_eq!(descriptor.words()[2], 1 << 2);
+        assert_eq!(descriptor.words()[3], 1 << 63);
+
+        // 第i位位于第i/8个字节的第i%8位
+        let bytes = descriptor.to_bytes();
+        for bit in 0..Descriptor256::BITS {
+            assert_eq!((bytes[bit / 8] >> (bit % 8)) & 1 == 1, descriptor.get(bit), "bit {}", bit);
+        }
+        assert_eq!(&bytes[..2], &[0b1000_0011, 0b0000_0001]);
+        assert_eq!(Descriptor256::from_bytes(&bytes).unwrap(), descriptor);
+        assert_eq!(Descriptor256::from_bitvector(&descriptor.to_bitvector()), descriptor);
+    }
+
+    #[test]
+    fn from_bytes_rejects_wrong_length() {
+        assert!(matches!(Descriptor256::from_bytes(&[0u8; 31]), Err(OrbError::DescriptorLength { expected: 32, found: 31 })));
+    }
+
+    #[test]
+    fn hamming_kernels_agree() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for len in [1, 3, 4, 5, 8, 13] {
+            for _ in 0..200 {
+                let a:Vec<u64> = (0..len).map(|_| rng.gen()).collect();
+                let b:Vec<u64> = (0..len).map(|_| rng.gen()).collect();
+                let expected:u32 = a.iter().zip(&b).map(|(x, y)| (x ^ y).count_ones()).sum();
+
+                assert_eq!(hamming_distance_portable(&a, &b), expected);
+                assert_eq!(hamming_distance(&a, &b).unwrap(), expected);
+
+                #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+                {
+                    if is_x86_feature_detected!("avx2") {
+                        assert_eq!(unsafe { hamming_distance_avx2(&a, &b) }, expected);
+                    }
+                    if is_x86_feature_detected!("popcnt") {
+                        assert_eq!(unsafe { hamming_distance_popcnt(&a, &b) }, expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hamming2_counts_differing_pairs() {
+        let a = [0b00_01_10_11u64];
+        let b = [0b11_01_01_00u64];
+        // 第0组 11/00 不同，第1组 10/01 不同，第2组 01/01 相同，第3组 00/11 不同
+        assert_eq!(hamming2_distance(&a, &b).unwrap(), 3);
+        assert!(hamming_distance(&a, &[0, 0]).is_err());
+    }
+}
@@ -0,0 +1,722 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+#![allow(clippy::needless_range_loop)] // 数值计算中按下标访问更清晰
+
+use crate::{geometry, linalg, error};
+use geometry::*;
+use linalg::*;
+use error::OrbError;
+
+// 常量
+const SEVEN_POINT_SAMPLE_SIZE:usize = 7; // 七点法所需的点对
+const EIGHT_POINT_SAMPLE_SIZE:usize = 8; // 八点法所需的点对
+const FIVE_POINT_SAMPLE_SIZE:usize = 5; // 五点法所需的点对
+const N_MONOMIALS:usize = 20; // x、y、z的不超过三次的单项式数量
+
+/// 表示针孔相机的内参
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraIntrinsics {
+    pub fx: f64, // x方向焦距（像素）
+    pub fy: f64, // y方向焦距（像素）
+    pub cx: f64, // 主点x坐标
+    pub cy: f64 // 主点y坐标
+}
+
+impl CameraIntrinsics {
+    /// 获取内参矩阵 `K`
+    pub fn matrix(&self) -> Matrix3 {
+        [[self.fx, 0.0, self.cx], [0.0, self.fy, self.cy], [0.0, 0.0, 1.0]]
+    }
+
+    /// 将像素坐标转换为归一化相机坐标
+    pub fn normalize(&self, p: Point2) -> Point2 {
+        ((p.0 - self.cx) / self.fx, (p.1 - self.cy) / self.fy)
+    }
+
+    /// 平均焦距，用于将像素阈值换算到归一化坐标
+    fn focal(&self) -> f64 {
+        (self.fx + self.fy) * 0.5
+    }
+
+    /// 检查内参是否有效
+    fn validate(&self) -> Result<(), OrbError> {
+        for (name, value) in [("fx", self.fx), ("fy", self.fy)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(OrbError::invalid_parameter(name, format!("focal length must be positive, got {}", value)));
+            }
+        }
+        for (name, value) in [("cx", self.cx), ("cy", self.cy)] {
+            if !value.is_finite() {
+                return Err(OrbError::invalid_parameter(name, format!("principal point must be finite, got {}", value)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 表示基础矩阵的最小求解方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FundamentalMethod {
+    #[default]
+    EightPoint, // 归一化八点法
+    SevenPoint // 七点法，每个样本最多得到3个解
+}
+
+/// 表示两个相机之间的相对位姿
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pose {
+    pub rotation: Matrix3, // 第二个相机相对于第一个相机的旋转
+    pub translation: [f64; 3], // 单位长度的平移，尺度不可恢复
+    pub inliers: Vec<bool> // 在两个相机前方都能三角化的点对
+}
+
+/// 检查两组点都不全部位于同一条直线上，否则对极约束无法确定模型
+fn check_not_collinear(src: &[Point2], dst: &[Point2]) -> Result<(), OrbError> {
+    if all_collinear(src) || all_collinear(dst) {
+        return Err(OrbError::DegenerateGeometry("points are collinear"));
+    }
+    Ok(())
+}
+
+/// 构建对极约束 `q^T F p = 0` 的系数行
+fn epipolar_row(p: Point2, q: Point2) -> Vec<f64> {
+    let ((x1, y1), (x2, y2)) = (p, q);
+    vec![x2 * x1, x2 * y1, x2, y2 * x1, y2 * y1, y2, x1, y1, 1.0]
+}
+
+/// 归一化两组点
+fn normalize_points(src: &[Point2], dst: &[Point2]) -> Result<(Vec<Point2>, Vec<Point2>, Matrix3, Matrix3), OrbError> {
+    let degenerate = || OrbError::DegenerateGeometry("points cannot be normalised");
+    let t1 = normalization_transform(src);
+    let t2 = normalization_transform(dst);
+    let s = src.iter().map(|&p| transform_point(&t1, p)).collect::<Option<Vec<Point2>>>().ok_or_else(degenerate)?;
+    let d = dst.iter().map(|&p| transform_point(&t2, p)).collect::<Option<Vec<Point2>>>().ok_or_else(degenerate)?;
+    Ok((s, d, t1, t2))
+}
+
+/// 将归一化坐标下的基础矩阵还原到原始坐标：`F = T2^T * F' * T1`
+fn denormalize(f: &Matrix3, t1: &Matrix3, t2: &Matrix3) -> Matrix3 {
+    normalize3(&mul3(&transpose3(t2), &mul3(f, t1)))
+}
+
+/// 将矩阵投影为秩2矩阵
+fn enforce_rank2(f: &Matrix3) -> Matrix3 {
+    let (u, s, v) = svd3(f);
+    let d = [[s[0], 0.0, 0.0], [0.0, s[1], 0.0], [0.0, 0.0, 0.0]];
+    mul3(&mul3(&u, &d), &transpose3(&v))
+}
+
+/// 将矩阵投影到本质矩阵流形上（两个相等的非零奇异值）
+fn enforce_essential(e: &Matrix3) -> Matrix3 {
+    let (u, s, v) = svd3(e);
+    let sigma = (s[0] + s[1]) * 0.5;
+    let d = [[sigma, 0.0, 0.0], [0.0, sigma, 0.0], [0.0, 0.0, 0.0]];
+    normalize3(&mul3(&mul3(&u, &d), &transpose3(&v)))
+}
+
+/// 计算点对相对于基础矩阵的Sampson距离（一阶几何误差，单位与坐标相同）
+pub fn sampson_error(f: &Matrix3, p: Point2, q: Point2) -> f64 {
+    let fp = mul3_vec(f, [p.0, p.1, 1.0]);
+    let ftq = mul3_vec(&transpose3(f), [q.0, q.1, 1.0]);
+    let qfp = q.0 * fp[0] + q.1 * fp[1] + fp[2];
+    let denom = fp[0] * fp[0] + fp[1] * fp[1] + ftq[0] * ftq[0] + ftq[1] * ftq[1];
+    if denom < f64::MIN_POSITIVE {
+        return f64::INFINITY;
+    }
+    (qfp * qfp / denom).sqrt()
+}
+
+//
+// Fundamental Matrix
+//
+
+/// 使用归一化八点法估计基础矩阵
+///
+/// # 参数
+///
+/// * `src` - 第一幅图像中的点，至少8个
+/// * `dst` - 第二幅图像中对应的点
+///
+/// # 返回值
+///
+/// 返回满足 `dst^T F src = 0`、秩为2且Frobenius范数为1的矩阵，点对不足时返回 `OrbError::NotEnoughPoints`，
+/// 任一组点全部共线时返回 `OrbError::DegenerateGeometry`
+pub fn fundamental_eight_point(src: &[Point2], dst: &[Point2]) -> Result<Matrix3, OrbError> {
+    check_correspondences(src, dst, EIGHT_POINT_SAMPLE_SIZE)?;
+    check_not_collinear(src, dst)?;
+
+    let (s, d, t1, t2) = normalize_points(src, dst)?;
+    let rows:Vec<Vec<f64>> = s.iter().zip(d.iter()).map(|(&p, &q)| epipolar_row(p, q)).collect();
+    let f = enforce_rank2(&to_matrix3(&null_vector(&rows)));
+
+    Ok(denormalize(&f, &t1, &t2))
+}
+
+/// 使用七点法估计基础矩阵
+///
+/// 7个点对确定一个二维解空间 `a * F1 + (1 - a) * F2`，再由 `det(F) = 0` 的三次方程求出 `a`。
+///
+/// # 返回值
+///
+/// 返回1个或3个候选矩阵，点对数量不为7时返回错误，任一组点全部共线时返回 `OrbError::DegenerateGeometry`
+pub fn fundamental_seven_point(src: &[Point2], dst: &[Point2]) -> Result<Vec<Matrix3>, OrbError> {
+    check_correspondences(src, dst, SEVEN_POINT_SAMPLE_SIZE)?;
+    if src.len() != SEVEN_POINT_SAMPLE_SIZE {
+        return Err(OrbError::invalid_parameter("src", format!("exactly {} point pairs are required, got {}", SEVEN_POINT_SAMPLE_SIZE, src.len())));
+    }
+    check_not_collinear(src, dst)?;
+
+    let (s, d, t1, t2) = normalize_points(src, dst)?;
+    let rows:Vec<Vec<f64>> = s.iter().zip(d.iter()).map(|(&p, &q)| epipolar_row(p, q)).collect();
+    let basis = null_space(&rows, 2);
+    let (f1, f2) = (to_matrix3(&basis[0]), to_matrix3(&basis[1]));
+
+    // det(a * F1 + (1 - a) * F2) 是a的三次多项式，通过4个点上的取值求系数
+    let det_at = |a: f64| {
+        let mut f = [[0.0; 3]; 3];
+        for i in 0..3 {
+            for j in 0..3 {
+                f[i][j] = a * f1[i][j] + (1.0 - a) * f2[i][j];
+            }
+        }
+        det3(&f)
+    };
+    let (g0, g1, gm1, g2) = (det_at(0.0), det_at(1.0), det_at(-1.0), det_at(2.0));
+    let c0 = g0;
+    let c2 = (g1 + gm1) * 0.5 - g0;
+    let odd = (g1 - gm1) * 0.5;
+    let c3 = (g2 - 4.0 * c2 - c0 - 2.0 * odd) / 6.0;
+    let c1 = odd - c3;
+
+    Ok(solve_cubic(c3, c2, c1, c0)
+        .into_iter()
+        .map(|a| {
+            let mut f = [[0.0; 3]; 3];
+            for i in 0..3 {
+                for j in 0..3 {
+                    f[i][j] = a * f1[i][j] + (1.0 - a) * f2[i][j];
+                }
+            }
+            denormalize(&f, &t1, &t2)
+        })
+        .collect())
+}
+
+/// 使用RANSAC从匹配点估计基础矩阵
+///
+/// # 参数
+///
+/// * `src` - 第一幅图像中的点
+/// * `dst` - 第二幅图像中对应的点
+/// * `method` - 每次迭代使用的最小求解方法，默认为八点法
+/// * `params` - RANSAC参数，误差为Sampson距离（像素）
+///
+/// # 返回值
+///
+/// 返回基础矩阵和内点掩码，内点不少于8个时使用所有内点重新估计；
+/// 点对少于样本大小时返回 `OrbError::NotEnoughPoints`，所有样本都退化时返回 `OrbError::DegenerateGeometry`
+pub fn find_fundamental(src: &[Point2], dst: &[Point2], method: Option<FundamentalMethod>, params: &RansacParams) -> Result<RansacEstimate, OrbError> {
+    let method = method.unwrap_or_default();
+    let sample_size = match method {
+        FundamentalMethod::EightPoint => EIGHT_POINT_SAMPLE_SIZE,
+        FundamentalMethod::SevenPoint => SEVEN_POINT_SAMPLE_SIZE
+    };
+
+    params.validate()?;
+    check_correspondences(src, dst, sample_size)?;
+
+    let error = |f: &Matrix3, i: usize| sampson_error(f, src[i], dst[i]);
+    let estimate = ransac(src.len(), sample_size, params, |indices| {
+        let s:Vec<Point2> = indices.iter().map(|&i| src[i]).collect();
+        let d:Vec<Point2> = indices.iter().map(|&i| dst[i]).collect();
+        match method {
+            FundamentalMethod::EightPoint => fundamental_eight_point(&s, &d).into_iter().collect(),
+            FundamentalMethod::SevenPoint => fundamental_seven_point(&s, &d).unwrap_or_default()
+        }
+    }, error).ok_or(OrbError::DegenerateGeometry(NO_VALID_MODEL))?;
+
+    Ok(refine(estimate, params, |mask| {
+        let (s, d) = select(src, dst, mask);
+        fundamental_eight_point(&s, &d).ok()
+    }, error))
+}
+
+/// 按掩码选出点对
+fn select(src: &[Point2], dst: &[Point2], mask: &[bool]) -> (Vec<Point2>, Vec<Point2>) {
+    src.iter().zip(dst.iter())
+        .zip(mask.iter())
+        .filter(|(_, &keep)| keep)
+        .map(|((&p, &q), _)| (p, q))
+        .unzip()
+}
+
+//
+// Essential Matrix
+//
+
+/// 单项式 `x^a y^b z^c` 的指数，前10个为三次项，后10个为基
+const MONOMIALS:[(u8, u8, u8); N_MONOMIALS] = [
+    (3, 0, 0), (2, 1, 0), (2, 0, 1), (1, 2, 0), (1, 1, 1), (1, 0, 2), (0, 3, 0), (0, 2, 1), (0, 1, 2), (0, 0, 3),
+    (2, 0, 0), (1, 1, 0), (1, 0, 1), (0, 2, 0), (0, 1, 1), (0, 0, 2),
+    (1, 0, 0), (0, 1, 0), (0, 0, 1), (0, 0, 0)
+];
+
+type Polynomial = [f64; N_MONOMIALS];
+
+fn monomial_index(exponents: (u8, u8, u8)) -> usize {
+    MONOMIALS.iter().position(|&m| m == exponents).expect("monomial degree exceeds 3")
+}
+
+fn poly_mul(a: &Polynomial, b: &Polynomial) -> Polynomial {
+    let mut c = [0.0; N_MONOMIALS];
+    for (i, &ca) in a.iter().enumerate().filter(|(_, &x)| x != 0.0) {
+        for (j, &cb) in b.iter().enumerate().filter(|(_, &x)| x != 0.0) {
+            let (ma, mb) = (MONOMIALS[i], MONOMIALS[j]);
+            c[monomial_index((ma.0 + mb.0, ma.1 + mb.1, ma.2 + mb.2))] += ca * cb;
+        }
+    }
+    c
+}
+
+fn poly_add(a: &Polynomial, b: &Polynomial) -> Polynomial {
+    let mut c = *a;
+    for (x, y) in c.iter_mut().zip(b.iter()) {
+        *x += y;
+    }
+    c
+}
+
+fn poly_scale(a: &Polynomial, k: f64) -> Polynomial {
+    a.map(|x| x * k)
+}
+
+/// 使用五点法估计本质矩阵（Stewénius等人的作用矩阵方法）
+///
+/// 5个点对确定一个四维解空间 `E = x X + y Y + z Z + W`，本质矩阵约束
+/// `det(E) = 0` 和 `2 E E^T E - tr(E E^T) E = 0` 给出10个三次方程，
+/// 消元后由乘以 `x` 的作用矩阵的实特征向量求出所有实数解。
+///
+/// # 参数
+///
+/// * `src` - 第一幅图像中的归一化相机坐标，至少5个
+/// * `dst` - 第二幅图像中对应的归一化相机坐标
+///
+/// # 返回值
+///
+/// 返回最多10个候选本质矩阵，点对不足5个时返回 `OrbError::NotEnoughPoints`，
+/// 任一组点全部共线或约束方程无法消元时返回 `OrbError::DegenerateGeometry`
+pub fn essential_five_point(src: &[Point2], dst: &[Point2]) -> Result<Vec<Matrix3>, OrbError> {
+    check_correspondences(src, dst, FIVE_POINT_SAMPLE_SIZE)?;
+    check_not_collinear(src, dst)?;
+
+    let rows:Vec<Vec<f64>> = src.iter().zip(dst.iter()).map(|(&p, &q)| epipolar_row(p, q)).collect();
+    let basis = null_space(&rows, 4);
+
+    // E 的每个元素是 x、y、z 的一次多项式
+    let (ix, iy, iz, i1) = (monomial_index((1, 0, 0)), monomial_index((0, 1, 0)), monomial_index((0, 0, 1)), monomial_index((0, 0, 0)));
+    let mut e = [[[0.0; N_MONOMIALS]; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            let k = i * 3 + j;
+            e[i][j][ix] = basis[0][k];
+            e[i][j][iy] = basis[1][k];
+            e[i][j][iz] = basis[2][k];
+            e[i][j][i1] = basis[3][k];
+        }
+    }
+
+    let mut equations:Vec<Polynomial> = Vec::with_capacity(10);
+
+    // det(E) = 0
+    let minor = |a: usize, b: usize, c: usize, d: usize| poly_add(
+        &poly_mul(&e[1][a], &e[2][b]),
+        &poly_scale(&poly_mul(&e[1][c], &e[2][d]), -1.0)
+    );
+    let det = poly_add(
+        &poly_add(&poly_mul(&e[0][0], &minor(1, 2, 2, 1)), &poly_scale(&poly_mul(&e[0][1], &minor(0, 2, 2, 0)), -1.0)),
+        &poly_mul(&e[0][2], &minor(0, 1, 1, 0))
+    );
+    equations.push(det);
+
+    // 2 E E^T E - tr(E E^T) E = 0
+    let mut eet = [[[0.0; N_MONOMIALS]; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            for k in 0..3 {
+                eet[i][j] = poly_add(&eet[i][j], &poly_mul(&e[i][k], &e[j][k]));
+            }
+        }
+    }
+    let trace = poly_add(&poly_add(&eet[0][0], &eet[1][1]), &eet[2][2]);
+    for i in 0..3 {
+        for j in 0..3 {
+            let mut equation = poly_scale(&poly_mul(&trace, &e[i][j]), -1.0);
+            for k in 0..3 {
+                equation = poly_add(&equation, &poly_scale(&poly_mul(&eet[i][k], &e[k][j]), 2.0));
+            }
+            equations.push(equation);
+        }
+    }
+
+    // 消去三次项，使每个三次单项式都表示为基的线性组合
+    let mut matrix:Vec<Vec<f64>> = equations.iter().map(|eq| eq.to_vec()).collect();
+    if gauss_jordan(&mut matrix).is_none() {
+        return Err(OrbError::DegenerateGeometry("essential matrix constraints are singular"));
+    }
+
+    // 乘以 x 的作用矩阵：x * b = M * b，其中 b 为后10个单项式
+    let n_basis = N_MONOMIALS - 10;
+    let mut action = vec![vec![0.0; n_basis]; n_basis];
+    for (row, &(a, b, c)) in MONOMIALS[10..].iter().enumerate() {
+        let product = monomial_index((a + 1, b, c));
+        if product < 10 {
+            for k in 0..n_basis {
+                action[row][k] = -matrix[product][10 + k];
+            }
+        } else {
+            action[row][product - 10] = 1.0;
+        }
+    }
+
+    let (bx, by, bz, b1) = (ix - 10, iy - 10, iz - 10, i1 - 10);
+    Ok(real_eigenvectors(&action)
+        .into_iter()
+        .filter(|(_, v)| v[b1].abs() > 1e-12)
+        .map(|(_, v)| {
+            let (x, y, z) = (v[bx] / v[b1], v[by] / v[b1], v[bz] / v[b1]);
+            let mut essential = [[0.0; 3]; 3];
+            for i in 0..3 {
+                for j in 0..3 {
+                    let k = i * 3 + j;
+                    essential[i][j] = x * basis[0][k] + y * basis[1][k] + z * basis[2][k] + basis[3][k];
+                }
+            }
+            normalize3(&essential)
+        })
+        .collect())
+}
+
+/// 使用RANSAC从匹配点估计本质矩阵
+///
+/// # 参数
+///
+/// * `src` - 第一幅图像中的像素坐标
+/// * `dst` - 第二幅图像中对应的像素坐标
+/// * `intrinsics` - 两幅图像共用的相机内参
+/// * `params` - RANSAC参数，阈值按平均焦距换算到归一化坐标
+///
+/// # 返回值
+///
+/// 返回归一化坐标下的本质矩阵和内点掩码，内点不少于8个时使用所有内点重新估计；
+/// 点对不足5个时返回 `OrbError::NotEnoughPoints`，所有样本都退化时返回 `OrbError::DegenerateGeometry`
+pub fn find_essential(src: &[Point2], dst: &[Point2], intrinsics: &CameraIntrinsics, params: &RansacParams) -> Result<RansacEstimate, OrbError> {
+    params.validate()?;
+    intrinsics.validate()?;
+    check_correspondences(src, dst, FIVE_POINT_SAMPLE_SIZE)?;
+
+    let src:Vec<Point2> = src.iter().map(|&p| intrinsics.normalize(p)).collect();
+    let dst:Vec<Point2> = dst.iter().map(|&p| intrinsics.normalize(p)).collect();
+    let params = RansacParams {
+        reproj_threshold: params.reproj_threshold / intrinsics.focal(),
+        ..*params
+    };
+
+    let error = |e: &Matrix3, i: usize| sampson_error(e, src[i], dst[i]);
+    let estimate = ransac(src.len(), FIVE_POINT_SAMPLE_SIZE, &params, |indices| {
+        let s:Vec<Point2> = indices.iter().map(|&i| src[i]).collect();
+        let d:Vec<Point2> = indices.iter().map(|&i| dst[i]).collect();
+        essential_five_point(&s, &d).unwrap_or_default()
+    }, error).ok_or(OrbError::DegenerateGeometry(NO_VALID_MODEL))?;
+
+    Ok(refine(estimate, &params, |mask| {
+        let (s, d) = select(&src, &dst, mask);
+        if s.len() < EIGHT_POINT_SAMPLE_SIZE {
+            return None;
+        }
+        let rows:Vec<Vec<f64>> = s.iter().zip(d.iter()).map(|(&p, &q)| epipolar_row(p, q)).collect();
+        Some(enforce_essential(&to_matrix3(&null_vector(&rows))))
+    }, error))
+}
+
+//
+// Pose Recovery
+//
+
+/// 线性三角化，返回点在第一个相机坐标系中的坐标
+fn triangulate(p: Point2, q: Point2, rotation: &Matrix3, translation: &[f64; 3]) -> Option<[f64; 3]> {
+    let p1 = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0]];
+    let p2 = [
+        [rotation[0][0], rotation[0][1], rotation[0][2], translation[0]],
+        [rotation[1][0], rotation[1][1], rotation[1][2], translation[1]],
+        [rotation[2][0], rotation[2][1], rotation[2][2], translation[2]]
+    ];
+
+    let row = |camera: &[[f64; 4]; 3], coord: f64, axis: usize| -> Vec<f64> {
+        (0..4).map(|k| coord * camera[2][k] - camera[axis][k]).collect()
+    };
+    let rows = vec![row(&p1, p.0, 0), row(&p1, p.1, 1), row(&p2, q.0, 0), row(&p2, q.1, 1)];
+
+    let x = null_vector(&rows);
+    if x[3].abs() < f64::EPSILON {
+        return None;
+    }
+    Some([x[0] / x[3], x[1] / x[3], x[2] / x[3]])
+}
+
+/// 从本质矩阵恢复相对位姿
+///
+/// 本质矩阵分解得到4组 `(R, t)`，选择使最多点对在两个相机前方（正深度）的一组。
+///
+/// # 参数
+///
+/// * `essential` - 本质矩阵
+/// * `src` - 第一幅图像中的像素坐标
+/// * `dst` - 第二幅图像中对应的像素坐标
+/// * `intrinsics` - 相机内参
+/// * `mask` - 参与检验的点对，通常为 `find_essential` 返回的内点掩码
+///
+/// # 返回值
+///
+/// 返回位姿及通过正深度检验的点对，没有点对通过检验时返回 `OrbError::DegenerateGeometry`
+pub fn recover_pose(essential: &Matrix3, src: &[Point2], dst: &[Point2], intrinsics: &CameraIntrinsics, mask: Option<&[bool]>) -> Result<Pose, OrbError> {
+    intrinsics.validate()?;
+    check_correspondences(src, dst, 1)?;
+    if let Some(mask) = mask.filter(|mask| mask.len() != src.len()) {
+        return Err(OrbError::invalid_parameter("mask", format!("has {} entries but there are {} point pairs", mask.len(), src.len())));
+    }
+
+    let (mut u, _, mut v) = svd3(essential);
+    if det3(&u) < 0.0 {
+        u = u.map(|row| row.map(|x| -x));
+    }
+    if det3(&v) < 0.0 {
+        v = v.map(|row| row.map(|x| -x));
+    }
+
+    let w = [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+    let r1 = mul3(&mul3(&u, &w), &transpose3(&v));
+    let r2 = mul3(&mul3(&u, &transpose3(&w)), &transpose3(&v));
+    let t = [u[0][2], u[1][2], u[2][2]];
+    let neg_t = t.map(|x| -x);
+
+    let src:Vec<Point2> = src.iter().map(|&p| intrinsics.normalize(p)).collect();
+    let dst:Vec<Point2> = dst.iter().map(|&p| intrinsics.normalize(p)).collect();
+
+    [(r1, t), (r1, neg_t), (r2, t), (r2, neg_t)]
+        .into_iter()
+        .map(|(rotation, translation)| {
+            let inliers:Vec<bool> = (0..src.len())
+                .map(|i| {
+                    if mask.is_some_and(|mask| !mask[i]) {
+                        return false;
+                    }
+                    match triangulate(src[i], dst[i], &rotation, &translation) {
+                        Some(x) => {
+                            let x2 = mul3_vec(&rotation, x);
+                            x[2] > 0.0 && x2[2] + translation[2] > 0.0
+                        },
+                        None => false
+                    }
+                })
+                .collect();
+            Pose { rotation, translation, inliers }
+        })
+        .max_by_key(|pose| pose.inliers.iter().filter(|&&inlier| inlier).count())
+        .filter(|pose| pose.inliers.iter().any(|&inlier| inlier))
+        .ok_or(OrbError::DegenerateGeometry("no point lies in front of both cameras"))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    const K:CameraIntrinsics = CameraIntrinsics { fx: 800.0, fy: 780.0, cx: 320.0, cy: 240.0 };
+    const N_POINTS:usize = 100; // 点对数量
+    const N_OUTLIERS:usize = 20; // 最后20个点对为外点
+
+    /// 绕单位轴旋转 `angle` 弧度的矩阵（罗德里格斯公式）
+    fn rotation(axis: [f64; 3], angle: f64) -> Matrix3 {
+        let norm = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+        let [x, y, z] = axis.map(|v| v / norm);
+        let (s, c) = angle.sin_cos();
+        let t = 1.0 - c;
+        [
+            [t * x * x + c, t * x * y - s * z, t * x * z + s * y],
+            [t * x * y + s * z, t * y * y + c, t * y * z - s * x],
+            [t * x * z - s * y, t * y * z + s * x, t * z * z + c]
+        ]
+    }
+
+    fn skew(t: [f64; 3]) -> Matrix3 {
+        [[0.0, -t[2], t[1]], [t[2], 0.0, -t[0]], [-t[1], t[0], 0.0]]
+    }
+
+    fn max_difference(a: &Matrix3, b: &Matrix3) -> f64 {
+        (0..9).map(|i| (a[i / 3][i % 3] - b[i / 3][i % 3]).abs()).fold(0.0, f64::max)
+    }
+
+    /// 忽略符号比较两个归一化矩阵
+    fn distance_up_to_sign(a: &Matrix3, b: &Matrix3) -> f64 {
+        let (a, b) = (normalize3(a), normalize3(b));
+        max_difference(&a, &b).min(max_difference(&a, &b.map(|row| row.map(|x| -x))))
+    }
+
+    struct Scene {
+        rotation: Matrix3, // 第二个相机的旋转
+        translation: [f64; 3], // 第二个相机的单位平移
+        src: Vec<Point2>, // 第一幅图像中的像素坐标
+        dst: Vec<Point2>, // 第二幅图像中的像素坐标
+        inliers: Vec<bool> // 真实的内点掩码
+    }
+
+    impl Scene {
+        fn essential(&self) -> Matrix3 {
+            normalize3(&mul3(&skew(self.translation), &self.rotation))
+        }
+
+        fn fundamental(&self) -> Matrix3 {
+            let k_inv = inverse3(&K.matrix()).unwrap();
+            normalize3(&mul3(&transpose3(&k_inv), &mul3(&self.essential(), &k_inv)))
+        }
+    }
+
+    /// 在两个相机前方生成100个三维点，最后20个点对的第二个点替换为远离对极线的随机像素
+    fn scene(seed: u64) -> Scene {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let rotation = rotation([0.2, 1.0, 0.1], 0.15);
+        let t:[f64; 3] = [0.8, 0.15, 0.1];
+        let norm = (t[0] * t[0] + t[1] * t[1] + t[2] * t[2]).sqrt();
+        let translation = t.map(|v| v / norm);
+        let project = |x: [f64; 3]| (K.fx * x[0] / x[2] + K.cx, K.fy * x[1] / x[2] + K.cy);
+
+        let mut scene = Scene { rotation, translation, src: vec![], dst: vec![], inliers: vec![] };
+        let f = scene.fundamental();
+        for i in 0..N_POINTS {
+            let x = [rng.gen_range(-2.0..2.0), rng.gen_range(-1.5..1.5), rng.gen_range(4.0..10.0)];
+            let x2 = mul3_vec(&rotation, x);
+            let p = project(x);
+            let mut q = project([x2[0] + translation[0], x2[1] + translation[1], x2[2] + translation[2]]);
+
+            let inlier = i < N_POINTS - N_OUTLIERS;
+            while !inlier && sampson_error(&f, p, q) < 20.0 {
+                q = (rng.gen_range(0.0..640.0), rng.gen_range(0.0..480.0));
+            }
+            scene.src.push(p);
+            scene.dst.push(q);
+            scene.inliers.push(inlier);
+        }
+        scene
+    }
+
+    fn params(seed: u64) -> RansacParams {
+        RansacParams { reproj_threshold: 1.0, seed: Some(seed), ..RansacParams::default() }
+    }
+
+    #[test]
+    fn find_fundamental_recovers_epipolar_geometry() {
+        let scene = scene(1);
+        let truth = scene.fundamental();
+
+        for method in [FundamentalMethod::EightPoint, FundamentalMethod::SevenPoint] {
+            let estimate = find_fundamental(&scene.src, &scene.dst, Some(method), &params(2)).unwrap();
+            assert_eq!(estimate.inliers, scene.inliers, "{:?}", method);
+
+            let residual = (0..N_POINTS - N_OUTLIERS)
+                .map(|i| sampson_error(&estimate.model, scene.src[i], scene.dst[i]))
+                .fold(0.0, f64::max);
+            assert!(residual < 1e-6, "{:?}: residual {}", method, residual);
+            assert!(distance_up_to_sign(&estimate.model, &truth) < 1e-6, "{:?}: {:?}", method, estimate.model);
+        }
+    }
+
+    #[test]
+    fn minimal_fundamental_solvers_fit_their_samples() {
+        let scene = scene(3);
+        let truth = scene.fundamental();
+
+        let f = fundamental_eight_point(&scene.src[..8], &scene.dst[..8]).unwrap();
+        assert!(distance_up_to_sign(&f, &truth) < 1e-6);
+        assert!(det3(&f).abs() < 1e-12);
+
+        let candidates = fundamental_seven_point(&scene.src[..7], &scene.dst[..7]).unwrap();
+        assert!(candidates.len() == 1 || candidates.len() == 3);
+        assert!(candidates.iter().any(|f| distance_up_to_sign(f, &truth) < 1e-6));
+        for f in &candidates {
+            assert!((0..7).all(|i| sampson_error(f, scene.src[i], scene.dst[i]) < 1e-6));
+        }
+    }
+
+    #[test]
+    fn essential_five_point_contains_true_essential() {
+        let scene = scene(4);
+        let src:Vec<Point2> = scene.src[..5].iter().map(|&p| K.normalize(p)).collect();
+        let dst:Vec<Point2> = scene.dst[..5].iter().map(|&p| K.normalize(p)).collect();
+
+        let candidates = essential_five_point(&src, &dst).unwrap();
+        assert!(!candidates.is_empty() && candidates.len() <= 10);
+        let best = candidates.iter().map(|e| distance_up_to_sign(e, &scene.essential())).fold(f64::INFINITY, f64::min);
+        assert!(best < 1e-8, "closest candidate differs by {}", best);
+    }
+
+    #[test]
+    fn find_essential_and_recover_pose() {
+        let scene = scene(5);
+
+        let estimate = find_essential(&scene.src, &scene.dst, &K, &params(6)).unwrap();
+        assert_eq!(estimate.inlier_count(), N_POINTS - N_OUTLIERS);
+        assert_eq!(estimate.inliers, scene.inliers);
+        assert!(distance_up_to_sign(&estimate.model, &scene.essential()) < 1e-6);
+
+        let pose = recover_pose(&estimate.model, &scene.src, &scene.dst, &K, Some(&estimate.inliers)).unwrap();
+        assert!(max_difference(&pose.rotation, &scene.rotation) < 1e-6, "{:?}", pose.rotation);
+        let t_error = (0..3).map(|i| (pose.translation[i] - scene.translation[i]).abs()).fold(0.0, f64::max);
+        assert!(t_error < 1e-6, "{:?}", pose.translation);
+        assert_eq!(pose.inliers, scene.inliers);
+    }
+
+    #[test]
+    fn too_few_points_are_rejected() {
+        let scene = scene(7);
+        let (src, dst) = (&scene.src, &scene.dst);
+        let params = params(8);
+
+        assert!(matches!(fundamental_eight_point(&src[..7], &dst[..7]), Err(OrbError::NotEnoughPoints { required: 8, found: 7 })));
+        assert!(matches!(fundamental_seven_point(&src[..6], &dst[..6]), Err(OrbError::NotEnoughPoints { required: 7, found: 6 })));
+        assert!(matches!(essential_five_point(&src[..4], &dst[..4]), Err(OrbError::NotEnoughPoints { required: 5, found: 4 })));
+        assert!(matches!(find_fundamental(&src[..7], &dst[..7], None, &params), Err(OrbError::NotEnoughPoints { .. })));
+        assert!(matches!(find_fundamental(&src[..6], &dst[..6], Some(FundamentalMethod::SevenPoint), &params), Err(OrbError::NotEnoughPoints { .. })));
+        assert!(matches!(find_essential(&src[..4], &dst[..4], &K, &params), Err(OrbError::NotEnoughPoints { .. })));
+        assert!(matches!(recover_pose(&scene.essential(), &[], &[], &K, None), Err(OrbError::NotEnoughPoints { .. })));
+    }
+
+    #[test]
+    fn collinear_points_are_degenerate() {
+        // 三维点位于一条直线上，两幅图像中的投影都共线
+        let scene = scene(9);
+        let src:Vec<Point2> = (0..30).map(|i| (100.0 + 10.0 * i as f64, 50.0 + 7.0 * i as f64)).collect();
+        let dst:Vec<Point2> = src.iter().map(|&(x, y)| (x * 0.9 + 20.0, y * 0.9 + 5.0)).collect();
+        let params = RansacParams { max_iters: 50, ..params(10) };
+        let degenerate = |result: Result<RansacEstimate, OrbError>| matches!(result, Err(OrbError::DegenerateGeometry(_)));
+
+        assert!(degenerate(find_fundamental(&src, &dst, None, &params)));
+        assert!(degenerate(find_fundamental(&src, &dst, Some(FundamentalMethod::SevenPoint), &params)));
+        assert!(degenerate(find_essential(&src, &dst, &K, &params)));
+        assert!(matches!(fundamental_eight_point(&src, &dst), Err(OrbError::DegenerateGeometry(_))));
+        assert!(matches!(fundamental_seven_point(&src[..7], &dst[..7]), Err(OrbError::DegenerateGeometry(_))));
+        assert!(matches!(essential_five_point(&src[..5], &dst[..5]), Err(OrbError::DegenerateGeometry(_))));
+
+        // 只有一侧共线也无法确定模型
+        assert!(matches!(fundamental_eight_point(&src[..8], &scene.dst[..8]), Err(OrbError::DegenerateGeometry(_))));
+    }
+}
@@ -0,0 +1,93 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+
+use std::{fmt, io};
+use image::ImageError;
+
+use crate::brief;
+use brief::PatternError;
+
+/// 本库所有可能失败的公共函数返回的错误
+#[derive(Debug)]
+pub enum OrbError {
+    ImageTooSmall { width: u32, height: u32, min: u32 }, // 图像的宽或高小于算法要求的最小边长
+    InvalidParameter { name: &'static str, reason: String }, // 参数超出有效范围或输入之间不一致
+    DescriptorLength { expected: usize, found: usize }, // 描述子的长度与要求的不一致
+    NotEnoughPoints { required: usize, found: usize }, // 点对数量少于求解器需要的数量
+    DegenerateGeometry(&'static str), // 点的分布退化，无法确定模型
+    Pattern(PatternError), // BRIEF采样模式无效
+    Image(ImageError), // 图像解码、编码或读写失败
+    Io(io::Error) // 文件读写失败
+}
+
+impl OrbError {
+    /// 创建参数无效的错误
+    pub(crate) fn invalid_parameter(name: &'static str, reason: impl fmt::Display) -> OrbError {
+        OrbError::InvalidParameter { name, reason: reason.to_string() }
+    }
+}
+
+impl fmt::Display for OrbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrbError::ImageTooSmall { width, height, min } =>
+                write!(f, "image of {}x{} is smaller than the minimum side length of {}", width, height, min),
+            OrbError::InvalidParameter { name, reason } => write!(f, "invalid `{}`: {}", name, reason),
+            OrbError::DescriptorLength { expected, found } =>
+                write!(f, "descriptor length {} does not match the expected length {}", found, expected),
+            OrbError::NotEnoughPoints { required, found } =>
+                write!(f, "at least {} point pairs are required, got {}", required, found),
+            OrbError::DegenerateGeometry(reason) => write!(f, "degenerate geometry: {}", reason),
+            OrbError::Pattern(err) => write!(f, "invalid BRIEF pattern: {}", err),
+            OrbError::Image(err) => write!(f, "image error: {}", err),
+            OrbError::Io(err) => write!(f, "I/O error: {}", err)
+        }
+    }
+}
+
+impl std::error::Error for OrbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OrbError::Pattern(err) => Some(err),
+            OrbError::Image(err) => Some(err),
+            OrbError::Io(err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<PatternError> for OrbError {
+    fn from(err: PatternError) -> Self {
+        OrbError::Pattern(err)
+    }
+}
+
+impl From<ImageError> for OrbError {
+    fn from(err: ImageError) -> Self {
+        OrbError::Image(err)
+    }
+}
+
+impl From<io::Error> for OrbError {
+    fn from(err: io::Error) -> Self {
+        OrbError::Io(err)
+    }
+}
+
+/// 检查图像的宽和高都不小于 `min`
+pub(crate) fn check_image_size(width: u32, height: u32, min: u32) -> Result<(), OrbError> {
+    if width < min || height < min {
+        return Err(OrbError::ImageTooSmall { width, height, min });
+    }
+    Ok(())
+}
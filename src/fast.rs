@@ -10,202 +10,698 @@
 #![allow(unused_must_use)] // 允许未使用的must_use结果
 #![allow(non_snake_case)] // 允许非蛇形命名
 
-use image::{ImageError, Rgba, GrayImage};
+use image::{DynamicImage, Rgba, GrayImage};
 use imageproc::drawing::draw_line_segment_mut;
 use cgmath::{prelude::{*}, Rad};
 
-use crate::common;
+use crate::{common, feature, error, parallel, mask};
 use common::*;
+use error::OrbError;
+use feature::{KeyPoint, FeatureDetector};
+use mask::DetectionMask;
 
 // 常量
 const DEFAULT_FAST_THRESHOLD:i32 = 50; // 默认的FAST阈值
+const HARRIS_BLOCK_SIZE:i32 = 7; // Harris响应的窗口边长
+const HARRIS_K:f32 = 0.04; // Harris响应的经验系数
+const ROW_BAND_HEIGHT:u32 = 16; // 分段测试时每个任务处理的行数
+const MAX_CIRCLE_LENGTH:usize = 16; // 最大的圆周像素数
+const DEFAULT_MOMENT_RADIUS:u32 = 5; // `fast` 计算矩时使用的圆形图像块半径
+const DEFAULT_SUBPIXEL_HALF_WINDOW:u32 = 2; // 梯度法细化时窗口的默认半径
+const DEFAULT_SUBPIXEL_ITERATIONS:u32 = 20; // 梯度法细化时的默认最大迭代次数
+const SUBPIXEL_EPSILON:f32 = 1e-3; // 梯度法细化时位移小于该值即停止迭代
+const QUADRATIC_MAX_STEPS:usize = 3; // 二次曲面拟合时中心最多移动的次数
 
 /// 表示一个FAST关键点
 #[derive(Debug, Clone, Copy)]
 pub struct FastKeypoint {
-    pub location: Point, // 关键点的位置
-    pub score: i32, // 关键点的得分
-    pub nms_dist: usize, // 非极大值抑制距离
+    pub location: Point, // 关键点所在的像素
+    pub position: PointF, // 亚像素位置，未经细化时等于 `location`
+    pub score: f32, // 关键点的得分，含义由 `ScoreType` 决定
+    pub nms_dist: usize, // 自适应非极大值抑制半径，未经抑制时为0
     pub moment: Moment // 关键点的矩
 }
 
 impl Matchable for FastKeypoint {
     /// 计算两个FAST关键点之间的距离
     fn distance(&self, other: &FastKeypoint) -> usize {
-        let ((ax, ay), (bx, by)) = (self.location, other.location);
-        ((ax-bx).pow(2) as f32 + (ay-by).pow(2) as f32).sqrt() as usize
+        let ((ax, ay), (bx, by)) = (self.position, other.position);
+        ((ax-bx).powi(2) + (ay-by).powi(2)).sqrt() as usize
+    }
+}
+
+impl Suppressible for FastKeypoint {
+    fn location(&self) -> Point {
+        self.location
+    }
+
+    fn response(&self) -> f32 {
+        self.score
+    }
+
+    fn set_nms_dist(&mut self, dist: usize) {
+        self.nms_dist = dist;
     }
 }
 
 /// 表示FAST算法的上下文
 #[derive(Debug)]
 pub struct FastContext {
-    offsets: Vec<Point>, // 偏移量向量
-    idx: Vec<usize>, // 索引向量
-    cmp: Vec<i32>, // 比较向量
-    radius: u32, // 半径
-    n: u32 // 数量
+    pub(crate) offsets: Vec<Point>, // 按顺时针排列的圆周偏移量
+    pub(crate) radius: u32, // 圆周半径加一个像素，检测时跳过的图像边界
+    pub(crate) n: u32 // 连续弧段的最小长度
 }
 
 #[allow(non_camel_case_types)]
 /// 表示FAST算法的类型
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum FastType {
+    TYPE_5_8, // 半径1的8像素圆周，连续5个
     TYPE_7_12, // 类型7_12
+    #[default]
     TYPE_9_16 // 类型9_16
 }
 
+/// 表示亚像素细化的方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubpixelMethod {
+    Quadratic, // 对3x3邻域的Harris响应拟合二次曲面，取极大值点；该点通常位于几何角点内侧1到2个像素
+    Gradient {
+        half_window: u32, // 窗口半径，窗口边长为 `2 * half_window + 1`
+        max_iterations: u32 // 最大迭代次数
+    } // 与OpenCV的 `cornerSubPix` 相同，令窗口内的梯度与到角点的向量正交，收敛到几何角点
+}
+
+impl SubpixelMethod {
+    /// 使用默认窗口和迭代次数的梯度法
+    pub fn gradient() -> SubpixelMethod {
+        SubpixelMethod::Gradient { half_window: DEFAULT_SUBPIXEL_HALF_WINDOW, max_iterations: DEFAULT_SUBPIXEL_ITERATIONS }
+    }
+}
+
+/// 表示关键点的评分方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScoreType {
+    Fast, // 圆周上超过阈值的像素差绝对值之和
+    #[default]
+    Harris, // 7x7窗口内的Harris角点响应，与OpenCV的ORB一致
+    ShiTomasi // 7x7窗口内结构张量的最小特征值
+}
+
 impl FastType {
     /// 获取FAST算法的上下文
     pub fn get_context(&self) -> FastContext {
         match self {
+            FastType::TYPE_5_8 => FastContext {
+                offsets: vec![
+                    ( 0, -1), ( 1, -1), ( 1,  0), ( 1,  1),
+                    ( 0,  1), (-1,  1), (-1,  0), (-1, -1)
+                ],
+                radius: 2,
+                n: 5
+            },
             FastType::TYPE_7_12 => FastContext {
                 offsets: vec![
                     ( 0, -2), ( 1, -2), ( 2, -1), ( 2,  0),
                     ( 2,  1), ( 1,  2), ( 0,  2), (-1,  2),
                     (-2,  1), (-2,  0), (-2, -1), (-1, -2)
                 ],
-                idx: vec![0, 6, 3, 9, 1, 2, 4, 5, 7, 8, 10, 11],
-                cmp: vec![1, 1, 1, 1, 3, 3, 3, 3, 3, 3, 3, 3],
                 radius: 3,
-                n: 9
+                n: 7
             },
             FastType::TYPE_9_16 => FastContext {
                 offsets: vec![
                     (0, -3), (1,  -3), (2, - 2), (3,  -1),
                     (3,  0), (3,   1), (2,   2), (1,   3),
                     (0,  3), (-1,  3), (-2,  2), (-3,  1),
-                    (-3, 0), (-3, -1), (-2, -2), (-1, -3) 
+                    (-3, 0), (-3, -1), (-2, -2), (-1, -3)
                 ],
-                idx: vec![0, 8, 4, 12, 1, 2, 3, 5, 6, 7, 9, 10, 11, 13, 14, 15],
-                cmp: vec![1, 1, 1, 1, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4],
                 radius: 4,
-                n: 12
+                n: 9
             }
         }
     }
 }
 
 /// 执行FAST算法以检测图像中的关键点
-pub fn fast(img: &image::GrayImage, fast_type: Option<FastType>, threshold: Option<i32>) -> Result<Vec<FastKeypoint>, ImageError> {
+///
+/// `nonmax_suppression` 默认开启，在3x3邻域内只保留FAST得分最大的角点。
+/// 关键点按 `score_type` 指定的得分从高到低排序，默认使用Harris响应，并带有半径为 `DEFAULT_MOMENT_RADIUS` 的矩
+///
+/// # 返回值
+///
+/// 图像的宽或高小于 `2 * radius + 1` 时返回 `OrbError::ImageTooSmall`，阈值为负数时返回 `OrbError::InvalidParameter`
+pub fn fast(img: &image::GrayImage, fast_type: Option<FastType>, threshold: Option<i32>, score_type: Option<ScoreType>, nonmax_suppression: Option<bool>) -> Result<Vec<FastKeypoint>, OrbError> {
+    let mut keypoints = fast_corners(img, fast_type, threshold, score_type, nonmax_suppression)?;
+    compute_default_moments(img, &mut keypoints);
+    Ok(keypoints)
+}
+
+/// 与 `fast` 相同但不计算矩，供先筛选关键点、再按需计算矩的调用方使用
+pub(crate) fn fast_corners(img: &GrayImage, fast_type: Option<FastType>, threshold: Option<i32>, score_type: Option<ScoreType>, nonmax_suppression: Option<bool>) -> Result<Vec<FastKeypoint>, OrbError> {
     let threshold = threshold.unwrap_or(DEFAULT_FAST_THRESHOLD);
     let fast_type = fast_type.unwrap_or(FastType::TYPE_9_16);
+    let score_type = score_type.unwrap_or_default();
+    let nonmax_suppression = nonmax_suppression.unwrap_or(true);
+
+    if threshold < 0 {
+        return Err(OrbError::invalid_parameter("threshold", format!("must not be negative, got {}", threshold)));
+    }
 
     let ctx = fast_type.get_context();
+    detect_segment(img, ctx.radius, score_type, nonmax_suppression, |y, found| {
+        for x in ctx.radius .. img.width()-ctx.radius {
+            if let Some(score) = segment_score(img, &ctx, x as i32, y as i32, threshold) {
+                found.push(((x as i32, y as i32), score));
+            }
+        }
+    })
+}
+
+/// 只在掩码允许的位置执行FAST算法
+///
+/// 与OpenCV检测器的 `mask` 参数相同，在整幅图像上检测和非极大值抑制后丢弃掩码之外的关键点，
+/// 其余参数和排序与 `fast` 一致
+///
+/// # 返回值
+///
+/// 掩码图像与输入图像大小不同时返回 `OrbError::InvalidParameter`，其余错误与 `fast` 相同
+pub fn fast_with_mask(img: &GrayImage, mask: &DetectionMask, fast_type: Option<FastType>, threshold: Option<i32>, score_type: Option<ScoreType>, nonmax_suppression: Option<bool>) -> Result<Vec<FastKeypoint>, OrbError> {
+    mask.validate(img.width(), img.height())?;
+    let mut keypoints = fast_corners(img, fast_type, threshold, score_type, nonmax_suppression)?;
+    keypoints.retain(|k| mask.allows((k.location.0 as f32, k.location.1 as f32)));
+    compute_default_moments(img, &mut keypoints);
+    Ok(keypoints)
+}
+
+/// 分段测试类检测器的公共流程：按行带扫描、非极大值抑制、评分并排序
+///
+/// 返回的关键点不带矩，由调用方在筛选之后计算
+///
+/// # 参数
+///
+/// * `img` - 输入图像
+/// * `border` - 跳过的图像边界宽度
+/// * `score_type` - 关键点评分方式
+/// * `nonmax_suppression` - 是否按分段测试得分执行3x3非极大值抑制
+/// * `scan_row` - 按行优先顺序把第 `y` 行中的角点及其分段测试得分追加到向量中
+///
+/// # 返回值
+///
+/// 图像的宽或高小于 `2 * border + 1` 时返回 `OrbError::ImageTooSmall`
+pub(crate) fn detect_segment<F>(img: &GrayImage, border: u32, score_type: ScoreType, nonmax_suppression: bool, scan_row: F) -> Result<Vec<FastKeypoint>, OrbError>
+where
+    F: Fn(u32, &mut Vec<(Point, i32)>) + Sync + Send
+{
+    error::check_image_size(img.width(), img.height(), 2 * border + 1)?;
+    let width = img.width() as usize;
+
+    // 按行带执行分段测试，每个行带内按行优先顺序返回角点
+    let (y_start, y_end) = (border, img.height() - border);
+    let n_bands = (y_end - y_start).div_ceil(ROW_BAND_HEIGHT) as usize;
+    let bands:Vec<Vec<(Point, i32)>> = parallel::map_range(n_bands, |band| {
+        let band_start = y_start + band as u32 * ROW_BAND_HEIGHT;
+        let band_end = (band_start + ROW_BAND_HEIGHT).min(y_end);
+
+        let mut found = vec![];
+        for y in band_start..band_end {
+            scan_row(y, &mut found);
+        }
+        found
+    });
+
+    // 非角点的得分为-1
+    let mut score_map = vec![-1i32; width * img.height() as usize];
+    let mut corners:Vec<Point> = vec![];
+    for (point, score) in bands.into_iter().flatten() {
+        score_map[point.1 as usize * width + point.0 as usize] = score;
+        corners.push(point);
+    }
+
+    if nonmax_suppression {
+        corners = nonmax_suppression_3x3(&score_map, width, &corners);
+    }
+
+    let mut fast_keypoint_matches:Vec<FastKeypoint> = parallel::map(&corners, |&point| {
+        let score = match score_type {
+            ScoreType::Fast => score_map[point.1 as usize * width + point.0 as usize] as f32,
+            ScoreType::Harris | ScoreType::ShiTomasi => corner_response(img, &point, score_type)
+        };
+
+        FastKeypoint {
+            location: point,
+            position: (point.0 as f32, point.1 as f32),
+            score,
+            nms_dist: 0,
+            moment: Moment::default()
+        }
+    });
+
+    // 按得分排序，稳定排序保证得分相同时的顺序与检测顺序一致
+    fast_keypoint_matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(fast_keypoint_matches)
+}
+
+/// 单尺度的FAST检测器，参数含义与 `fast` 一致，`None` 时使用 `fast` 的默认值
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FastDetector {
+    pub fast_type: Option<FastType>, // FAST的圆周类型
+    pub threshold: Option<i32>, // FAST阈值
+    pub score_type: Option<ScoreType>, // 关键点评分方式
+    pub nonmax_suppression: Option<bool>, // 是否执行3x3非极大值抑制
+    pub subpixel: Option<SubpixelMethod> // 亚像素细化方法，`None` 时不细化
+}
+
+impl FastDetector {
+    /// 创建FAST检测器
+    pub fn new(fast_type: Option<FastType>, threshold: Option<i32>, score_type: Option<ScoreType>, nonmax_suppression: Option<bool>, subpixel: Option<SubpixelMethod>) -> FastDetector {
+        FastDetector { fast_type, threshold, score_type, nonmax_suppression, subpixel }
+    }
+}
+
+impl FeatureDetector for FastDetector {
+    fn detect(&self, img: &DynamicImage) -> Result<Vec<KeyPoint>, OrbError> {
+        let fast_type = self.fast_type.unwrap_or_default();
+        // 关键点直径为圆周的直径，与OpenCV的FAST一致
+        let size = match fast_type {
+            FastType::TYPE_5_8 => 3.0,
+            FastType::TYPE_7_12 => 5.0,
+            FastType::TYPE_9_16 => 7.0
+        };
+
+        let gray_img = img.to_luma8();
+        let mut keypoints = fast(&gray_img, Some(fast_type), self.threshold, self.score_type, self.nonmax_suppression)?;
+        if let Some(method) = self.subpixel {
+            refine_subpixel(&gray_img, &mut keypoints, method);
+        }
+        Ok(keypoints.into_iter()
+            .map(|k| KeyPoint {
+                pt: k.position,
+                size,
+                angle: k.moment.angle,
+                response: k.score,
+                octave: 0,
+                class_id: -1
+            })
+            .collect())
+    }
+}
+
+/// 对单个像素执行分段测试
+///
+/// 圆周上存在至少 `n` 个首尾相接的像素全部亮于 `center + threshold` 或全部暗于 `center - threshold` 时为角点，
+/// 弧段可以跨过圆周的起点。任意长度为 `n` 的弧段至少包含四个等分点中的两个，因此先用等分点快速排除
+///
+/// # 返回值
+///
+/// 像素是角点时返回圆周上超过阈值的像素差绝对值之和，否则返回 `None`
+pub(crate) fn segment_score(img: &GrayImage, ctx: &FastContext, x: i32, y: i32, threshold: i32) -> Option<i32> {
+    let center_pixel = img.get_pixel(x as u32, y as u32).0[0] as i32;
+    let diff = |i: usize| {
+        let (dx, dy) = ctx.offsets[i];
+        img.get_pixel((x + dx) as u32, (y + dy) as u32).0[0] as i32 - center_pixel
+    };
+
+    let len = ctx.offsets.len();
+    let (mut brighter, mut darker) = (0, 0);
+    for i in (0..len).step_by(len / 4) {
+        let d = diff(i);
+        brighter += (d > threshold) as u32;
+        darker += (d < -threshold) as u32;
+    }
+    if brighter < 2 && darker < 2 {
+        return None;
+    }
+
+    let mut diffs = [0i32; MAX_CIRCLE_LENGTH];
+    for (i, d) in diffs.iter_mut().enumerate().take(len) {
+        *d = diff(i);
+    }
+    let has_arc = |is_member: &dyn Fn(i32) -> bool| {
+        // 遍历两圈以处理跨过起点的弧段
+        let mut run = 0;
+        for i in 0..len + ctx.n as usize - 1 {
+            run = if is_member(diffs[i % len]) { run + 1 } else { 0 };
+            if run >= ctx.n {
+                return true;
+            }
+        }
+        false
+    };
+
+    if !((brighter >= 2 && has_arc(&|d| d > threshold)) || (darker >= 2 && has_arc(&|d| d < -threshold))) {
+        return None;
+    }
+
+    Some(diffs[..len].iter().map(|d| d.abs()).filter(|&d| d > threshold).sum())
+}
 
-    let mut fast_keypoint_matches = Vec::<FastKeypoint>::new();
-
-    for y in ctx.radius .. img.height()-ctx.radius {
-        'x_loop: for x in ctx.radius .. img.width()-ctx.radius {
-            let center_pixel = img.get_pixel(x, y).0[0] as i32;
-            let x = x as i32;
-            let y = y as i32;
-            let point:Point = (x, y);
-
-            let mut score:i32 = 0;
-            let mut similars:i32 = 0;
-
-            for idx in 0..ctx.offsets.len() {
-                let px_idx = ctx.idx[idx];
-                let px = img.get_pixel((x + ctx.offsets[px_idx].0) as u32, (y + ctx.offsets[px_idx].1) as u32).0[0] as i32;
-                let diff = (px - center_pixel).abs();
-                
-                if diff < threshold {
-                    similars += 1;
-                    if similars > ctx.cmp[idx] {
-                        continue 'x_loop;
+/// 位掩码表示的 `len` 个圆周像素中是否存在至少 `n` 个连续像素，包括跨过起点的弧段
+pub(crate) fn has_arc(set: u16, len: usize, n: usize) -> bool {
+    // 复制一圈后，每次与右移一位的自身相与，剩下的位是长度足够的连续段的起点
+    let mut runs = set as u32 | (set as u32) << len;
+    for _ in 1..n {
+        runs &= runs >> 1;
+    }
+    runs & ((1 << len) - 1) != 0
+}
+
+/// 在3x3邻域内执行非极大值抑制
+///
+/// 角点的得分必须不小于所有相邻角点；得分相同时保留按行优先顺序最先出现的角点，
+/// 因此结果与输入顺序无关。每个角点只检查8个邻居，时间复杂度与图像大小成线性关系。
+///
+/// # 参数
+///
+/// * `score_map` - 按行存储的得分图，非角点为-1
+/// * `width` - 得分图的宽度
+/// * `corners` - 候选角点
+///
+/// # 返回值
+///
+/// 返回保留下来的角点
+fn nonmax_suppression_3x3(score_map: &[i32], width: usize, corners: &[Point]) -> Vec<Point> {
+    let height = score_map.len() / width;
+
+    corners.iter()
+        .copied()
+        .filter(|&(x, y)| {
+            let index = y as usize * width + x as usize;
+            let score = score_map[index];
+
+            for dy in -1i32..=1 {
+                for dx in -1i32..=1 {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if (dx == 0 && dy == 0) || nx < 0 || ny < 0 || nx as usize >= width || ny as usize >= height {
+                        continue;
+                    }
+
+                    let neighbour_index = ny as usize * width + nx as usize;
+                    let neighbour = score_map[neighbour_index];
+                    // 先出现的邻居得分相同时胜出
+                    let suppressed = if neighbour_index < index { neighbour >= score } else { neighbour > score };
+                    if suppressed {
+                        return false;
                     }
-                } else {
-                    score += diff;
                 }
             }
 
-            let moment = moment_centroid(img, &point, None);
-            fast_keypoint_matches.push(FastKeypoint {
-                location: point,
-                score: score,
-                nms_dist: 0,
-                moment: moment
-            });
+            true
+        })
+        .collect()
+}
+
+/// 计算关键点周围窗口内的Harris响应或Shi-Tomasi最小特征值
+///
+/// 梯度使用3x3 Sobel算子，并按OpenCV的方式归一化，窗口超出图像的部分按边缘像素处理
+fn corner_response(img: &GrayImage, point: &Point, score_type: ScoreType) -> f32 {
+    let (width, height) = (img.width() as i32, img.height() as i32);
+    let px = |x: i32, y: i32| img.get_pixel(x.clamp(0, width - 1) as u32, y.clamp(0, height - 1) as u32).0[0] as i32;
+
+    let r = HARRIS_BLOCK_SIZE / 2;
+    let (mut a, mut b, mut c) = (0i64, 0i64, 0i64);
+    for y in point.1 - r..=point.1 + r {
+        for x in point.0 - r..=point.0 + r {
+            let ix = (px(x + 1, y - 1) - px(x - 1, y - 1))
+                + 2 * (px(x + 1, y) - px(x - 1, y))
+                + (px(x + 1, y + 1) - px(x - 1, y + 1));
+            let iy = (px(x - 1, y + 1) - px(x - 1, y - 1))
+                + 2 * (px(x, y + 1) - px(x, y - 1))
+                + (px(x + 1, y + 1) - px(x + 1, y - 1));
+            a += (ix * ix) as i64;
+            b += (iy * iy) as i64;
+            c += (ix * iy) as i64;
         }
     }
 
-    // 按得分排序
-    fast_keypoint_matches.sort_by(|a, b| b.score.cmp(&a.score));
-    Ok(fast_keypoint_matches)
+    // 归一化到与图像位深和窗口大小无关的尺度
+    let scale = 1.0 / (4.0 * HARRIS_BLOCK_SIZE as f64 * 255.0);
+    let scale_sq = scale * scale;
+    let (a, b, c) = (a as f64 * scale_sq, b as f64 * scale_sq, c as f64 * scale_sq);
+
+    let response = match score_type {
+        ScoreType::ShiTomasi => ((a + b) - ((a - b) * (a - b) + 4.0 * c * c).sqrt()) * 0.5,
+        _ => a * b - c * c - HARRIS_K as f64 * (a + b) * (a + b)
+    };
+
+    response as f32
 }
 
-/// 表示图像的矩信息
-#[derive(Debug, Clone, Copy)]
+/// 将关键点细化到亚像素精度，结果写入 `position`
+///
+/// 拟合失败或位移超出拟合范围（二次曲面为3个像素，梯度法为窗口半径）的关键点保持整数位置
+///
+/// # 参数
+///
+/// * `img` - 检测关键点的图像
+/// * `keypoints` - 关键点
+/// * `method` - 细化方法
+pub fn refine_subpixel(img: &GrayImage, keypoints: &mut [FastKeypoint], method: SubpixelMethod) {
+    parallel::for_each_mut(keypoints, |k| {
+        let refined = match method {
+            SubpixelMethod::Quadratic => quadratic_offset(img, &k.location),
+            SubpixelMethod::Gradient { half_window, max_iterations } => gradient_offset(img, &k.location, half_window, max_iterations)
+        };
+        let (dx, dy) = refined.unwrap_or((0.0, 0.0));
+        k.position = (k.location.0 as f32 + dx, k.location.1 as f32 + dy);
+    });
+}
+
+/// 对3x3邻域的Harris响应拟合二次曲面，返回极值点相对关键点的偏移
+///
+/// 与SIFT的关键点定位相同，极值点偏离拟合中心超过半个像素时将中心移到相邻像素重新拟合
+fn quadratic_offset(img: &GrayImage, point: &Point) -> Option<(f32, f32)> {
+    let mut center = *point;
+
+    for _ in 0..=QUADRATIC_MAX_STEPS {
+        let r = |dx: i32, dy: i32| corner_response(img, &(center.0 + dx, center.1 + dy), ScoreType::Harris) as f64;
+        let value = r(0, 0);
+
+        let dx = (r(1, 0) - r(-1, 0)) * 0.5;
+        let dy = (r(0, 1) - r(0, -1)) * 0.5;
+        let dxx = r(1, 0) - 2.0 * value + r(-1, 0);
+        let dyy = r(0, 1) - 2.0 * value + r(0, -1);
+        let dxy = (r(1, 1) - r(1, -1) - r(-1, 1) + r(-1, -1)) * 0.25;
+
+        // 只接受极大值：Hessian负定
+        let det = dxx * dyy - dxy * dxy;
+        if !(det > 0.0 && dxx < 0.0) {
+            return None;
+        }
+
+        let ox = -(dyy * dx - dxy * dy) / det;
+        let oy = -(dxx * dy - dxy * dx) / det;
+        if ox.abs() <= 0.5 && oy.abs() <= 0.5 {
+            return Some(((center.0 - point.0) as f32 + ox as f32, (center.1 - point.1) as f32 + oy as f32));
+        }
+
+        center.0 += (ox.abs() > 0.5) as i32 * ox.signum() as i32;
+        center.1 += (oy.abs() > 0.5) as i32 * oy.signum() as i32;
+    }
+
+    None
+}
+
+/// 双线性插值读取像素，超出图像的部分按边缘像素处理
+fn bilinear(img: &GrayImage, x: f32, y: f32) -> f32 {
+    let (width, height) = (img.width() as i32, img.height() as i32);
+    let px = |x: i32, y: i32| img.get_pixel(x.clamp(0, width - 1) as u32, y.clamp(0, height - 1) as u32).0[0] as f32;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i32, y0 as i32);
+
+    (px(x0, y0) * (1.0 - fx) + px(x0 + 1, y0) * fx) * (1.0 - fy)
+        + (px(x0, y0 + 1) * (1.0 - fx) + px(x0 + 1, y0 + 1) * fx) * fy
+}
+
+/// 迭代求解窗口内梯度与到角点的向量正交的位置，返回相对中心的偏移
+fn gradient_offset(img: &GrayImage, point: &Point, half_window: u32, max_iterations: u32) -> Option<(f32, f32)> {
+    let w = half_window.max(1) as i32;
+    // 与OpenCV相同的高斯权重，窗口边缘的权重约为 1/e
+    let weight = |i: i32| (-((i * i) as f32) / (w * w) as f32).exp();
+    let (mut cx, mut cy) = (point.0 as f32, point.1 as f32);
+
+    for _ in 0..max_iterations {
+        let (mut a, mut b, mut c, mut bb1, mut bb2) = (0f32, 0f32, 0f32, 0f32, 0f32);
+        for j in -w..=w {
+            for i in -w..=w {
+                let (x, y) = (cx + i as f32, cy + j as f32);
+                let gx = (bilinear(img, x + 1.0, y) - bilinear(img, x - 1.0, y)) * 0.5;
+                let gy = (bilinear(img, x, y + 1.0) - bilinear(img, x, y - 1.0)) * 0.5;
+                let m = weight(i) * weight(j);
+                let (gxx, gxy, gyy) = (gx * gx * m, gx * gy * m, gy * gy * m);
+
+                a += gxx;
+                b += gxy;
+                c += gyy;
+                bb1 += gxx * i as f32 + gxy * j as f32;
+                bb2 += gxy * i as f32 + gyy * j as f32;
+            }
+        }
+
+        let det = a * c - b * b;
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let (sx, sy) = ((c * bb1 - b * bb2) / det, (a * bb2 - b * bb1) / det);
+        cx += sx;
+        cy += sy;
+        if sx * sx + sy * sy < SUBPIXEL_EPSILON * SUBPIXEL_EPSILON {
+            break;
+        }
+    }
+
+    let offset = (cx - point.0 as f32, cy - point.1 as f32);
+    (offset.0.abs() <= w as f32 && offset.1.abs() <= w as f32).then_some(offset)
+}
+
+/// 表示关键点周围圆形图像块的灰度质心
+///
+/// 矩以关键点为原点计算，`m10` 和 `m01` 为带符号的整数和，不会因图像尺寸而溢出
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct Moment {
-    pub centroid: Point, // 质心
-    pub moment: Point, // 矩
-    pub rotation: f64 // 旋转角度
+    pub m00: i64, // 零阶矩，即图像块内的灰度和
+    pub m10: i64, // x方向的一阶矩
+    pub m01: i64, // y方向的一阶矩
+    pub centroid: (f32, f32), // 质心相对关键点的偏移，图像块全黑时为(0, 0)
+    pub rotation: f64, // 关键点指向质心的方向，单位为弧度，范围为(-π, π]
+    pub angle: f32 // 与 `rotation` 相同的方向，单位为度，范围为[0, 360)
 }
 
-fn patch_moment(img: &GrayImage, x:u32, y:u32, x_moment:u32, y_moment:u32, radius:Option<u32>) -> f64 {
-    let radius = radius.unwrap_or(5);
+/// 图像块超出图像边界时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BorderPolicy {
+    #[default]
+    Skip, // 忽略图像外的像素，只对图像内的部分求矩
+    Clamp // 图像外的像素取最近的边界像素
+}
 
-    if x < radius || y < radius || x + radius >= img.width() || y + radius >= img.height() {
-        return 1.0;
+/// 计算半径为 `radius` 的圆形图像块每一行的半宽
+///
+/// 与OpenCV的ORB相同，先按圆的方程计算45度以下的行，再按对称性补齐其余行，
+/// 保证图像块关于对角线对称，从而旋转后的方向与图像旋转一致
+///
+/// # 返回值
+///
+/// 返回长度为 `radius + 1` 的向量，第 `v` 个元素是第 `±v` 行的半宽；半径为0时图像块只有中心像素
+pub fn circular_patch_extent(radius: u32) -> Vec<i32> {
+    if radius == 0 {
+        return vec![0];
     }
 
-    let mut patch_sum:u32 = 0;
-    for mx in (x-radius)..=(x+radius) {
-        for my in (y-radius)..=(y+radius) {
-            let coefficient = match (x_moment, y_moment) {
-                (0, 0) => 1,
-                (0, 1) => my,
-                (1, 0) => mx,
-                _ => 0
-            };
-            patch_sum += coefficient * img.get_pixel(mx, my).0[0] as u32;
+    let radius = radius as i32;
+    let mut u_max = vec![0i32; radius as usize + 2];
+    let v_max = (radius as f64 * std::f64::consts::SQRT_2 / 2.0 + 1.0).floor() as i32;
+    let v_min = (radius as f64 * std::f64::consts::SQRT_2 / 2.0).ceil() as i32;
+
+    for v in 0..=v_max.min(radius) {
+        u_max[v as usize] = ((radius * radius - v * v) as f64).sqrt().round() as i32;
+    }
+
+    // 按对角线对称补齐45度以上的行
+    let mut v0 = 0;
+    let mut v = radius;
+    while v >= v_min {
+        while u_max[v0] == u_max[v0 + 1] {
+            v0 += 1;
         }
+        u_max[v as usize] = v0 as i32;
+        v0 += 1;
+        v -= 1;
     }
 
-    patch_sum as f64
+    u_max.truncate(radius as usize + 1);
+    u_max
 }
 
-fn moment_centroid(img: &GrayImage, point: &Point, moment_radius:Option<u32>) -> Moment {
-    let p_m = patch_moment(img, point.0 as u32, point.1 as u32, 0, 0, moment_radius);
-    let p_x = patch_moment(img, point.0 as u32, point.1 as u32, 1, 0, moment_radius);
-    let p_y = patch_moment(img, point.0 as u32, point.1 as u32, 0, 1, moment_radius);
+/// 计算以 `point` 为中心的圆形图像块的灰度质心
+///
+/// # 参数
+///
+/// * `u_max` - `circular_patch_extent` 返回的每行半宽
+/// * `border` - 图像块超出图像时的处理方式
+fn moment_centroid(img: &GrayImage, point: &Point, u_max: &[i32], border: BorderPolicy) -> Moment {
+    let (width, height) = (img.width() as i32, img.height() as i32);
+    let (mut m00, mut m10, mut m01) = (0i64, 0i64, 0i64);
+
+    let radius = u_max.len() as i32 - 1;
+    for v in -radius..=radius {
+        let y = point.1 + v;
+        if border == BorderPolicy::Skip && (y < 0 || y >= height) {
+            continue;
+        }
 
-    let (mx, my) = (
-        (p_x/p_m),
-        (p_y/p_m)
-    );
+        let half_width = u_max[v.unsigned_abs() as usize];
+        for u in -half_width..=half_width {
+            let x = point.0 + u;
+            let value = match border {
+                BorderPolicy::Skip if x < 0 || x >= width => continue,
+                BorderPolicy::Skip => img.get_pixel(x as u32, y as u32).0[0],
+                BorderPolicy::Clamp => img.get_pixel(x.clamp(0, width - 1) as u32, y.clamp(0, height - 1) as u32).0[0]
+            } as i64;
+
+            m00 += value;
+            m10 += u as i64 * value;
+            m01 += v as i64 * value;
+        }
+    }
 
-    let x_diff = (point.0 as f64 - mx) as f64;
-    let y_diff = (point.1 as f64 - my) as f64;
+    let centroid = if m00 == 0 { (0.0, 0.0) } else { ((m10 as f64 / m00 as f64) as f32, (m01 as f64 / m00 as f64) as f32) };
+    // y轴向下，角度按图像坐标系顺时针增加，与OpenCV一致
+    let rotation = (m01 as f64).atan2(m10 as f64);
 
     Moment {
-        centroid: point.clone(),
-        moment: (mx.round() as i32, my.round() as i32),
-        rotation: y_diff.atan2(x_diff)
+        m00,
+        m10,
+        m01,
+        centroid,
+        rotation,
+        angle: (rotation.to_degrees() as f32).rem_euclid(360.0) % 360.0
     }
 }
 
-pub fn draw_moments(img: &mut image::RgbaImage, vec: &Vec<FastKeypoint>) {
+/// 以半径 `DEFAULT_MOMENT_RADIUS` 计算公开检测函数返回的关键点的矩
+pub(crate) fn compute_default_moments(img: &GrayImage, keypoints: &mut [FastKeypoint]) {
+    compute_moments(img, keypoints, DEFAULT_MOMENT_RADIUS, None);
+}
+
+/// 使用给定的圆形图像块半径重新计算关键点的矩
+///
+/// `fast` 使用半径 `DEFAULT_MOMENT_RADIUS` 计算矩，ORB在选取关键点之后按采样图像块的半径计算。
+/// `border` 为 `None` 时忽略图像外的像素
+pub fn compute_moments(img: &GrayImage, keypoints: &mut [FastKeypoint], radius: u32, border: Option<BorderPolicy>) {
+    let border = border.unwrap_or_default();
+    let u_max = circular_patch_extent(radius);
+    parallel::for_each_mut(keypoints, |k| {
+        k.moment = moment_centroid(img, &k.location, &u_max, border);
+    });
+}
+
+pub fn draw_moments(img: &mut image::RgbaImage, vec: &[FastKeypoint]) {
     let ctx = FastType::TYPE_9_16.get_context();
 
+    // 按最大得分归一化，使颜色和线段长度与评分方式无关
+    let max_score = vec.iter().map(|k| k.score).fold(f32::EPSILON, f32::max);
+
     for k in vec {
-        let score = (k.score / 300) as u8;
+        let strength = (k.score / max_score).clamp(0.0, 1.0);
+        let score = (strength * 255.0) as u8;
         let color = [score, 0, 122, 125];
 
-        let start_point = k.location;
+        let start_point = k.position;
 
         let rotation_radians = Rad(k.moment.rotation);
-        let dist = (score * 5) as f64;
+        let dist = strength as f64 * 15.0;
 
         let end_point = (
-            start_point.0 as f32 + (dist * Rad::cos(rotation_radians)).round() as f32,
-            start_point.1 as f32 + (dist * Rad::sin(rotation_radians)).round() as f32
+            start_point.0 + (dist * Rad::cos(rotation_radians)) as f32,
+            start_point.1 + (dist * Rad::sin(rotation_radians)) as f32
         );
 
         draw_line_segment_mut(
             img,
-            (start_point.0 as f32, start_point.1 as f32),
+            start_point,
             end_point,
             Rgba([0, 0, 0, 125])
         );
@@ -218,4 +714,63 @@ pub fn draw_moments(img: &mut image::RgbaImage, vec: &Vec<FastKeypoint>) {
                 img.get_pixel_mut((k.location.0 + dx) as u32, (k.location.1 + dy) as u32).0 = color;
             });
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use image::Luma;
+
+    use super::*;
+
+    #[test]
+    fn circular_patch_extent_matches_opencv_umax() {
+        assert_eq!(circular_patch_extent(0), vec![0]);
+        assert_eq!(circular_patch_extent(1), vec![1, 0]);
+        assert_eq!(circular_patch_extent(3), vec![3, 3, 2, 1]);
+        // OpenCV的ORB在HALF_PATCH_SIZE为15时的umax
+        assert_eq!(circular_patch_extent(15), vec![15, 15, 15, 15, 14, 14, 14, 13, 13, 12, 11, 10, 9, 8, 6, 3]);
+
+        // 图像块关于对角线对称：第v行的半宽不小于u，当且仅当第u行的半宽不小于v
+        for radius in 1..40 {
+            let u_max = circular_patch_extent(radius);
+            for v in 0..u_max.len() {
+                for u in 0..u_max.len() {
+                    assert_eq!(u_max[v] >= u as i32, u_max[u] >= v as i32, "radius {} ({}, {})", radius, u, v);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn moment_angle_follows_intensity_gradient() {
+        let center:Point = (32, 32);
+        // 亮度沿 (gx, gy) 方向线性增加，在半径15的图像块内不会截断；y轴向下，角度按顺时针计算
+        for ((gx, gy), expected) in [((1, 0), 0.0), ((1, 1), 45.0), ((0, 1), 90.0), ((-1, 0), 180.0), ((0, -1), 270.0), ((2, -1), 360.0 - 26.56505)] {
+            let img = GrayImage::from_fn(64, 64, |x, y| Luma([(128 + 3 * (gx * (x as i32 - 32) + gy * (y as i32 - 32))).clamp(0, 255) as u8]));
+            let mut keypoints = [FastKeypoint { location: center, position: (32.0, 32.0), score: 0.0, nms_dist: 0, moment: Moment::default() }];
+
+            compute_moments(&img, &mut keypoints, 15, None);
+            let moment = keypoints[0].moment;
+            assert!((moment.angle - expected).abs() < 1e-3, "gradient ({}, {}): angle {}", gx, gy, moment.angle);
+            assert!((moment.rotation.to_degrees().rem_euclid(360.0) - expected as f64).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn compute_moments_accepts_radius_zero_and_borders() {
+        let img = GrayImage::from_fn(16, 16, |x, _| Luma([(x * 10) as u8]));
+        let mut keypoints = [
+            FastKeypoint { location: (8, 8), position: (8.0, 8.0), score: 0.0, nms_dist: 0, moment: Moment::default() },
+            FastKeypoint { location: (0, 0), position: (0.0, 0.0), score: 0.0, nms_dist: 0, moment: Moment::default() }
+        ];
+
+        compute_moments(&img, &mut keypoints, 0, None);
+        assert_eq!((keypoints[0].moment.m00, keypoints[0].moment.m10, keypoints[0].moment.m01), (80, 0, 0));
+        assert_eq!(keypoints[0].moment.angle, 0.0);
+
+        // 角落处只对图像内的部分求矩，亮度向右增加，方向指向右下方
+        compute_moments(&img, &mut keypoints, 5, Some(BorderPolicy::Skip));
+        assert!(keypoints[1].moment.angle > 0.0 && keypoints[1].moment.angle < 90.0);
+        compute_moments(&img, &mut keypoints, 5, Some(BorderPolicy::Clamp));
+        assert!(keypoints[1].moment.angle.abs() < 1e-3);
+    }
+}
@@ -0,0 +1,457 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use image::GrayImage;
+
+use crate::{fast, error, parallel};
+use fast::{FastType, FastKeypoint, ScoreType};
+use error::OrbError;
+
+// 常量
+const DEFAULT_TRAINING_THRESHOLD:i32 = 20; // 默认的训练阈值，只影响样本的统计分布
+const DEFAULT_DETECT_THRESHOLD:i32 = 50; // 检测时的默认阈值，与 `fast::fast` 一致
+
+/// 圆周像素相对中心像素的三种状态，与子节点的顺序一致
+const DARKER:usize = 0; // 暗于中心像素减阈值
+const SIMILAR:usize = 1; // 与中心像素相差不超过阈值
+const BRIGHTER:usize = 2; // 亮于中心像素加阈值
+
+/// 表示决策树的节点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TreeNode {
+    Leaf(bool), // 叶节点，是否为角点
+    Test {
+        pixel: usize, // 比较的圆周像素序号
+        children: [usize; 3] // 更暗、相近、更亮时进入的子节点
+    } // 内部节点
+}
+
+/// 表示一个训练样本，即圆周像素状态的一种组合
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    brighter: u16, // 更亮的圆周像素位掩码
+    darker: u16, // 更暗的圆周像素位掩码
+    count: u64, // 该组合在训练图像中出现的次数
+    corner: bool // 分段测试的结果
+}
+
+impl Sample {
+    /// 第 `i` 个圆周像素的状态
+    fn state(&self, i: usize) -> usize {
+        if self.brighter & 1 << i != 0 {
+            BRIGHTER
+        } else if self.darker & 1 << i != 0 {
+            DARKER
+        } else {
+            SIMILAR
+        }
+    }
+}
+
+/// 表示决策树某个节点上已经比较过的圆周像素
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PartialState {
+    known: u16, // 已比较的像素
+    brighter: u16, // 已知更亮的像素
+    darker: u16 // 已知更暗的像素
+}
+
+impl PartialState {
+    /// 比较第 `i` 个像素得到 `state` 后的状态
+    fn with(&self, i: usize, state: usize) -> PartialState {
+        let bit = 1 << i;
+        PartialState {
+            known: self.known | bit,
+            brighter: self.brighter | if state == BRIGHTER { bit } else { 0 },
+            darker: self.darker | if state == DARKER { bit } else { 0 }
+        }
+    }
+}
+
+/// 用ID3算法学习的FAST决策树
+///
+/// 决策树按FAST论文的方法训练：以训练图像中的每个像素作为样本，按圆周像素的三种状态划分，
+/// 每次选择信息增益最大的像素比较。只有已比较的像素已经能确定分段测试的结果时才会生成叶节点，
+/// 训练图像中没有出现的组合也会按固定顺序继续比较，因此对任意图像和阈值，
+/// 结果都与 `fast::fast` 的分段测试完全一致，训练数据只影响平均比较次数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FastTree {
+    fast_type: FastType, // 决策树对应的圆周类型
+    nodes: Vec<TreeNode>, // 按先序排列的节点，第一个节点为根节点
+    n_samples: u64 // 训练使用的像素数
+}
+
+impl FastTree {
+    /// 决策树对应的圆周类型
+    pub fn fast_type(&self) -> FastType {
+        self.fast_type
+    }
+
+    /// 节点数量
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// 训练使用的像素数
+    pub fn n_samples(&self) -> u64 {
+        self.n_samples
+    }
+
+    /// 最多需要比较的像素数
+    pub fn depth(&self) -> usize {
+        fn depth(nodes: &[TreeNode], index: usize) -> usize {
+            match nodes[index] {
+                TreeNode::Leaf(_) => 0,
+                TreeNode::Test { children, .. } => 1 + children.iter().map(|&c| depth(nodes, c)).max().unwrap_or(0)
+            }
+        }
+        depth(&self.nodes, 0)
+    }
+
+    /// 沿决策树判断一个像素是否为角点
+    ///
+    /// # 参数
+    ///
+    /// * `pixel` - 返回第 `i` 个圆周像素与中心像素的差，只会对需要比较的像素调用
+    /// * `threshold` - FAST阈值
+    pub fn is_corner(&self, pixel: impl Fn(usize) -> i32, threshold: i32) -> bool {
+        let mut index = 0;
+        loop {
+            match self.nodes[index] {
+                TreeNode::Leaf(corner) => return corner,
+                TreeNode::Test { pixel: i, children } => {
+                    index = children[classify(pixel(i), threshold)];
+                }
+            }
+        }
+    }
+
+    /// 使用决策树检测图像中的关键点，结果与相同参数的 `fast::fast` 完全一致
+    ///
+    /// # 参数
+    ///
+    /// * `img` - 输入图像
+    /// * `threshold` - FAST阈值，默认为50
+    /// * `score_type` - 关键点评分方式，默认为Harris响应
+    /// * `nonmax_suppression` - 是否执行3x3非极大值抑制，默认开启
+    ///
+    /// # 返回值
+    ///
+    /// 错误与 `fast::fast` 相同
+    pub fn detect(&self, img: &GrayImage, threshold: Option<i32>, score_type: Option<ScoreType>, nonmax_suppression: Option<bool>) -> Result<Vec<FastKeypoint>, OrbError> {
+        detect_with(img, Some(self.fast_type), threshold, score_type, nonmax_suppression, |pixel, threshold| self.is_corner(pixel, threshold))
+    }
+
+    /// 生成与决策树等价的Rust函数源码
+    ///
+    /// 生成的函数签名为 `pub fn <fn_name>(pixel: &dyn Fn(usize) -> i32, threshold: i32) -> bool`，
+    /// 参数含义与 `is_corner` 相同，可以直接传给 `detect_with`
+    ///
+    /// # 参数
+    ///
+    /// * `fn_name` - 生成的函数名
+    pub fn to_rust_source(&self, fn_name: &str) -> String {
+        let ctx = self.fast_type.get_context();
+        let mut source = String::new();
+        writeln!(source, "/// 由 `bye_orb_rs::fast_tree` 从{}个训练像素生成的FAST-{}-{}决策树，共{}个节点",
+            self.n_samples, ctx.n, ctx.offsets.len(), self.nodes.len()).unwrap();
+        writeln!(source, "///").unwrap();
+        writeln!(source, "/// 圆周偏移量依次为 {:?}，`pixel(i)` 返回第 `i` 个圆周像素与中心像素的差", ctx.offsets).unwrap();
+        writeln!(source, "#[allow(clippy::all)]").unwrap();
+        writeln!(source, "pub fn {}(pixel: &dyn Fn(usize) -> i32, threshold: i32) -> bool {{", fn_name).unwrap();
+        self.write_node(&mut source, 0, 1);
+        writeln!(source, "}}").unwrap();
+        source
+    }
+
+    /// 以 `depth` 级缩进写出第 `index` 个节点
+    fn write_node(&self, source: &mut String, index: usize, depth: usize) {
+        let indent = "    ".repeat(depth);
+        match self.nodes[index] {
+            TreeNode::Leaf(corner) => {
+                writeln!(source, "{}{}", indent, corner).unwrap();
+            },
+            TreeNode::Test { pixel, children } => {
+                writeln!(source, "{}let p = pixel({});", indent, pixel).unwrap();
+                writeln!(source, "{}if p > threshold {{", indent).unwrap();
+                self.write_node(source, children[BRIGHTER], depth + 1);
+                writeln!(source, "{}}} else if p < -threshold {{", indent).unwrap();
+                self.write_node(source, children[DARKER], depth + 1);
+                writeln!(source, "{}}} else {{", indent).unwrap();
+                self.write_node(source, children[SIMILAR], depth + 1);
+                writeln!(source, "{}}}", indent).unwrap();
+            }
+        }
+    }
+}
+
+/// FAST决策树的训练器，逐张加入训练图像后训练
+#[derive(Debug, Clone)]
+pub struct FastTreeTrainer {
+    fast_type: FastType, // 圆周类型
+    threshold: i32, // 计算样本状态时使用的阈值
+    samples: HashMap<(u16, u16), (u64, bool)> // 每种状态组合的出现次数和分段测试结果
+}
+
+impl FastTreeTrainer {
+    /// 创建训练器
+    ///
+    /// # 参数
+    ///
+    /// * `fast_type` - 圆周类型，默认为 `TYPE_9_16`
+    /// * `threshold` - 计算样本状态时使用的阈值，默认为20；训练得到的决策树可以使用任意阈值，
+    ///   该阈值应接近检测时的阈值，使样本分布与实际使用时一致
+    ///
+    /// # 返回值
+    ///
+    /// 阈值为负数时返回 `OrbError::InvalidParameter`
+    pub fn new(fast_type: Option<FastType>, threshold: Option<i32>) -> Result<FastTreeTrainer, OrbError> {
+        let threshold = threshold.unwrap_or(DEFAULT_TRAINING_THRESHOLD);
+        if threshold < 0 {
+            return Err(OrbError::invalid_parameter("threshold", format!("must not be negative, got {}", threshold)));
+        }
+        Ok(FastTreeTrainer { fast_type: fast_type.unwrap_or_default(), threshold, samples: HashMap::new() })
+    }
+
+    /// 把图像中的每个像素加入训练样本，以 `fast::fast` 的分段测试结果作为标签
+    ///
+    /// # 返回值
+    ///
+    /// 图像的宽或高小于 `2 * radius + 1` 时返回 `OrbError::ImageTooSmall`
+    pub fn add_image(&mut self, img: &GrayImage) -> Result<&mut FastTreeTrainer, OrbError> {
+        let ctx = self.fast_type.get_context();
+        error::check_image_size(img.width(), img.height(), 2 * ctx.radius + 1)?;
+
+        let rows:Vec<Vec<Sample>> = parallel::map_range((img.height() - 2 * ctx.radius) as usize, |row| {
+            let y = row as u32 + ctx.radius;
+            (ctx.radius..img.width() - ctx.radius)
+                .map(|x| {
+                    let center = img.get_pixel(x, y).0[0] as i32;
+                    let (mut brighter, mut darker) = (0u16, 0u16);
+                    for (i, &(dx, dy)) in ctx.offsets.iter().enumerate() {
+                        let diff = img.get_pixel((x as i32 + dx) as u32, (y as i32 + dy) as u32).0[0] as i32 - center;
+                        match classify(diff, self.threshold) {
+                            BRIGHTER => brighter |= 1 << i,
+                            DARKER => darker |= 1 << i,
+                            _ => {}
+                        }
+                    }
+                    let corner = fast::segment_score(img, &ctx, x as i32, y as i32, self.threshold).is_some();
+                    Sample { brighter, darker, count: 1, corner }
+                })
+                .collect()
+        });
+
+        for sample in rows.into_iter().flatten() {
+            self.samples.entry((sample.brighter, sample.darker)).or_insert((0, sample.corner)).0 += 1;
+        }
+        Ok(self)
+    }
+
+    /// 已加入的不同状态组合的数量
+    pub fn n_patterns(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// 用ID3算法训练决策树
+    ///
+    /// 每个节点选择使子节点加权熵最小的像素，信息增益相同时选择能让更多样本直接到达叶节点的像素；
+    /// 没有样本的分支选择能直接确定结果的子节点最多的像素
+    pub fn train(&self) -> FastTree {
+        let ctx = self.fast_type.get_context();
+        let len = ctx.offsets.len();
+        let quarter = len / 4;
+        let order:Vec<usize> = [0, 2, 1, 3].iter()
+            .map(|&i| i * quarter)
+            .chain((0..len).filter(|i| i % quarter != 0))
+            .collect();
+
+        // 按状态组合排序，使训练结果与哈希表的遍历顺序无关
+        let mut samples:Vec<Sample> = self.samples.iter()
+            .map(|(&(brighter, darker), &(count, corner))| Sample { brighter, darker, count, corner })
+            .collect();
+        samples.sort_by_key(|s| (s.brighter, s.darker));
+
+        let builder = TreeBuilder { len, n: ctx.n as usize, order };
+        let mut nodes = vec![];
+        builder.build(&samples, PartialState { known: 0, brighter: 0, darker: 0 }, &mut nodes);
+
+        FastTree {
+            fast_type: self.fast_type,
+            nodes,
+            n_samples: samples.iter().map(|s| s.count).sum()
+        }
+    }
+}
+
+/// 构建决策树时使用的圆周信息
+struct TreeBuilder {
+    len: usize, // 圆周像素数
+    n: usize, // 连续弧段的最小长度
+    order: Vec<usize> // 没有样本时的比较顺序，也用于打破平局
+}
+
+impl TreeBuilder {
+    /// 已比较的像素是否已经能确定分段测试的结果
+    fn decide(&self, state: &PartialState) -> Option<bool> {
+        let unknown = !state.known & ((1u32 << self.len) - 1) as u16;
+        if fast::has_arc(state.brighter, self.len, self.n) || fast::has_arc(state.darker, self.len, self.n) {
+            Some(true)
+        } else if !fast::has_arc(state.brighter | unknown, self.len, self.n) && !fast::has_arc(state.darker | unknown, self.len, self.n) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// 递归构建以 `state` 为根的子树，返回根节点的序号
+    fn build(&self, samples: &[Sample], state: PartialState, nodes: &mut Vec<TreeNode>) -> usize {
+        let index = nodes.len();
+        if let Some(corner) = self.decide(&state) {
+            nodes.push(TreeNode::Leaf(corner));
+            return index;
+        }
+        nodes.push(TreeNode::Leaf(false));
+
+        let pixel = self.choose_pixel(samples, &state);
+        let mut children = [0; 3];
+        for (s, child) in children.iter_mut().enumerate() {
+            let subset:Vec<Sample> = samples.iter().filter(|sample| sample.state(pixel) == s).copied().collect();
+            *child = self.build(&subset, state.with(pixel, s), nodes);
+        }
+
+        nodes[index] = TreeNode::Test { pixel, children };
+        index
+    }
+
+    /// 选择信息增益最大的像素
+    fn choose_pixel(&self, samples: &[Sample], state: &PartialState) -> usize {
+        let candidates = self.order.iter().copied().filter(|&i| state.known & 1 << i == 0);
+        if samples.is_empty() {
+            // 没有样本时选择能直接确定结果的分支最多的像素
+            let decided = |i: usize| (0..3).filter(|&s| self.decide(&state.with(i, s)).is_some()).count();
+            return candidates.rev().max_by_key(|&i| decided(i)).expect("undecided state must have an unknown pixel");
+        }
+
+        // 熵乘以样本数，比较时不需要归一化
+        let weighted_entropy = |counts: &[u64; 2]| {
+            let total = (counts[0] + counts[1]) as f64;
+            counts.iter()
+                .filter(|&&c| c > 0)
+                .map(|&c| -(c as f64) * (c as f64 / total).ln())
+                .sum::<f64>()
+        };
+
+        let mut best = None;
+        for i in candidates {
+            let mut counts = [[0u64; 2]; 3];
+            for sample in samples {
+                counts[sample.state(i)][sample.corner as usize] += sample.count;
+            }
+
+            let entropy:f64 = counts.iter().map(weighted_entropy).sum();
+            let decided:u64 = (0..3)
+                .filter(|&s| self.decide(&state.with(i, s)).is_some())
+                .map(|s| counts[s][0] + counts[s][1])
+                .sum();
+
+            // 熵相同时比较直接到达叶节点的样本数，仍然相同时保留顺序靠前的像素
+            let better = match best {
+                None => true,
+                Some((_, best_entropy, best_decided)) => entropy < best_entropy - 1e-9 || (entropy <= best_entropy + 1e-9 && decided > best_decided)
+            };
+            if better {
+                best = Some((i, entropy, decided));
+            }
+        }
+        best.map(|(i, _, _)| i).expect("undecided state must have an unknown pixel")
+    }
+}
+
+/// 按阈值把像素差划分为三种状态
+fn classify(diff: i32, threshold: i32) -> usize {
+    if diff > threshold {
+        BRIGHTER
+    } else if diff < -threshold {
+        DARKER
+    } else {
+        SIMILAR
+    }
+}
+
+/// 训练FAST决策树
+///
+/// # 参数
+///
+/// * `images` - 训练图像，应与实际使用的图像相似
+/// * `fast_type` - 圆周类型，默认为 `TYPE_9_16`
+/// * `threshold` - 计算样本状态时使用的阈值，默认为20
+///
+/// # 返回值
+///
+/// 错误与 `FastTreeTrainer::new` 和 `FastTreeTrainer::add_image` 相同
+pub fn train(images: &[GrayImage], fast_type: Option<FastType>, threshold: Option<i32>) -> Result<FastTree, OrbError> {
+    let mut trainer = FastTreeTrainer::new(fast_type, threshold)?;
+    for img in images {
+        trainer.add_image(img)?;
+    }
+    Ok(trainer.train())
+}
+
+/// 使用给定的分类函数检测图像中的关键点
+///
+/// 分类函数可以是 `FastTree::is_corner`，也可以是 `FastTree::to_rust_source` 生成的函数。
+/// 得分、非极大值抑制和排序的含义与 `fast::fast` 一致
+///
+/// # 参数
+///
+/// * `img` - 输入图像
+/// * `fast_type` - 圆周类型，必须与分类函数训练时的类型相同，默认为 `TYPE_9_16`
+/// * `threshold` - FAST阈值，默认为50
+/// * `score_type` - 关键点评分方式，默认为Harris响应
+/// * `nonmax_suppression` - 是否执行3x3非极大值抑制，默认开启
+/// * `is_corner` - 根据圆周像素与中心像素的差和阈值判断是否为角点
+///
+/// # 返回值
+///
+/// 错误与 `fast::fast` 相同
+pub fn detect_with<C>(img: &GrayImage, fast_type: Option<FastType>, threshold: Option<i32>, score_type: Option<ScoreType>, nonmax_suppression: Option<bool>, is_corner: C) -> Result<Vec<FastKeypoint>, OrbError>
+where
+    C: Fn(&dyn Fn(usize) -> i32, i32) -> bool + Sync + Send
+{
+    let threshold = threshold.unwrap_or(DEFAULT_DETECT_THRESHOLD);
+    if threshold < 0 {
+        return Err(OrbError::invalid_parameter("threshold", format!("must not be negative, got {}", threshold)));
+    }
+
+    let ctx = fast_type.unwrap_or_default().get_context();
+    let mut keypoints = fast::detect_segment(img, ctx.radius, score_type.unwrap_or_default(), nonmax_suppression.unwrap_or(true), |y, found| {
+        for x in ctx.radius..img.width() - ctx.radius {
+            let center = img.get_pixel(x, y).0[0] as i32;
+            let pixel = |i: usize| {
+                let (dx, dy) = ctx.offsets[i];
+                img.get_pixel((x as i32 + dx) as u32, (y as i32 + dy) as u32).0[0] as i32 - center
+            };
+
+            if is_corner(&pixel, threshold) {
+                let score = (0..ctx.offsets.len()).map(|i| pixel(i).abs()).filter(|&d| d > threshold).sum();
+                found.push(((x as i32, y as i32), score));
+            }
+        }
+    })?;
+
+    fast::compute_default_moments(img, &mut keypoints);
+    Ok(keypoints)
+}
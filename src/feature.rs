@@ -0,0 +1,216 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+
+use image::DynamicImage;
+
+use crate::{common, error};
+use common::*;
+use error::OrbError;
+
+/// 表示检测到的关键点，字段与OpenCV的 `cv::KeyPoint` 一致
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyPoint {
+    pub pt: PointF, // 原图中的坐标，开启亚像素细化时带有小数部分
+    pub size: f32, // 有意义的邻域直径
+    pub angle: f32, // 方向，单位为度，范围为 [0, 360)，没有方向时为-1
+    pub response: f32, // 角点响应，用于排序和筛选
+    pub octave: usize, // 检测到该关键点的金字塔层
+    pub class_id: i32 // 关键点所属的对象类别，未分类时为-1
+}
+
+impl KeyPoint {
+    /// 创建没有方向、响应和类别的关键点
+    pub fn new(pt: PointF, size: f32) -> KeyPoint {
+        KeyPoint {
+            pt,
+            size,
+            angle: -1.0,
+            response: 0.0,
+            octave: 0,
+            class_id: -1
+        }
+    }
+}
+
+impl Default for KeyPoint {
+    fn default() -> Self {
+        KeyPoint::new((0.0, 0.0), 0.0)
+    }
+}
+
+/// 具有原图坐标的特征，几何估计通过该接口获取匹配点的位置
+pub trait Located {
+    /// 获取原图中的坐标
+    fn pt(&self) -> PointF;
+}
+
+impl Located for KeyPoint {
+    fn pt(&self) -> PointF {
+        self.pt
+    }
+}
+
+/// 一一对应的关键点和描述子
+///
+/// 描述子单独存放在连续的数组中，匹配时可以直接传给 [`DescriptorMatcher`]，
+/// 第 `i` 个描述子属于第 `i` 个关键点
+#[derive(Debug, Clone, PartialEq)]
+pub struct Features<D> {
+    keypoints: Vec<KeyPoint>, // 关键点
+    descriptors: Vec<D> // 与关键点一一对应的描述子
+}
+
+impl<D> Default for Features<D> {
+    fn default() -> Self {
+        Features { keypoints: vec![], descriptors: vec![] }
+    }
+}
+
+impl<D> Features<D> {
+    /// 组合关键点和描述子
+    ///
+    /// # 返回值
+    ///
+    /// 两者数量不同时返回 `OrbError::InvalidParameter`
+    pub fn new(keypoints: Vec<KeyPoint>, descriptors: Vec<D>) -> Result<Features<D>, OrbError> {
+        if keypoints.len() != descriptors.len() {
+            return Err(OrbError::invalid_parameter("descriptors", format!("expected one per keypoint ({}), got {}", keypoints.len(), descriptors.len())));
+        }
+        Ok(Features { keypoints, descriptors })
+    }
+
+    /// 关键点
+    pub fn keypoints(&self) -> &[KeyPoint] {
+        &self.keypoints
+    }
+
+    /// 连续存放的描述子
+    pub fn descriptors(&self) -> &[D] {
+        &self.descriptors
+    }
+
+    /// 特征数量
+    pub fn len(&self) -> usize {
+        self.keypoints.len()
+    }
+
+    /// 是否没有特征
+    pub fn is_empty(&self) -> bool {
+        self.keypoints.is_empty()
+    }
+
+    /// 获取第 `index` 个关键点和描述子
+    pub fn get(&self, index: usize) -> Option<(&KeyPoint, &D)> {
+        Some((self.keypoints.get(index)?, self.descriptors.get(index)?))
+    }
+
+    /// 按顺序遍历关键点和描述子
+    pub fn iter(&self) -> impl Iterator<Item = (&KeyPoint, &D)> {
+        self.keypoints.iter().zip(&self.descriptors)
+    }
+
+    /// 追加一个特征
+    pub fn push(&mut self, keypoint: KeyPoint, descriptor: D) {
+        self.keypoints.push(keypoint);
+        self.descriptors.push(descriptor);
+    }
+
+    /// 只保留满足条件的特征，保持原有顺序
+    pub fn retain<F: FnMut(&KeyPoint, &D) -> bool>(&mut self, mut f: F) {
+        let (keypoints, descriptors) = std::mem::take(self).into_parts();
+        for (keypoint, descriptor) in keypoints.into_iter().zip(descriptors) {
+            if f(&keypoint, &descriptor) {
+                self.push(keypoint, descriptor);
+            }
+        }
+    }
+
+    /// 拆分为关键点和描述子
+    pub fn into_parts(self) -> (Vec<KeyPoint>, Vec<D>) {
+        (self.keypoints, self.descriptors)
+    }
+}
+
+impl<D: Matchable> Features<D> {
+    /// 将本组特征作为查询特征，与 `train` 匹配，`matcher` 可以是 `&dyn DescriptorMatcher<Descriptor = D>`
+    pub fn match_with<M>(&self, train: &Features<D>, matcher: &M) -> Result<Vec<DMatch>, OrbError>
+    where
+        M: DescriptorMatcher<Descriptor = D> + ?Sized
+    {
+        matcher.match_descriptors(&self.descriptors, &train.descriptors)
+    }
+}
+
+/// 关键点检测器
+pub trait FeatureDetector {
+    /// 检测关键点
+    ///
+    /// # 参数
+    ///
+    /// * `img` - 输入图像
+    ///
+    /// # 返回值
+    ///
+    /// 返回原图坐标系下的关键点
+    fn detect(&self, img: &DynamicImage) -> Result<Vec<KeyPoint>, OrbError>;
+}
+
+/// 描述子提取器
+pub trait DescriptorExtractor {
+    /// 描述子类型，匹配时使用其距离
+    type Descriptor: Matchable;
+
+    /// 为给定的关键点计算描述子
+    ///
+    /// # 参数
+    ///
+    /// * `img` - 输入图像
+    /// * `keypoints` - 原图坐标系下的关键点
+    ///
+    /// # 返回值
+    ///
+    /// 返回一一对应的关键点和描述子，无法计算描述子的关键点会被移除
+    fn compute(&self, img: &DynamicImage, keypoints: &[KeyPoint]) -> Result<(Vec<KeyPoint>, Vec<Self::Descriptor>), OrbError>;
+}
+
+/// 描述子匹配器
+pub trait DescriptorMatcher {
+    /// 描述子类型，与 `DescriptorExtractor::Descriptor` 对应
+    type Descriptor: Matchable;
+
+    /// 为每个查询描述子寻找距离最小的训练描述子
+    fn match_descriptors(&self, query: &[Self::Descriptor], train: &[Self::Descriptor]) -> Result<Vec<DMatch>, OrbError>;
+
+    /// 为每个查询描述子寻找距离最小的 `k` 个训练描述子
+    fn knn_match(&self, query: &[Self::Descriptor], train: &[Self::Descriptor], k: usize) -> Result<Vec<Vec<DMatch>>, OrbError>;
+}
+
+/// 使用任意的检测器和描述子提取器计算特征
+///
+/// # 参数
+///
+/// * `detector` - 关键点检测器
+/// * `extractor` - 描述子提取器
+/// * `img` - 输入图像
+///
+/// # 返回值
+///
+/// 返回一一对应的关键点和描述子
+pub fn detect_and_compute<D, E>(detector: &D, extractor: &E, img: &DynamicImage) -> Result<(Vec<KeyPoint>, Vec<E::Descriptor>), OrbError>
+where
+    D: FeatureDetector,
+    E: DescriptorExtractor
+{
+    let keypoints = detector.detect(img)?;
+    extractor.compute(img, &keypoints)
+}
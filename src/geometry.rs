@@ -0,0 +1,418 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use rand::{SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+
+use crate::{feature, linalg, error};
+use feature::Located;
+use error::OrbError;
+use linalg::*;
+
+pub use linalg::Matrix3;
+
+pub type Point2 = (f64, f64); // 浮点坐标
+
+// 常量
+const DEFAULT_REPROJ_THRESHOLD:f64 = 3.0; // 默认的重投影误差阈值（像素）
+const DEFAULT_CONFIDENCE:f64 = 0.995; // 默认的置信度
+const DEFAULT_MAX_ITERS:usize = 2000; // 默认的最大迭代次数
+const HOMOGRAPHY_SAMPLE_SIZE:usize = 4; // 估计单应性所需的最少点对
+const COLLINEAR_EPSILON:f64 = 1e-6; // 判断三点共线的面积阈值
+pub(crate) const NO_VALID_MODEL:&str = "no random sample produced a valid model"; // RANSAC失败时的错误说明
+
+/// 表示RANSAC的参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RansacParams {
+    pub reproj_threshold: f64, // 判定为内点的最大误差（像素）
+    pub confidence: f64, // 至少采样到一次全内点样本的概率
+    pub max_iters: usize, // 最大迭代次数
+    pub seed: Option<u64> // 随机数种子，`None` 时使用系统熵
+}
+
+impl Default for RansacParams {
+    fn default() -> Self {
+        RansacParams {
+            reproj_threshold: DEFAULT_REPROJ_THRESHOLD,
+            confidence: DEFAULT_CONFIDENCE,
+            max_iters: DEFAULT_MAX_ITERS,
+            seed: None
+        }
+    }
+}
+
+/// 表示RANSAC估计的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct RansacEstimate {
+    pub model: Matrix3, // 估计的3x3矩阵
+    pub inliers: Vec<bool> // 每个点对是否为内点
+}
+
+impl RansacParams {
+    /// 检查参数是否在有效范围内
+    pub(crate) fn validate(&self) -> Result<(), OrbError> {
+        if !(self.reproj_threshold.is_finite() && self.reproj_threshold > 0.0) {
+            return Err(OrbError::invalid_parameter("reproj_threshold", format!("must be a positive number, got {}", self.reproj_threshold)));
+        }
+        if !(self.confidence > 0.0 && self.confidence < 1.0) {
+            return Err(OrbError::invalid_parameter("confidence", format!("must be in (0, 1), got {}", self.confidence)));
+        }
+        if self.max_iters == 0 {
+            return Err(OrbError::invalid_parameter("max_iters", "must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+impl RansacEstimate {
+    /// 获取内点数量
+    pub fn inlier_count(&self) -> usize {
+        self.inliers.iter().filter(|&&inlier| inlier).count()
+    }
+}
+
+/// 将匹配的关键点转换为两组对应的浮点坐标
+///
+/// # 参数
+///
+/// * `kp1` - 第一幅图像的关键点，可以是 `KeyPoint` 或 `Brief` 等带坐标的特征
+/// * `kp2` - 第二幅图像的关键点
+/// * `matches` - `orb::match_brief` 返回的索引对
+///
+/// # 返回值
+///
+/// 返回 `(第一幅图像的坐标, 第二幅图像的坐标)`，索引越界时返回 `OrbError::InvalidParameter`
+pub fn matched_points<T: Located>(kp1: &[T], kp2: &[T], matches: &[(usize, usize)]) -> Result<(Vec<Point2>, Vec<Point2>), OrbError> {
+    if let Some(&(i, j)) = matches.iter().find(|&&(i, j)| i >= kp1.len() || j >= kp2.len()) {
+        return Err(OrbError::invalid_parameter("matches", format!("pair ({}, {}) is out of range for {} and {} keypoints", i, j, kp1.len(), kp2.len())));
+    }
+
+    Ok(matches.iter()
+        .map(|&(i, j)| {
+            let ((x1, y1), (x2, y2)) = (kp1[i].pt(), kp2[j].pt());
+            ((x1 as f64, y1 as f64), (x2 as f64, y2 as f64))
+        })
+        .unzip())
+}
+
+/// 检查两组点一一对应且数量不少于 `required`
+pub(crate) fn check_correspondences(src: &[Point2], dst: &[Point2], required: usize) -> Result<(), OrbError> {
+    if src.len() != dst.len() {
+        return Err(OrbError::invalid_parameter("dst", format!("has {} points but `src` has {}", dst.len(), src.len())));
+    }
+    if src.len() < required {
+        return Err(OrbError::NotEnoughPoints { required, found: src.len() });
+    }
+    Ok(())
+}
+
+//
+// RANSAC
+//
+
+/// 通用的RANSAC循环
+///
+/// `fit` 根据样本索引返回零个或多个候选模型，`error` 返回模型在第 `i` 个点对上的误差。
+/// 迭代次数根据当前最佳内点率自适应减少。
+pub(crate) fn ransac<F, E>(n_points: usize, sample_size: usize, params: &RansacParams, mut fit: F, error: E) -> Option<RansacEstimate>
+where
+    F: FnMut(&[usize]) -> Vec<Matrix3>,
+    E: Fn(&Matrix3, usize) -> f64
+{
+    if n_points < sample_size {
+        return None;
+    }
+
+    let mut rng = match params.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy()
+    };
+
+    let mut best:Option<RansacEstimate> = None;
+    let mut best_count:usize = 0;
+    let mut max_iters = params.max_iters;
+    let mut iteration:usize = 0;
+
+    while iteration < max_iters {
+        iteration += 1;
+
+        let indices = sample(&mut rng, n_points, sample_size).into_vec();
+        for model in fit(&indices) {
+            let inliers:Vec<bool> = (0..n_points)
+                .map(|i| error(&model, i) <= params.reproj_threshold)
+                .collect();
+            let count = inliers.iter().filter(|&&inlier| inlier).count();
+
+            if count > best_count {
+                best_count = count;
+                best = Some(RansacEstimate { model, inliers });
+                max_iters = max_iters.min(adaptive_iterations(count, n_points, sample_size, params.confidence));
+            }
+        }
+    }
+
+    best
+}
+
+/// 根据内点率计算达到给定置信度所需的迭代次数
+fn adaptive_iterations(inliers: usize, n_points: usize, sample_size: usize, confidence: f64) -> usize {
+    let inlier_ratio = inliers as f64 / n_points as f64;
+    let all_inliers = inlier_ratio.powi(sample_size as i32);
+    if all_inliers >= 1.0 - f64::EPSILON {
+        return 0;
+    }
+    if all_inliers <= f64::EPSILON {
+        return usize::MAX;
+    }
+
+    let confidence = confidence.clamp(0.0, 1.0 - f64::EPSILON);
+    ((1.0 - confidence).ln() / (1.0 - all_inliers).ln()).ceil() as usize
+}
+
+//
+// Normalization
+//
+
+/// 计算将点集平移到原点、平均距离缩放到 `sqrt(2)` 的相似变换
+pub(crate) fn normalization_transform(points: &[Point2]) -> Matrix3 {
+    let n = points.len() as f64;
+    let (cx, cy) = points.iter().fold((0.0, 0.0), |(sx, sy), p| (sx + p.0, sy + p.1));
+    let (cx, cy) = (cx / n, cy / n);
+    let mean_dist = points.iter().map(|p| ((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt()).sum::<f64>() / n;
+    let scale = if mean_dist > f64::EPSILON { std::f64::consts::SQRT_2 / mean_dist } else { 1.0 };
+
+    [[scale, 0.0, -scale * cx], [0.0, scale, -scale * cy], [0.0, 0.0, 1.0]]
+}
+
+/// 使用3x3矩阵变换点，点位于无穷远时返回 `None`
+pub fn transform_point(m: &Matrix3, p: Point2) -> Option<Point2> {
+    let [x, y, w] = mul3_vec(m, [p.0, p.1, 1.0]);
+    if w.abs() < f64::EPSILON {
+        return None;
+    }
+    Some((x / w, y / w))
+}
+
+//
+// Homography
+//
+
+/// 使用归一化DLT从至少4个点对估计单应性矩阵
+///
+/// # 参数
+///
+/// * `src` - 源图像中的点
+/// * `dst` - 目标图像中对应的点
+///
+/// # 返回值
+///
+/// 返回满足 `dst ~ H * src` 且 `H[2][2] = 1` 的矩阵；点对不足时返回 `OrbError::NotEnoughPoints`，
+/// 点的分布退化时返回 `OrbError::DegenerateGeometry`
+pub fn homography_dlt(src: &[Point2], dst: &[Point2]) -> Result<Matrix3, OrbError> {
+    check_correspondences(src, dst, HOMOGRAPHY_SAMPLE_SIZE)?;
+    if is_degenerate_configuration(src) || is_degenerate_configuration(dst) {
+        return Err(OrbError::DegenerateGeometry("points are collinear"));
+    }
+
+    let degenerate = || OrbError::DegenerateGeometry("points cannot be normalised");
+    let t1 = normalization_transform(src);
+    let t2 = normalization_transform(dst);
+
+    let mut rows:Vec<Vec<f64>> = Vec::with_capacity(src.len() * 2);
+    for (&p, &q) in src.iter().zip(dst.iter()) {
+        let (x, y) = transform_point(&t1, p).ok_or_else(degenerate)?;
+        let (u, v) = transform_point(&t2, q).ok_or_else(degenerate)?;
+        rows.push(vec![-x, -y, -1.0, 0.0, 0.0, 0.0, u * x, u * y, u]);
+        rows.push(vec![0.0, 0.0, 0.0, -x, -y, -1.0, v * x, v * y, v]);
+    }
+
+    let h = to_matrix3(&null_vector(&rows));
+    let h = mul3(&inverse3(&t2).ok_or_else(degenerate)?, &mul3(&h, &t1));
+
+    if h[2][2].abs() < f64::EPSILON {
+        return Err(OrbError::DegenerateGeometry("homography maps the origin to infinity"));
+    }
+    Ok(h.map(|row| row.map(|x| x / h[2][2])))
+}
+
+/// 使用RANSAC从匹配点估计单应性矩阵
+///
+/// 每次迭代用4个随机点对求解DLT，共线的样本会被跳过；最后使用所有内点重新估计并更新内点掩码。
+///
+/// # 参数
+///
+/// * `src` - 源图像中的点
+/// * `dst` - 目标图像中对应的点
+/// * `params` - RANSAC参数，误差为 `dst` 与 `H * src` 之间的像素距离
+///
+/// # 返回值
+///
+/// 返回单应性矩阵和内点掩码；点对不足4个时返回 `OrbError::NotEnoughPoints`，
+/// 所有样本都退化时返回 `OrbError::DegenerateGeometry`
+pub fn find_homography(src: &[Point2], dst: &[Point2], params: &RansacParams) -> Result<RansacEstimate, OrbError> {
+    params.validate()?;
+    check_correspondences(src, dst, HOMOGRAPHY_SAMPLE_SIZE)?;
+
+    let error = |h: &Matrix3, i: usize| homography_error(h, src[i], dst[i]);
+    let estimate = ransac(src.len(), HOMOGRAPHY_SAMPLE_SIZE, params, |indices| {
+        let s:Vec<Point2> = indices.iter().map(|&i| src[i]).collect();
+        let d:Vec<Point2> = indices.iter().map(|&i| dst[i]).collect();
+        if has_collinear_triple(&s) || has_collinear_triple(&d) {
+            return vec![];
+        }
+        homography_dlt(&s, &d).into_iter().collect()
+    }, error).ok_or(OrbError::DegenerateGeometry(NO_VALID_MODEL))?;
+
+    Ok(refine(estimate, params, |mask| {
+        let (s, d):(Vec<Point2>, Vec<Point2>) = src.iter().zip(dst.iter())
+            .zip(mask.iter())
+            .filter(|(_, &inlier)| inlier)
+            .map(|((&p, &q), _)| (p, q))
+            .unzip();
+        homography_dlt(&s, &d).ok()
+    }, error))
+}
+
+/// 使用所有内点重新估计模型，内点数量不减少时采用新模型
+pub(crate) fn refine<F, E>(estimate: RansacEstimate, params: &RansacParams, fit: F, error: E) -> RansacEstimate
+where
+    F: Fn(&[bool]) -> Option<Matrix3>,
+    E: Fn(&Matrix3, usize) -> f64
+{
+    let model = match fit(&estimate.inliers) {
+        Some(model) => model,
+        None => return estimate
+    };
+
+    let inliers:Vec<bool> = (0..estimate.inliers.len())
+        .map(|i| error(&model, i) <= params.reproj_threshold)
+        .collect();
+    let refined = RansacEstimate { model, inliers };
+
+    if refined.inlier_count() >= estimate.inlier_count() { refined } else { estimate }
+}
+
+/// 计算单应性的前向重投影误差
+fn homography_error(h: &Matrix3, p: Point2, q: Point2) -> f64 {
+    match transform_point(h, p) {
+        Some((x, y)) => ((x - q.0).powi(2) + (y - q.1).powi(2)).sqrt(),
+        None => f64::INFINITY
+    }
+}
+
+/// 判断点集是否无法确定单应性：4个点中存在三点共线，或更多的点全部位于同一条直线上
+fn is_degenerate_configuration(points: &[Point2]) -> bool {
+    if points.len() <= HOMOGRAPHY_SAMPLE_SIZE {
+        return has_collinear_triple(points);
+    }
+    all_collinear(points)
+}
+
+/// 判断所有点是否位于同一条直线上，即协方差矩阵的较小特征值相对总方差接近0
+pub(crate) fn all_collinear(points: &[Point2]) -> bool {
+    let n = points.len() as f64;
+    let (cx, cy) = points.iter().fold((0.0, 0.0), |(sx, sy), p| (sx + p.0 / n, sy + p.1 / n));
+    let (sxx, syy, sxy) = points.iter().fold((0.0, 0.0, 0.0), |(a, b, c), p| {
+        let (dx, dy) = (p.0 - cx, p.1 - cy);
+        (a + dx * dx, b + dy * dy, c + dx * dy)
+    });
+    let half_trace = (sxx + syy) / 2.0;
+    let smallest = half_trace - (((sxx - syy) / 2.0).powi(2) + sxy * sxy).sqrt();
+    smallest <= COLLINEAR_EPSILON * half_trace
+}
+
+/// 判断点集中是否存在三点共线
+fn has_collinear_triple(points: &[Point2]) -> bool {
+    let n = points.len();
+    for i in 0..n {
+        for j in i + 1..n {
+            for k in j + 1..n {
+                let (a, b, c) = (points[i], points[j], points[k]);
+                let area = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+                if area.abs() < COLLINEAR_EPSILON {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    // 透视变换，H[2][2] = 1
+    const H:Matrix3 = [[1.2, 0.1, 30.0], [-0.05, 0.9, 12.0], [2e-4, -1e-4, 1.0]];
+
+    fn max_difference(a: &Matrix3, b: &Matrix3) -> f64 {
+        (0..9).map(|i| (a[i / 3][i % 3] - b[i / 3][i % 3]).abs()).fold(0.0, f64::max)
+    }
+
+    /// 生成100个点对，前70个为精确的内点，后30个偏离真值至少20像素
+    fn scene(rng: &mut StdRng) -> (Vec<Point2>, Vec<Point2>, Vec<bool>) {
+        let src:Vec<Point2> = (0..100).map(|_| (rng.gen_range(0.0..640.0), rng.gen_range(0.0..480.0))).collect();
+        let inliers:Vec<bool> = (0..100).map(|i| i < 70).collect();
+        let dst = src.iter().zip(&inliers)
+            .map(|(&p, &inlier)| {
+                let (x, y) = transform_point(&H, p).unwrap();
+                if inlier {
+                    return (x, y);
+                }
+                let (angle, dist):(f64, f64) = (rng.gen_range(0.0..std::f64::consts::TAU), rng.gen_range(20.0..200.0));
+                (x + dist * angle.cos(), y + dist * angle.sin())
+            })
+            .collect();
+        (src, dst, inliers)
+    }
+
+    #[test]
+    fn find_homography_recovers_model_and_inliers() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let (src, dst, inliers) = scene(&mut rng);
+        let params = RansacParams { seed: Some(2), ..RansacParams::default() };
+
+        let estimate = find_homography(&src, &dst, &params).unwrap();
+        assert!(max_difference(&estimate.model, &H) < 1e-8, "{:?}", estimate.model);
+        assert_eq!(estimate.inliers, inliers);
+        assert_eq!(estimate.inlier_count(), 70);
+    }
+
+    #[test]
+    fn homography_dlt_minimal_case() {
+        let src = [(0.0, 0.0), (100.0, 0.0), (100.0, 80.0), (10.0, 90.0)];
+        let dst:Vec<Point2> = src.iter().map(|&p| transform_point(&H, p).unwrap()).collect();
+
+        let h = homography_dlt(&src, &dst).unwrap();
+        assert!(max_difference(&h, &H) < 1e-8, "{:?}", h);
+        assert!(matches!(homography_dlt(&src[..3], &dst[..3]), Err(OrbError::NotEnoughPoints { required: 4, found: 3 })));
+        assert!(matches!(find_homography(&src[..3], &dst[..3], &RansacParams::default()), Err(OrbError::NotEnoughPoints { .. })));
+    }
+
+    #[test]
+    fn collinear_points_are_degenerate() {
+        let src:Vec<Point2> = (0..20).map(|i| (i as f64 * 10.0, i as f64 * 5.0 + 3.0)).collect();
+        let dst:Vec<Point2> = src.iter().map(|&p| transform_point(&H, p).unwrap()).collect();
+        let params = RansacParams { seed: Some(3), max_iters: 50, ..RansacParams::default() };
+
+        assert!(matches!(find_homography(&src, &dst, &params), Err(OrbError::DegenerateGeometry(_))));
+        assert!(matches!(homography_dlt(&src, &dst), Err(OrbError::DegenerateGeometry(_))));
+
+        // 4个点中有3个共线
+        let src = [(0.0, 0.0), (50.0, 50.0), (100.0, 100.0), (10.0, 90.0)];
+        let dst:Vec<Point2> = src.iter().map(|&p| transform_point(&H, p).unwrap()).collect();
+        assert!(matches!(homography_dlt(&src, &dst), Err(OrbError::DegenerateGeometry(_))));
+    }
+}
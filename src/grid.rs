@@ -0,0 +1,211 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use image::{imageops, GrayImage};
+
+use crate::{common, fast, error, parallel};
+use common::Point;
+use fast::FastKeypoint;
+use error::OrbError;
+
+// 常量
+const DEFAULT_CELL_SIZE:u32 = 32; // 默认的单元格边长
+const DEFAULT_MIN_THRESHOLD:i32 = 7; // 单元格中没有关键点时默认的重试阈值，与ORB-SLAM一致
+const CELL_MARGIN:u32 = 8; // 重新检测单元格时向外扩展的像素数，覆盖分段测试圆周、Harris窗口和矩的图像块
+
+/// 网格自适应检测的参数
+///
+/// 与ORB-SLAM和OpenCV的 `GridAdaptedFeatureDetector` 类似，把检测区域划分为边长约为 `cell_size` 的单元格，
+/// 没有关键点的单元格用 `min_threshold` 重新检测，再从每个单元格中轮流选取最强的关键点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridConfig {
+    pub cell_size: u32, // 单元格的目标边长，实际边长会略微放大以均分检测区域
+    pub min_threshold: i32 // 单元格中没有关键点时重新检测使用的阈值，不小于原阈值时不重试
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        GridConfig { cell_size: DEFAULT_CELL_SIZE, min_threshold: DEFAULT_MIN_THRESHOLD }
+    }
+}
+
+impl GridConfig {
+    /// 创建网格参数
+    ///
+    /// # 参数
+    ///
+    /// * `cell_size` - 单元格的目标边长，默认为32
+    /// * `min_threshold` - 单元格中没有关键点时重新检测使用的阈值，默认为7
+    pub fn new(cell_size: Option<u32>, min_threshold: Option<i32>) -> GridConfig {
+        GridConfig {
+            cell_size: cell_size.unwrap_or(DEFAULT_CELL_SIZE),
+            min_threshold: min_threshold.unwrap_or(DEFAULT_MIN_THRESHOLD)
+        }
+    }
+
+    /// 检查参数是否有效
+    ///
+    /// # 返回值
+    ///
+    /// 单元格边长为0或重试阈值为负数时返回 `OrbError::InvalidParameter`
+    pub fn validate(&self) -> Result<(), OrbError> {
+        if self.cell_size == 0 {
+            return Err(OrbError::invalid_parameter("cell_size", "must be at least 1"));
+        }
+        if self.min_threshold < 0 {
+            return Err(OrbError::invalid_parameter("min_threshold", format!("must not be negative, got {}", self.min_threshold)));
+        }
+        Ok(())
+    }
+}
+
+/// 检测区域的网格划分
+#[derive(Debug, Clone, Copy)]
+struct Grid {
+    origin: (u32, u32), // 检测区域左上角
+    size: (u32, u32), // 检测区域的宽和高
+    cells: (u32, u32), // 列数和行数
+    cell: (u32, u32) // 单元格的宽和高
+}
+
+impl Grid {
+    /// 把 `size` 大小的区域均分为边长约为 `cell_size` 的单元格
+    fn new(origin: (u32, u32), size: (u32, u32), cell_size: u32) -> Grid {
+        let cols = (size.0 / cell_size).max(1);
+        let rows = (size.1 / cell_size).max(1);
+        Grid { origin, size, cells: (cols, rows), cell: (size.0.div_ceil(cols), size.1.div_ceil(rows)) }
+    }
+
+    /// 单元格数量
+    fn len(&self) -> usize {
+        (self.cells.0 * self.cells.1) as usize
+    }
+
+    /// 第 `index` 个单元格的左上角和宽高，按行优先顺序编号
+    fn rect(&self, index: usize) -> (u32, u32, u32, u32) {
+        let (col, row) = (index as u32 % self.cells.0, index as u32 / self.cells.0);
+        let (x, y) = (col * self.cell.0, row * self.cell.1);
+        (self.origin.0 + x, self.origin.1 + y, self.cell.0.min(self.size.0 - x), self.cell.1.min(self.size.1 - y))
+    }
+
+    /// 第 `index` 个单元格中是否有允许检测的像素
+    fn any_allowed<A: Fn(Point) -> bool>(&self, index: usize, allowed: &A) -> bool {
+        let (x, y, width, height) = self.rect(index);
+        (y..y + height).any(|py| (x..x + width).any(|px| allowed((px as i32, py as i32))))
+    }
+
+    /// 点所在的单元格，不在检测区域内时返回 `None`
+    fn index(&self, (x, y): (i32, i32)) -> Option<usize> {
+        let (dx, dy) = (x - self.origin.0 as i32, y - self.origin.1 as i32);
+        if dx < 0 || dy < 0 || dx >= self.size.0 as i32 || dy >= self.size.1 as i32 {
+            return None;
+        }
+        Some(((dy as u32 / self.cell.1) * self.cells.0 + dx as u32 / self.cell.0) as usize)
+    }
+}
+
+/// 按网格均匀地检测关键点
+///
+/// 单元格数量超过 `n` 时会放大单元格，使每个单元格至少能分到一个关键点。
+/// 先用 `threshold` 检测整幅图像，没有关键点的单元格再向外扩展 `CELL_MARGIN` 个像素裁剪出来，
+/// 用 `grid.min_threshold` 重新检测。然后按单元格内的得分排名轮流选取：先取每个单元格最强的关键点，
+/// 再取每个单元格第二强的，依此类推，同一轮中得分高的优先，直到选满 `n` 个。
+/// 关键点少的单元格用不完的名额自然留给其他单元格
+///
+/// # 参数
+///
+/// * `img` - 输入图像
+/// * `border` - 距离图像边界小于该值的关键点会被丢弃，网格只覆盖边界以内的区域
+/// * `n` - 需要保留的最大关键点数
+/// * `grid` - 网格参数
+/// * `threshold` - 检测整幅图像时使用的阈值
+/// * `detect` - 用给定阈值检测图像中的关键点，如 `fast::fast` 或 `agast::agast`
+/// * `allowed` - 像素是否允许检测，不允许的关键点在选取前丢弃，没有允许像素的单元格不会重新检测
+///
+/// # 返回值
+///
+/// 返回最多 `n` 个关键点，按得分从高到低排列；网格参数无效时返回 `OrbError::InvalidParameter`，
+/// 其余错误与 `detect` 相同
+pub fn detect_in_grid<F, A>(img: &GrayImage, border: u32, n: usize, grid: &GridConfig, threshold: i32, detect: F, allowed: A) -> Result<Vec<FastKeypoint>, OrbError>
+where
+    F: Fn(&GrayImage, i32) -> Result<Vec<FastKeypoint>, OrbError> + Sync + Send,
+    A: Fn(Point) -> bool + Sync + Send
+{
+    grid.validate()?;
+    if img.width() <= 2 * border || img.height() <= 2 * border {
+        return Ok(vec![]);
+    }
+
+    // 单元格比关键点多时，第一轮只能选到得分最高的单元格，因此放大单元格使每个单元格都能分到关键点
+    let size = (img.width() - 2 * border, img.height() - 2 * border);
+    let min_cell_size = ((size.0 as f64 * size.1 as f64 / n.max(1) as f64).sqrt().ceil() as u32).min(size.0.max(size.1));
+    let cells = Grid::new((border, border), size, grid.cell_size.max(min_cell_size));
+    let mut buckets:Vec<Vec<FastKeypoint>> = vec![vec![]; cells.len()];
+    for keypoint in detect(img, threshold)? {
+        if let Some(index) = cells.index(keypoint.location).filter(|_| allowed(keypoint.location)) {
+            buckets[index].push(keypoint);
+        }
+    }
+
+    // 用更低的阈值重新检测空单元格
+    if grid.min_threshold < threshold {
+        let empty:Vec<usize> = (0..cells.len())
+            .filter(|&i| buckets[i].is_empty() && cells.any_allowed(i, &allowed))
+            .collect();
+        let retried = parallel::map(&empty, |&index| detect_in_cell(img, &cells, index, grid.min_threshold, &detect, &allowed))
+            .into_iter()
+            .collect::<Result<Vec<Vec<FastKeypoint>>, OrbError>>()?;
+        for (index, keypoints) in empty.into_iter().zip(retried) {
+            buckets[index] = keypoints;
+        }
+    }
+
+    // 单元格内按得分排名，稳定排序保证得分相同时保持检测顺序
+    let mut ranked:Vec<(usize, FastKeypoint)> = vec![];
+    for mut bucket in buckets {
+        bucket.sort_by(|a, b| b.score.total_cmp(&a.score));
+        ranked.extend(bucket.into_iter().enumerate());
+    }
+    ranked.sort_by(|(rank_a, a), (rank_b, b)| rank_a.cmp(rank_b).then(b.score.total_cmp(&a.score)));
+
+    let mut selected:Vec<FastKeypoint> = ranked.into_iter().take(n).map(|(_, k)| k).collect();
+    selected.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(selected)
+}
+
+/// 裁剪出第 `index` 个单元格及其外围 `CELL_MARGIN` 个像素重新检测，只保留单元格内允许检测的关键点
+fn detect_in_cell<F, A>(img: &GrayImage, cells: &Grid, index: usize, threshold: i32, detect: &F, allowed: &A) -> Result<Vec<FastKeypoint>, OrbError>
+where
+    F: Fn(&GrayImage, i32) -> Result<Vec<FastKeypoint>, OrbError>,
+    A: Fn(Point) -> bool
+{
+    let (x, y, width, height) = cells.rect(index);
+    let (x0, y0) = (x.saturating_sub(CELL_MARGIN), y.saturating_sub(CELL_MARGIN));
+    let x1 = (x + width + CELL_MARGIN).min(img.width());
+    let y1 = (y + height + CELL_MARGIN).min(img.height());
+    let crop = imageops::crop_imm(img, x0, y0, x1 - x0, y1 - y0).to_image();
+
+    let keypoints = match detect(&crop, threshold) {
+        Ok(keypoints) => keypoints,
+        // 靠近图像边界的小单元格容纳不下检测模板
+        Err(OrbError::ImageTooSmall { .. }) => return Ok(vec![]),
+        Err(e) => return Err(e)
+    };
+
+    Ok(keypoints.into_iter()
+        .filter_map(|mut k| {
+            k.location = (k.location.0 + x0 as i32, k.location.1 + y0 as i32);
+            k.position = (k.position.0 + x0 as f32, k.position.1 + y0 as f32);
+            (cells.index(k.location) == Some(index) && allowed(k.location)).then_some(k)
+        })
+        .collect())
+}
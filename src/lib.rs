@@ -4,5 +4,35 @@ pub mod brief;
 pub mod orb;
 // ORB算法的FAST计算模块
 pub mod fast;
+// AGAST角点检测模块
+pub mod agast;
+// 用ID3学习的FAST决策树
+pub mod fast_tree;
 // ORB算法的通用功能模块
-pub mod common;
\ No newline at end of file
+pub mod common;
+// 统一的错误类型
+pub mod error;
+// 检测器、描述子提取器和匹配器的通用接口
+pub mod feature;
+// ORB算法的二进制描述子模块
+pub mod descriptor;
+// ORB算法的特征匹配模块
+pub mod matcher;
+// 计算描述子前的图像平滑
+pub mod smoothing;
+// 网格自适应的关键点分布
+pub mod grid;
+// 检测掩码和感兴趣区域
+pub mod mask;
+// 预先旋转的BRIEF采样模式查找表
+pub mod steering;
+// ORB算法的图像金字塔模块
+pub mod pyramid;
+// 基于匹配点的几何估计模块
+pub mod geometry;
+// 对极几何估计模块
+pub mod epipolar;
+// 几何估计使用的线性代数工具
+mod linalg;
+// 可选的rayon并行迭代
+mod parallel;
\ No newline at end of file
@@ -0,0 +1,698 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+#![allow(clippy::needless_range_loop)] // 数值计算中按下标访问更清晰
+
+// 常量
+const JACOBI_MAX_SWEEPS:usize = 100; // Jacobi迭代的最大轮数
+
+pub type Matrix3 = [[f64; 3]; 3]; // 按行存储的3x3矩阵
+
+/// 计算对称矩阵的特征分解（循环Jacobi方法）
+///
+/// # 返回值
+///
+/// 返回按特征值从小到大排列的 `(特征值, 单位特征向量)`
+pub(crate) fn symmetric_eigen(matrix: &[Vec<f64>]) -> Vec<(f64, Vec<f64>)> {
+    let n = matrix.len();
+    let mut a:Vec<Vec<f64>> = matrix.to_vec();
+    let mut v:Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+
+    for _ in 0..JACOBI_MAX_SWEEPS {
+        let off:f64 = (0..n).flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        let scale:f64 = (0..n).map(|i| a[i][i] * a[i][i]).sum::<f64>().max(f64::MIN_POSITIVE);
+        if off <= scale * 1e-30 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() < f64::MIN_POSITIVE {
+                    continue;
+                }
+
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let (akp, akq) = (a[k][p], a[k][q]);
+                    a[k][p] = c * akp - s * akq;
+                    a[k][q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[p][k], a[q][k]);
+                    a[p][k] = c * apk - s * aqk;
+                    a[q][k] = s * apk + c * aqk;
+                }
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut pairs:Vec<(f64, Vec<f64>)> = (0..n)
+        .map(|j| (a[j][j], v.iter().map(|row| row[j]).collect()))
+        .collect();
+    pairs.sort_by(|x, y| x.0.total_cmp(&y.0));
+    pairs
+}
+
+/// 求 `A^T A` 最小特征值对应的单位特征向量，即最小二乘意义下 `A x = 0` 的解
+///
+/// # 参数
+///
+/// * `rows` - 矩阵 `A` 的各行
+pub(crate) fn null_vector(rows: &[Vec<f64>]) -> Vec<f64> {
+    null_space(rows, 1).remove(0)
+}
+
+/// 求 `A^T A` 最小的 `k` 个特征值对应的单位特征向量
+pub(crate) fn null_space(rows: &[Vec<f64>], k: usize) -> Vec<Vec<f64>> {
+    let n = rows.first().map_or(0, |r| r.len());
+    let mut ata = vec![vec![0.0; n]; n];
+    for row in rows {
+        for i in 0..n {
+            for j in i..n {
+                ata[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    for i in 0..n {
+        for j in 0..i {
+            ata[i][j] = ata[j][i];
+        }
+    }
+
+    symmetric_eigen(&ata).into_iter().take(k).map(|(_, v)| v).collect()
+}
+
+/// 将长度为9的向量按行排列为3x3矩阵
+pub(crate) fn to_matrix3(v: &[f64]) -> Matrix3 {
+    [[v[0], v[1], v[2]], [v[3], v[4], v[5]], [v[6], v[7], v[8]]]
+}
+
+pub(crate) fn mul3(a: &Matrix3, b: &Matrix3) -> Matrix3 {
+    let mut c = [[0.0; 3]; 3];
+    for (i, row) in c.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    c
+}
+
+pub(crate) fn transpose3(a: &Matrix3) -> Matrix3 {
+    let mut t = [[0.0; 3]; 3];
+    for (i, row) in a.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate() {
+            t[j][i] = value;
+        }
+    }
+    t
+}
+
+pub(crate) fn det3(a: &Matrix3) -> f64 {
+    a[0][0] * (a[1][1] * a[2][2] - a[1][2] * a[2][1])
+        - a[0][1] * (a[1][0] * a[2][2] - a[1][2] * a[2][0])
+        + a[0][2] * (a[1][0] * a[2][1] - a[1][1] * a[2][0])
+}
+
+/// 求3x3矩阵的逆，矩阵奇异时返回 `None`
+pub(crate) fn inverse3(a: &Matrix3) -> Option<Matrix3> {
+    let det = det3(a);
+    if det.abs() < f64::EPSILON {
+        return None;
+    }
+
+    let mut inv = [[0.0; 3]; 3];
+    for (i, row) in inv.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            // 伴随矩阵的第 (i, j) 项是 a 的第 (j, i) 项的代数余子式
+            let (r0, r1) = ((j + 1) % 3, (j + 2) % 3);
+            let (c0, c1) = ((i + 1) % 3, (i + 2) % 3);
+            *value = (a[r0][c0] * a[r1][c1] - a[r0][c1] * a[r1][c0]) / det;
+        }
+    }
+    Some(inv)
+}
+
+pub(crate) fn mul3_vec(a: &Matrix3, v: [f64; 3]) -> [f64; 3] {
+    [
+        a[0][0] * v[0] + a[0][1] * v[1] + a[0][2] * v[2],
+        a[1][0] * v[0] + a[1][1] * v[1] + a[1][2] * v[2],
+        a[2][0] * v[0] + a[2][1] * v[1] + a[2][2] * v[2]
+    ]
+}
+
+/// 按Frobenius范数归一化3x3矩阵
+pub(crate) fn normalize3(a: &Matrix3) -> Matrix3 {
+    let norm = a.iter().flatten().map(|x| x * x).sum::<f64>().sqrt();
+    if norm < f64::MIN_POSITIVE {
+        return *a;
+    }
+    a.map(|row| row.map(|x| x / norm))
+}
+
+pub(crate) fn cross3(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn normalize_vec3(v: [f64; 3]) -> [f64; 3] {
+    let norm = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if norm < f64::MIN_POSITIVE { v } else { v.map(|x| x / norm) }
+}
+
+/// 3x3矩阵的奇异值分解 `A = U * diag(s) * V^T`，奇异值从大到小排列
+///
+/// 通过 `A^T A` 的特征分解求得 `V`，较小奇异值对应的 `U` 的列由叉积补全为正交基
+pub(crate) fn svd3(a: &Matrix3) -> (Matrix3, [f64; 3], Matrix3) {
+    let ata = mul3(&transpose3(a), a);
+    let eigen = symmetric_eigen(&ata.iter().map(|row| row.to_vec()).collect::<Vec<Vec<f64>>>());
+
+    // 特征值从小到大排列，奇异值需要从大到小
+    let columns:Vec<[f64; 3]> = eigen.iter().rev().map(|(_, v)| [v[0], v[1], v[2]]).collect();
+    let mut v_cols = [columns[0], columns[1], cross3(columns[0], columns[1])];
+    let mut s = [0.0; 3];
+    let mut u_cols = [[0.0; 3]; 3];
+
+    let largest = eigen.last().map_or(0.0, |(value, _)| value.max(0.0).sqrt());
+    let tolerance = largest * 1e-12;
+    let mut rank = 0;
+    for k in 0..3 {
+        let av = mul3_vec(a, v_cols[k]);
+        let sigma = (av[0] * av[0] + av[1] * av[1] + av[2] * av[2]).sqrt();
+        s[k] = sigma;
+        if sigma > tolerance && rank == k {
+            u_cols[k] = av.map(|x| x / sigma);
+            rank += 1;
+        }
+    }
+
+    // 补全 `U` 的正交基
+    if rank == 0 {
+        u_cols[0] = [1.0, 0.0, 0.0];
+    }
+    if rank <= 1 {
+        let helper = if u_cols[0][0].abs() < 0.9 { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] };
+        u_cols[1] = normalize_vec3(cross3(u_cols[0], helper));
+    }
+    if rank <= 2 {
+        u_cols[2] = cross3(u_cols[0], u_cols[1]);
+    }
+
+    let from_cols = |cols: &[[f64; 3]; 3]| -> Matrix3 {
+        [[cols[0][0], cols[1][0], cols[2][0]], [cols[0][1], cols[1][1], cols[2][1]], [cols[0][2], cols[1][2], cols[2][2]]]
+    };
+    (from_cols(&u_cols), s, from_cols(&v_cols))
+}
+
+/// 求实系数一元三次方程 `a x^3 + b x^2 + c x + d = 0` 的实根
+pub(crate) fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    let scale = a.abs().max(b.abs()).max(c.abs()).max(d.abs());
+    if scale < f64::MIN_POSITIVE {
+        return vec![];
+    }
+
+    if a.abs() < scale * 1e-12 {
+        // 退化为二次方程
+        if b.abs() < scale * 1e-12 {
+            return if c.abs() < scale * 1e-12 { vec![] } else { vec![-d / c] };
+        }
+        let disc = c * c - 4.0 * b * d;
+        if disc < 0.0 {
+            return vec![];
+        }
+        let q = -0.5 * (c + c.signum() * disc.sqrt());
+        let mut roots = vec![q / b];
+        if q.abs() > f64::MIN_POSITIVE {
+            roots.push(d / q);
+        }
+        return roots;
+    }
+
+    // 化为 x^3 + p x^2 + q x + r = 0 并使用三角/卡尔达诺公式
+    let (p, q, r) = (b / a, c / a, d / a);
+    let q3 = (p * p - 3.0 * q) / 9.0;
+    let r3 = (2.0 * p * p * p - 9.0 * p * q + 27.0 * r) / 54.0;
+    let shift = p / 3.0;
+
+    if r3 * r3 < q3 * q3 * q3 {
+        let theta = (r3 / (q3 * q3 * q3).sqrt()).clamp(-1.0, 1.0).acos();
+        let m = -2.0 * q3.sqrt();
+        let two_pi = 2.0 * std::f64::consts::PI;
+        vec![
+            m * (theta / 3.0).cos() - shift,
+            m * ((theta + two_pi) / 3.0).cos() - shift,
+            m * ((theta - two_pi) / 3.0).cos() - shift
+        ]
+    } else {
+        let big_a = -r3.signum() * (r3.abs() + (r3 * r3 - q3 * q3 * q3).sqrt()).cbrt();
+        let big_b = if big_a.abs() > f64::MIN_POSITIVE { q3 / big_a } else { 0.0 };
+        vec![big_a + big_b - shift]
+    }
+}
+
+/// 使用部分主元高斯消元求解线性方程组 `A x = b`，矩阵奇异时返回 `None`
+pub(crate) fn solve_linear(matrix: &[Vec<f64>], rhs: &[f64]) -> Option<Vec<f64>> {
+    let n = matrix.len();
+    let mut a:Vec<Vec<f64>> = matrix.iter().zip(rhs.iter())
+        .map(|(row, &b)| {
+            let mut row = row.clone();
+            row.push(b);
+            row
+        })
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < f64::MIN_POSITIVE {
+            return None;
+        }
+        a.swap(col, pivot);
+
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+            if factor == 0.0 {
+                continue;
+            }
+            for k in col..=n {
+                a[row][k] -= factor * a[col][k];
+            }
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum:f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (a[row][n] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+/// 对 `rows x cols` 矩阵的前 `rows` 列执行高斯-若尔当消元，使其化为单位矩阵
+///
+/// # 返回值
+///
+/// 左侧矩阵奇异时返回 `None`
+pub(crate) fn gauss_jordan(matrix: &mut [Vec<f64>]) -> Option<()> {
+    let rows = matrix.len();
+    let cols = matrix.first().map_or(0, |r| r.len());
+
+    for col in 0..rows {
+        let pivot = (col..rows).max_by(|&i, &j| matrix[i][col].abs().total_cmp(&matrix[j][col].abs()))?;
+        if matrix[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(col, pivot);
+
+        let inv = 1.0 / matrix[col][col];
+        for k in 0..cols {
+            matrix[col][k] *= inv;
+        }
+        for row in 0..rows {
+            if row == col {
+                continue;
+            }
+            let factor = matrix[row][col];
+            if factor == 0.0 {
+                continue;
+            }
+            for k in 0..cols {
+                matrix[row][k] -= factor * matrix[col][k];
+            }
+        }
+    }
+
+    Some(())
+}
+
+/// 将一般实矩阵约化为上Hessenberg形式（带主元的消元法）
+fn hessenberg(a: &mut [Vec<f64>]) {
+    let n = a.len();
+    for m in 1..n.saturating_sub(1) {
+        let mut x:f64 = 0.0;
+        let mut pivot = m;
+        for j in m..n {
+            if a[j][m - 1].abs() > x.abs() {
+                x = a[j][m - 1];
+                pivot = j;
+            }
+        }
+
+        if pivot != m {
+            for j in m - 1..n {
+                let tmp = a[pivot][j];
+                a[pivot][j] = a[m][j];
+                a[m][j] = tmp;
+            }
+            for row in a.iter_mut() {
+                row.swap(pivot, m);
+            }
+        }
+
+        if x != 0.0 {
+            for i in m + 1..n {
+                let mut y = a[i][m - 1];
+                if y != 0.0 {
+                    y /= x;
+                    a[i][m - 1] = y;
+                    for j in m..n {
+                        a[i][j] -= y * a[m][j];
+                    }
+                    for row in a.iter_mut() {
+                        row[m] += y * row[i];
+                    }
+                }
+            }
+        }
+    }
+
+    // 清除消元乘子，只保留Hessenberg部分
+    for i in 2..n {
+        for j in 0..i - 1 {
+            a[i][j] = 0.0;
+        }
+    }
+}
+
+/// 使用带双重位移的QR算法求上Hessenberg矩阵的全部特征值
+///
+/// # 返回值
+///
+/// 返回 `(实部, 虚部)`，迭代不收敛时返回 `None`
+fn hessenberg_eigenvalues(a: &mut [Vec<f64>]) -> Option<Vec<(f64, f64)>> {
+    let n = a.len() as isize;
+    let mut values = vec![(0.0, 0.0); n as usize];
+    let idx = |i: isize| i as usize;
+
+    let mut anorm = 0.0;
+    for i in 0..n {
+        for j in (i - 1).max(0)..n {
+            anorm += a[idx(i)][idx(j)].abs();
+        }
+    }
+
+    let mut nn = n - 1;
+    let mut t = 0.0;
+    while nn >= 0 {
+        let mut its = 0;
+        loop {
+            // 寻找可以分解的小次对角元
+            let mut l = nn;
+            while l > 0 {
+                let mut s = a[idx(l - 1)][idx(l - 1)].abs() + a[idx(l)][idx(l)].abs();
+                if s == 0.0 {
+                    s = anorm;
+                }
+                if a[idx(l)][idx(l - 1)].abs() <= f64::EPSILON * s {
+                    a[idx(l)][idx(l - 1)] = 0.0;
+                    break;
+                }
+                l -= 1;
+            }
+
+            let mut x = a[idx(nn)][idx(nn)];
+            if l == nn {
+                // 一个实根
+                values[idx(nn)] = (x + t, 0.0);
+                nn -= 1;
+                break;
+            }
+
+            let mut y = a[idx(nn - 1)][idx(nn - 1)];
+            let mut w = a[idx(nn)][idx(nn - 1)] * a[idx(nn - 1)][idx(nn)];
+            if l == nn - 1 {
+                // 两个根
+                let p = 0.5 * (y - x);
+                let q = p * p + w;
+                let z = q.abs().sqrt();
+                x += t;
+                if q >= 0.0 {
+                    let z = p + z.copysign(p);
+                    values[idx(nn - 1)] = (x + z, 0.0);
+                    values[idx(nn)] = (if z != 0.0 { x - w / z } else { x + z }, 0.0);
+                } else {
+                    values[idx(nn)] = (x + p, -z);
+                    values[idx(nn - 1)] = (x + p, z);
+                }
+                nn -= 2;
+                break;
+            }
+
+            if its == 60 {
+                return None;
+            }
+            if its == 10 || its == 20 {
+                // 特殊位移
+                t += x;
+                for i in 0..=nn {
+                    a[idx(i)][idx(i)] -= x;
+                }
+                let s = a[idx(nn)][idx(nn - 1)].abs() + a[idx(nn - 1)][idx(nn - 2)].abs();
+                x = 0.75 * s;
+                y = x;
+                w = -0.4375 * s * s;
+            }
+            its += 1;
+
+            // 寻找两个连续的小次对角元
+            let mut m = nn - 2;
+            let (mut p, mut q, mut r);
+            loop {
+                let z = a[idx(m)][idx(m)];
+                let rr = x - z;
+                let ss = y - z;
+                p = (rr * ss - w) / a[idx(m + 1)][idx(m)] + a[idx(m)][idx(m + 1)];
+                q = a[idx(m + 1)][idx(m + 1)] - z - rr - ss;
+                r = a[idx(m + 2)][idx(m + 1)];
+                let s = p.abs() + q.abs() + r.abs();
+                p /= s;
+                q /= s;
+                r /= s;
+                if m == l {
+                    break;
+                }
+                let u = a[idx(m)][idx(m - 1)].abs() * (q.abs() + r.abs());
+                let v = p.abs() * (a[idx(m - 1)][idx(m - 1)].abs() + z.abs() + a[idx(m + 1)][idx(m + 1)].abs());
+                if u <= f64::EPSILON * v {
+                    break;
+                }
+                m -= 1;
+            }
+
+            for i in m..nn - 1 {
+                a[idx(i + 2)][idx(i)] = 0.0;
+                if i != m {
+                    a[idx(i + 2)][idx(i - 1)] = 0.0;
+                }
+            }
+
+            // 双重QR步
+            for k in m..nn {
+                if k != m {
+                    p = a[idx(k)][idx(k - 1)];
+                    q = a[idx(k + 1)][idx(k - 1)];
+                    r = if k + 1 != nn { a[idx(k + 2)][idx(k - 1)] } else { 0.0 };
+                    x = p.abs() + q.abs() + r.abs();
+                    if x != 0.0 {
+                        p /= x;
+                        q /= x;
+                        r /= x;
+                    }
+                }
+
+                let s = (p * p + q * q + r * r).sqrt().copysign(p);
+                if s == 0.0 {
+                    continue;
+                }
+
+                if k == m {
+                    if l != m {
+                        a[idx(k)][idx(k - 1)] = -a[idx(k)][idx(k - 1)];
+                    }
+                } else {
+                    a[idx(k)][idx(k - 1)] = -s * x;
+                }
+                p += s;
+                x = p / s;
+                y = q / s;
+                let z = r / s;
+                q /= p;
+                r /= p;
+
+                for j in k..=nn {
+                    let mut pp = a[idx(k)][idx(j)] + q * a[idx(k + 1)][idx(j)];
+                    if k + 1 != nn {
+                        pp += r * a[idx(k + 2)][idx(j)];
+                        a[idx(k + 2)][idx(j)] -= pp * z;
+                    }
+                    a[idx(k + 1)][idx(j)] -= pp * y;
+                    a[idx(k)][idx(j)] -= pp * x;
+                }
+
+                let mmin = if nn < k + 3 { nn } else { k + 3 };
+                for i in l..=mmin {
+                    let mut pp = x * a[idx(i)][idx(k)] + y * a[idx(i)][idx(k + 1)];
+                    if k + 1 != nn {
+                        pp += z * a[idx(i)][idx(k + 2)];
+                        a[idx(i)][idx(k + 2)] -= pp * r;
+                    }
+                    a[idx(i)][idx(k + 1)] -= pp * q;
+                    a[idx(i)][idx(k)] -= pp;
+                }
+            }
+        }
+    }
+
+    Some(values)
+}
+
+/// 求一般实矩阵的实特征值及对应的单位特征向量
+///
+/// 特征值由Hessenberg约化和QR迭代求得，特征向量由逆迭代求得
+pub(crate) fn real_eigenvectors(matrix: &[Vec<f64>]) -> Vec<(f64, Vec<f64>)> {
+    let n = matrix.len();
+    let mut h = matrix.to_vec();
+    hessenberg(&mut h);
+
+    let values = match hessenberg_eigenvalues(&mut h) {
+        Some(values) => values,
+        None => return vec![]
+    };
+
+    let scale = matrix.iter().flatten().map(|x| x.abs()).fold(0.0, f64::max).max(1.0);
+    values.into_iter()
+        .filter(|&(re, im)| im.abs() <= 1e-8 * scale.max(re.abs()))
+        .filter_map(|(lambda, _)| {
+            // 对 `A - (lambda + eps) I` 做逆迭代
+            let shift = lambda + 1e-10 * scale;
+            let shifted:Vec<Vec<f64>> = matrix.iter().enumerate()
+                .map(|(i, row)| row.iter().enumerate().map(|(j, &x)| if i == j { x - shift } else { x }).collect())
+                .collect();
+
+            let mut v = vec![1.0 / (n as f64).sqrt(); n];
+            for _ in 0..3 {
+                let next = solve_linear(&shifted, &v)?;
+                let norm = next.iter().map(|x| x * x).sum::<f64>().sqrt();
+                if !norm.is_finite() || norm < f64::MIN_POSITIVE {
+                    return None;
+                }
+                v = next.into_iter().map(|x| x / norm).collect();
+            }
+            Some((lambda, v))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    fn max_difference(a: &Matrix3, b: &Matrix3) -> f64 {
+        (0..9).map(|i| (a[i / 3][i % 3] - b[i / 3][i % 3]).abs()).fold(0.0, f64::max)
+    }
+
+    const IDENTITY:Matrix3 = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    #[test]
+    fn svd3_reconstructs_full_and_rank_deficient_matrices() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut matrices:Vec<Matrix3> = (0..50).map(|_| [[0.0; 3]; 3].map(|row| row.map(|_: f64| rng.gen_range(-5.0..5.0)))).collect();
+        matrices.push([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [-1.0, 0.5, 2.0]]);
+        matrices.push([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [3.0, 6.0, 9.0]]);
+        matrices.push([[0.0; 3]; 3]);
+
+        for a in &matrices {
+            let (u, s, v) = svd3(a);
+            let d = [[s[0], 0.0, 0.0], [0.0, s[1], 0.0], [0.0, 0.0, s[2]]];
+            assert!(max_difference(&mul3(&mul3(&u, &d), &transpose3(&v)), a) < 1e-9, "{:?}", a);
+            assert!(max_difference(&mul3(&transpose3(&u), &u), &IDENTITY) < 1e-9);
+            assert!(max_difference(&mul3(&transpose3(&v), &v), &IDENTITY) < 1e-9);
+            assert!(s[0] >= s[1] && s[1] >= s[2] && s[2] >= 0.0);
+        }
+    }
+
+    #[test]
+    fn solve_cubic_finds_real_roots() {
+        let sorted = |mut roots: Vec<f64>| {
+            roots.sort_by(f64::total_cmp);
+            roots
+        };
+        let close = |a: &[f64], b: &[f64]| a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-9);
+
+        // (x - 1)(x + 2)(x - 3)
+        assert!(close(&sorted(solve_cubic(2.0, -4.0, -10.0, 12.0)), &[-2.0, 1.0, 3.0]));
+        // (x - 2)(x^2 + 1) 只有一个实根
+        assert!(close(&solve_cubic(1.0, -2.0, 1.0, -2.0), &[2.0]));
+        // 退化为二次和一次方程
+        assert!(close(&sorted(solve_cubic(0.0, 1.0, -5.0, 6.0)), &[2.0, 3.0]));
+        assert!(close(&solve_cubic(0.0, 0.0, 2.0, -1.0), &[0.5]));
+        assert!(solve_cubic(0.0, 0.0, 0.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn symmetric_eigen_sorts_eigenpairs() {
+        let a = vec![vec![2.0, 1.0, 0.0], vec![1.0, 2.0, 0.0], vec![0.0, 0.0, 5.0]];
+        let pairs = symmetric_eigen(&a);
+        let values:Vec<f64> = pairs.iter().map(|(value, _)| *value).collect();
+        assert!(values.iter().zip([1.0, 3.0, 5.0]).all(|(x, y)| (x - y).abs() < 1e-12));
+
+        for (value, v) in &pairs {
+            for i in 0..3 {
+                let av:f64 = (0..3).map(|j| a[i][j] * v[j]).sum();
+                assert!((av - value * v[i]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn real_eigenvectors_skips_complex_pairs() {
+        // 分块对角矩阵：二维旋转块的特征值为复数，另外两个实特征值为2和-3
+        let a = vec![
+            vec![0.0, -1.0, 0.0, 0.0],
+            vec![1.0, 0.0, 0.0, 0.0],
+            vec![0.0, 0.0, 2.0, 1.0],
+            vec![0.0, 0.0, 0.0, -3.0]
+        ];
+        let mut pairs = real_eigenvectors(&a);
+        pairs.sort_by(|x, y| x.0.total_cmp(&y.0));
+        assert_eq!(pairs.len(), 2);
+
+        for ((value, v), expected) in pairs.iter().zip([-3.0, 2.0]) {
+            assert!((value - expected).abs() < 1e-9);
+            for i in 0..4 {
+                let av:f64 = (0..4).map(|j| a[i][j] * v[j]).sum();
+                assert!((av - value * v[i]).abs() < 1e-7);
+            }
+        }
+    }
+
+    #[test]
+    fn null_space_and_inverse() {
+        // x + y + z = 0 和 x - y = 0 的零空间为 (1, 1, -2)
+        let v = null_vector(&[vec![1.0, 1.0, 1.0], vec![1.0, -1.0, 0.0]]);
+        let scale = v[0];
+        assert!((v[1] - scale).abs() < 1e-12 && (v[2] + 2.0 * scale).abs() < 1e-12);
+
+        let a = [[4.0, 7.0, 2.0], [3.0, 6.0, 1.0], [2.0, 5.0, 3.0]];
+        assert!(max_difference(&mul3(&a, &inverse3(&a).unwrap()), &IDENTITY) < 1e-12);
+        assert!(inverse3(&[[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 1.0, 1.0]]).is_none());
+    }
+}
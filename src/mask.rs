@@ -0,0 +1,108 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use image::GrayImage;
+
+use crate::{common, error};
+use common::{Point, PointF};
+use error::OrbError;
+
+/// 表示原图中的矩形感兴趣区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Roi {
+    pub x: u32, // 左上角的x坐标
+    pub y: u32, // 左上角的y坐标
+    pub width: u32, // 宽度
+    pub height: u32 // 高度
+}
+
+impl Roi {
+    /// 创建矩形区域
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Roi {
+        Roi { x, y, width, height }
+    }
+
+    /// 像素是否位于区域内
+    pub fn contains(&self, (x, y): Point) -> bool {
+        x >= self.x as i32 && y >= self.y as i32
+            && (x as i64) < self.x as i64 + self.width as i64
+            && (y as i64) < self.y as i64 + self.height as i64
+    }
+}
+
+/// 限制关键点检测范围的掩码，与OpenCV检测器的 `mask` 参数含义相同
+///
+/// 掩码图像和矩形区域都以原图坐标表示，可以只给出其中一个；同时给出时取交集，
+/// 即关键点所在的像素必须在掩码图像中非零，并且位于至少一个矩形区域内。
+/// 在金字塔的各层中，关键点换算回原图坐标后再检查，因此所有层使用同一个掩码
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DetectionMask {
+    image: Option<GrayImage>, // 与原图同样大小的掩码图像，非零像素允许检测
+    rois: Vec<Roi> // 允许检测的矩形区域，为空时不限制
+}
+
+impl DetectionMask {
+    /// 创建检测掩码
+    ///
+    /// # 参数
+    ///
+    /// * `image` - 与原图同样大小的掩码图像，非零像素允许检测，`None` 时不限制
+    /// * `rois` - 允许检测的矩形区域，为空时不限制
+    pub fn new(image: Option<GrayImage>, rois: Vec<Roi>) -> DetectionMask {
+        DetectionMask { image, rois }
+    }
+
+    /// 只使用掩码图像
+    pub fn from_image(image: GrayImage) -> DetectionMask {
+        DetectionMask::new(Some(image), vec![])
+    }
+
+    /// 只使用矩形区域
+    pub fn from_rois(rois: Vec<Roi>) -> DetectionMask {
+        DetectionMask::new(None, rois)
+    }
+
+    /// 掩码图像
+    pub fn image(&self) -> Option<&GrayImage> {
+        self.image.as_ref()
+    }
+
+    /// 矩形区域
+    pub fn rois(&self) -> &[Roi] {
+        &self.rois
+    }
+
+    /// 检查掩码图像与 `width` x `height` 的原图大小一致
+    ///
+    /// # 返回值
+    ///
+    /// 大小不一致时返回 `OrbError::InvalidParameter`
+    pub fn validate(&self, width: u32, height: u32) -> Result<(), OrbError> {
+        match &self.image {
+            Some(image) if image.dimensions() != (width, height) => Err(OrbError::invalid_parameter("mask",
+                format!("must have the same size as the image ({}x{}), got {}x{}", width, height, image.width(), image.height()))),
+            _ => Ok(())
+        }
+    }
+
+    /// 原图坐标 `pt` 最近的像素是否允许检测，掩码图像之外的像素不允许检测
+    pub fn allows(&self, pt: PointF) -> bool {
+        let pixel = (pt.0.round() as i32, pt.1.round() as i32);
+
+        let in_image = self.image.as_ref().is_none_or(|image| {
+            pixel.0 >= 0 && pixel.1 >= 0
+                && (pixel.0 as u32) < image.width() && (pixel.1 as u32) < image.height()
+                && image.get_pixel(pixel.0 as u32, pixel.1 as u32).0[0] != 0
+        });
+        in_image && (self.rois.is_empty() || self.rois.iter().any(|roi| roi.contains(pixel)))
+    }
+}
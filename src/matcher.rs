@@ -0,0 +1,228 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use std::marker::PhantomData;
+
+use crate::{common, feature, error, parallel};
+use common::*;
+use error::OrbError;
+use feature::DescriptorMatcher;
+
+/// 暴力匹配器，对每个查询特征遍历所有训练特征
+///
+/// 类型参数 `D` 为描述子类型，通常由匹配时传入的描述子推断
+#[derive(Debug)]
+pub struct BFMatcher<D> {
+    pub max_distance: Option<usize>, // 允许的最大距离，超过的匹配会被丢弃
+    pub cross_check: bool, // 是否只保留互为最近邻的匹配
+    descriptor: PhantomData<fn(&D)> // 描述子类型，不拥有描述子
+}
+
+// 手动实现以免要求描述子类型本身满足这些特性
+impl<D> Clone for BFMatcher<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D> Copy for BFMatcher<D> {}
+
+impl<D> PartialEq for BFMatcher<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.max_distance == other.max_distance && self.cross_check == other.cross_check
+    }
+}
+
+impl<D> Eq for BFMatcher<D> {}
+
+impl<D> Default for BFMatcher<D> {
+    fn default() -> Self {
+        BFMatcher::new(None, false)
+    }
+}
+
+impl<D> BFMatcher<D> {
+    /// 创建暴力匹配器
+    ///
+    /// # 参数
+    ///
+    /// * `max_distance` - 允许的最大距离，`None` 表示不限制
+    /// * `cross_check` - 是否只保留互为最近邻的匹配
+    pub fn new(max_distance: Option<usize>, cross_check: bool) -> BFMatcher<D> {
+        BFMatcher { max_distance, cross_check, descriptor: PhantomData }
+    }
+}
+
+impl<D: Matchable + MaybeSync> BFMatcher<D> {
+
+    /// 为每个查询特征寻找距离最小的训练特征
+    ///
+    /// # 返回值
+    ///
+    /// 返回通过最大距离和交叉检验的匹配，按查询索引排列
+    pub fn match_descriptors(&self, query: &[D], train: &[D]) -> Result<Vec<DMatch>, OrbError> {
+        Ok(self.knn_match(query, train, 1)?
+            .into_iter()
+            .filter_map(|matches| matches.into_iter().next())
+            .collect())
+    }
+
+    /// 为每个查询特征寻找距离最小的 `k` 个训练特征
+    ///
+    /// # 参数
+    ///
+    /// * `query` - 查询特征
+    /// * `train` - 训练特征
+    /// * `k` - 每个查询特征返回的最大匹配数
+    ///
+    /// # 返回值
+    ///
+    /// 返回与 `query` 等长的向量，每个元素按距离从小到大排列，距离相同时训练索引小的在前。
+    /// 开启交叉检验时，只保留查询特征恰好是训练特征最近邻的匹配；`k` 为0时返回 `OrbError::InvalidParameter`
+    pub fn knn_match(&self, query: &[D], train: &[D], k: usize) -> Result<Vec<Vec<DMatch>>, OrbError> {
+        if k == 0 {
+            return Err(OrbError::invalid_parameter("k", "must be at least 1"));
+        }
+
+        let reverse_best = if self.cross_check {
+            Some(nearest_indices(train, query))
+        } else {
+            None
+        };
+
+        Ok(parallel::map_range(query.len(), |query_idx| {
+            let q = &query[query_idx];
+            let mut best:Vec<DMatch> = Vec::with_capacity(k + 1);
+
+            for (train_idx, t) in train.iter().enumerate() {
+                let distance = q.distance(t);
+                if self.max_distance.is_some_and(|max| distance > max) {
+                    continue;
+                }
+                if best.len() == k && best.last().is_some_and(|m| m.distance <= distance) {
+                    continue;
+                }
+
+                // 插入到第一个距离更大的位置之前，保持稳定顺序
+                let position = best.partition_point(|m| m.distance <= distance);
+                best.insert(position, DMatch { query_idx, train_idx, distance });
+                best.truncate(k);
+            }
+
+            if let Some(reverse_best) = &reverse_best {
+                best.retain(|m| reverse_best[m.train_idx] == Some(query_idx));
+            }
+
+            best
+        }))
+    }
+}
+
+impl<D: Matchable + MaybeSync> DescriptorMatcher for BFMatcher<D> {
+    type Descriptor = D;
+
+    fn match_descriptors(&self, query: &[D], train: &[D]) -> Result<Vec<DMatch>, OrbError> {
+        BFMatcher::match_descriptors(self, query, train)
+    }
+
+    fn knn_match(&self, query: &[D], train: &[D], k: usize) -> Result<Vec<Vec<DMatch>>, OrbError> {
+        BFMatcher::knn_match(self, query, train, k)
+    }
+}
+
+/// 为 `from` 中的每个特征寻找 `to` 中距离最小的特征的索引，距离相同时取索引小的
+fn nearest_indices<T>(from: &[T], to: &[T]) -> Vec<Option<usize>>
+where
+    T: Matchable + MaybeSync
+{
+    parallel::map(from, |f| {
+        to.iter()
+            .enumerate()
+            .map(|(i, t)| (f.distance(t), i))
+            .min()
+            .map(|(_, i)| i)
+    })
+}
+
+/// Lowe比率测试
+///
+/// # 参数
+///
+/// * `knn_matches` - `knn_match` 返回的匹配，`k` 至少为2
+/// * `ratio` - 最近距离与次近距离之比的上限，常用0.7到0.8
+///
+/// # 返回值
+///
+/// 返回最近邻明显优于次近邻的匹配；只有一个候选的查询特征会被保留。
+/// `ratio` 不在 `(0, 1]` 内时返回 `OrbError::InvalidParameter`
+pub fn ratio_test(knn_matches: &[Vec<DMatch>], ratio: f32) -> Result<Vec<DMatch>, OrbError> {
+    if !(ratio > 0.0 && ratio <= 1.0) {
+        return Err(OrbError::invalid_parameter("ratio", format!("must be in (0, 1], got {}", ratio)));
+    }
+
+    Ok(knn_matches.iter()
+        .filter_map(|matches| match matches.as_slice() {
+            [best, second, ..] if (best.distance as f32) < ratio * second.distance as f32 => Some(*best),
+            [best] => Some(*best),
+            _ => None
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{descriptor::Descriptor256, feature::{Features, KeyPoint}};
+
+    fn descriptor(word: u64) -> Descriptor256 {
+        Descriptor256::from_words([word, 0, 0, 0])
+    }
+
+    #[test]
+    fn matchers_can_be_boxed() {
+        let query = [descriptor(0b0000), descriptor(0b1111), descriptor(0b1100_0000)];
+        let train = [descriptor(0b0001), descriptor(0b0111)];
+
+        let matchers:Vec<Box<dyn DescriptorMatcher<Descriptor = Descriptor256>>> = vec![
+            Box::new(BFMatcher::new(None, false)),
+            Box::new(BFMatcher::new(None, true))
+        ];
+        let matched:Vec<Vec<(usize, usize)>> = matchers.iter()
+            .map(|matcher| matcher.match_descriptors(&query, &train).unwrap().iter().map(|m| (m.query_idx, m.train_idx)).collect())
+            .collect();
+
+        // 第三个查询的最近邻是第0个训练描述子，但后者的最近邻是第0个查询，交叉检验时被去掉
+        assert_eq!(matched[0], vec![(0, 0), (1, 1), (2, 0)]);
+        assert_eq!(matched[1], vec![(0, 0), (1, 1)]);
+
+        let features = |descriptors: &[Descriptor256]| Features::new(vec![KeyPoint::default(); descriptors.len()], descriptors.to_vec()).unwrap();
+        let matches = features(&query).match_with(&features(&train), matchers[1].as_ref()).unwrap();
+        assert_eq!(matches.len(), 2);
+    }
+
+    #[test]
+    fn knn_match_and_ratio_test() {
+        let query = [descriptor(0b0000), descriptor(0b1111_0000)];
+        let train = [descriptor(0b0111), descriptor(0b0001), descriptor(0b1111_0001), descriptor(0b0011)];
+        let matcher = BFMatcher::new(Some(3), false);
+
+        let knn = matcher.knn_match(&query, &train, 2).unwrap();
+        let distances:Vec<Vec<(usize, usize)>> = knn.iter().map(|ms| ms.iter().map(|m| (m.train_idx, m.distance)).collect()).collect();
+        assert_eq!(distances, vec![vec![(1, 1), (3, 2)], vec![(2, 1)]]);
+
+        // 1 < 0.5 * 2 不成立，第一个查询被比率测试去掉；只有一个候选的查询保留
+        let kept = ratio_test(&knn, 0.5).unwrap();
+        assert_eq!(kept.iter().map(|m| m.query_idx).collect::<Vec<_>>(), vec![1]);
+        assert!(matcher.knn_match(&query, &train, 0).is_err());
+        assert!(ratio_test(&knn, 1.5).is_err());
+    }
+}
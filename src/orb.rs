@@ -11,18 +11,33 @@
 #![allow(non_snake_case)] // 允许非蛇形命名
 
 use std::cmp::{min, max};
-use image::{ImageError, GenericImageView, DynamicImage, ImageBuffer, GrayImage, ImageFormat, RgbImage};
-use image::imageops::{blur};
+use image::{GenericImageView, DynamicImage, ImageBuffer, GrayImage, ImageFormat, RgbImage};
 use cgmath::{prelude::{*},Rad, Deg};
-use bitvector::BitVector;
-
-use crate::{fast, brief, common};
-use fast::{FastKeypoint};
+use rand::{SeedableRng, rngs::StdRng, seq::index};
+
+use crate::{fast, agast, brief, common, pyramid, descriptor, matcher, feature, error, parallel, steering, smoothing, grid, mask};
+use fast::{FastKeypoint, FastType, ScoreType, SubpixelMethod};
+use agast::SegmentMask;
+use feature::{KeyPoint, Located, Features, FeatureDetector, DescriptorExtractor};
+use brief::BriefPattern;
+use error::OrbError;
+use descriptor::Descriptor256;
+use steering::{Steering, SteeredPattern, BorderMode};
+use smoothing::{Smoothing, SmoothedImage};
+use pyramid::{PyramidConfig, PyramidLevel, ScalePyramid};
+use matcher::BFMatcher;
+use grid::GridConfig;
+use mask::DetectionMask;
+
+// 常量
+const MAX_MATCH_DISTANCE:usize = 49; // `match_brief` 接受的最大汉明距离
+const DEFAULT_N_FEATURES:usize = 500; // 默认的特征总数
+const DEFAULT_EDGE_THRESHOLD:u32 = 31; // 默认的边界阈值
+const DEFAULT_FAST_THRESHOLD:i32 = 20; // 默认的FAST阈值
+const WTA_SEED:u64 = 0x12345; // 生成 `WTA_K` 元组的随机种子
+const FAST_BORDER:u32 = 4; // FAST和AGAST圆周加上一个像素的最大边界，小于该值的边界阈值不起作用
 use common::{*};
 
-// Consts
-const DEFAULT_BRIEF_LENGTH:usize = 256;
-
 //
 // Sobel Calculations
 //
@@ -47,7 +62,7 @@ unsafe fn sobel(img: &image::GrayImage, filter: &SobelFilter, x: i32, y: i32) ->
             sobel += px as i32 * *k;
         }
     }
-    min(sobel.abs() as u8, u8::MAX)
+    min(sobel.unsigned_abs(), u8::MAX as u32) as u8
 }
 
 /// 创建Sobel梯度图像
@@ -57,7 +72,7 @@ fn create_sobel_image(img: &GrayImage) -> GrayImage {
     for y in 1..img.height()-1 {
         for x in 1..img.width()-1 {
             let mut px = new_image.get_pixel_mut(x, y);
-            px.0[0] = unsafe { sobel(img, &SOBEL_Y, x as i32, y as i32) as u8 };
+            px.0[0] = unsafe { sobel(img, &SOBEL_Y, x as i32, y as i32) };
         }
     }
 
@@ -68,131 +83,761 @@ fn create_sobel_image(img: &GrayImage) -> GrayImage {
 // BRIEF Calculations
 //
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Brief {
-    pub x: i32,
-    pub y: i32,
-    pub b: BitVector
+    pub x: f32, // 第0层中的x坐标
+    pub y: f32, // 第0层中的y坐标
+    pub octave: usize, // 检测到该关键点的金字塔层
+    pub scale: f32, // 该层相对于原图的缩放比例
+    pub size: f32, // 采样图像块在原图中的直径
+    pub wta_k: u8, // 每次比较的点数，为3或4时每次比较占2位
+    pub padded: bool, // 采样范围是否超出图像，为true时描述子使用了填充的像素
+    pub b: Descriptor256 // 打包的描述子位
 }
 
 impl Matchable for Brief {
     fn distance(&self, other: &Self) -> usize {
-        (0..min(self.b.capacity(), other.b.capacity()))
-        .fold(0, |acc, x| {
-            acc + (self.b.contains(x) != other.b.contains(x)) as usize
-        })
+        if self.wta_k > 2 {
+            self.b.hamming2_distance(&other.b) as usize
+        } else {
+            self.b.hamming_distance(&other.b) as usize
+        }
+    }
+}
+
+impl Located for Brief {
+    fn pt(&self) -> PointF {
+        (self.x, self.y)
     }
 }
 
-/// 将角度四舍五入到最近的增量
-fn round_angle(angle: i32, increment: i32) -> i32 {
-    let modulo:i32 = angle % increment;
-    let complement:i32 = if angle < 0 
-        { increment + modulo } else { increment - modulo} ;
+/// 只含描述子位的ORB描述子，在 [`Features`] 中连续存放，关键点的几何信息由 `KeyPoint` 保存
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OrbDescriptor {
+    pub b: Descriptor256, // 打包的描述子位
+    pub wta_k: u8, // 每次比较的点数，为3或4时使用 `hamming2_distance` 匹配
+    pub padded: bool // 采样范围是否超出图像
+}
 
-    if modulo.abs() > (increment << 1) {
-        return if angle < 0 { angle - complement } else { angle + complement };
+impl Matchable for OrbDescriptor {
+    fn distance(&self, other: &Self) -> usize {
+        if self.wta_k > 2 {
+            self.b.hamming2_distance(&other.b) as usize
+        } else {
+            self.b.hamming_distance(&other.b) as usize
+        }
     }
+}
 
-    angle - modulo
+impl From<&Brief> for OrbDescriptor {
+    fn from(brief: &Brief) -> Self {
+        OrbDescriptor { b: brief.b, wta_k: brief.wta_k, padded: brief.padded }
+    }
 }
 
-/// 计算BRIEF描述子
-pub fn brief(blurred_img: &GrayImage, vec: &Vec<FastKeypoint>, brief_length: Option<usize>) -> Vec<Brief> {
-    let brief_length = brief_length.unwrap_or(DEFAULT_BRIEF_LENGTH);
-    let width:i32 = blurred_img.width() as i32;
-    let height:i32 = blurred_img.height() as i32;
-
-    // copy offsets into current frame on stack
-    let offsets = brief::OFFSETS.clone();
-
-    vec.into_iter()
-        .map(|k| {
-            let rotation = Deg::from(Rad(k.moment.rotation)).0.round() as i32;
-            let rounded_angle = Deg(round_angle(rotation, 12) as f32);
-
-            let cos_a = Deg::cos(rounded_angle);
-            let sin_a = Deg::sin(rounded_angle);
-            let (x, y) = k.location;
-
-            let mut bit_vec = BitVector::new(brief_length);
-
-            for (i, ((x0, y0), (x1, y1))) in offsets.iter().enumerate() {
-                let mut steered_p1 = (
-                    x + (x0 * cos_a - y0 * sin_a).round() as i32,
-                    y + (x0 * sin_a + y0 * cos_a).round() as i32
-                );
-
-                let mut steered_p2 = (
-                    x + (x1 * cos_a - y1 * sin_a).round() as i32,
-                    y + (x1 * sin_a + y1 * cos_a).round() as i32
-                );
-
-                steered_p1.0 = max(min(steered_p1.0, width - 1), 0);
-                steered_p2.0 = max(min(steered_p2.0, width - 1), 0);
-                steered_p1.1 = max(min(steered_p1.1, height - 1), 0);
-                steered_p2.1 = max(min(steered_p2.1, height - 1), 0);
-
-                let brief_feature = blurred_img.get_pixel(steered_p1.0 as u32, steered_p1.1 as u32).0[0] >
-                                    blurred_img.get_pixel(steered_p2.0 as u32, steered_p2.1 as u32).0[0];
-
-                if brief_feature {
-                    bit_vec.insert(i);
-                }
+/// ORB的输出：关键点及其连续存放的描述子
+pub type OrbFeatures = Features<OrbDescriptor>;
+
+/// 使用旋转后的采样偏移计算单个关键点的描述子
+///
+/// `wta_k` 为2时每两个相邻的采样点组成一个点对，占1位；
+/// 否则每 `wta_k` 个采样点组成一个元组，最亮的点的下标占2位
+fn describe(smoothed: &SmoothedImage, location: Point, offsets: &[(i32, i32)], radius: i32, wta_k: usize, border: BorderMode) -> Descriptor256 {
+    let sample = steering::sampler(smoothed, location, offsets, radius, border);
+    let mut bits = Descriptor256::zeros();
+
+    if wta_k == 2 {
+        for i in 0..offsets.len() / 2 {
+            bits.set(i, sample(2 * i) > sample(2 * i + 1));
+        }
+        return bits;
+    }
+
+    for i in 0..offsets.len() / wta_k {
+        let mut best = 0;
+        let mut best_value = sample(i * wta_k);
+        for j in 1..wta_k {
+            let value = sample(i * wta_k + j);
+            if value > best_value {
+                best = j;
+                best_value = value;
             }
+        }
 
-            Brief {
-                x: k.location.0,
-                y: k.location.1,
-                b: bit_vec
-            } 
-        })
-        .collect::<Vec<Brief>>()
+        bits.set(2 * i, best & 1 == 1);
+        bits.set(2 * i + 1, best & 2 == 2);
+    }
+
+    bits
+}
+
+/// 从采样模式的所有点中为 `WTA_K` 选取不含重复点的元组，依次展开为采样点
+fn wta_tuples(pattern: &BriefPattern, wta_k: usize) -> Vec<(f32, f32)> {
+    let points:Vec<(f32, f32)> = pattern.pairs().iter().flat_map(|&(p0, p1)| [p0, p1]).collect();
+    let n_tuples = pattern.len().min(Descriptor256::BITS / 2);
+    let mut rng = StdRng::seed_from_u64(WTA_SEED);
+
+    (0..n_tuples)
+        .flat_map(|_| index::sample(&mut rng, points.len(), wta_k).into_iter())
+        .map(|i| points[i])
+        .collect()
+}
+
+/// 计算BRIEF描述子
+///
+/// `pattern` 为 `None` 时使用ORB论文中学习得到的rBRIEF模式，描述子位数等于模式的点对数量。
+/// 采样点按12度量化的方向旋转。`border` 默认为 `BorderMode::Filter`，此时采样范围超出图像的关键点不出现在结果中。
+/// 图像为空时返回 `OrbError::ImageTooSmall`
+pub fn brief(blurred_img: &GrayImage, vec: &[FastKeypoint], pattern: Option<&BriefPattern>, border: Option<BorderMode>) -> Result<Vec<Brief>, OrbError> {
+    error::check_image_size(blurred_img.width(), blurred_img.height(), 1)?;
+
+    let default_pattern;
+    let pattern = match pattern {
+        Some(pattern) => pattern,
+        None => {
+            default_pattern = BriefPattern::default();
+            &default_pattern
+        }
+    };
+
+    let border = border.unwrap_or_default();
+    let smoothed = SmoothedImage::new(blurred_img, Smoothing::None)?;
+    let steered = SteeredPattern::from_pattern(pattern);
+    let (width, height) = blurred_img.dimensions();
+    let kept:Vec<&FastKeypoint> = vec.iter()
+        .filter(|k| border != BorderMode::Filter || steered.fits(k.location, width, height))
+        .collect();
+
+    Ok(parallel::map(&kept, |k| Brief {
+        x: k.position.0,
+        y: k.position.1,
+        octave: 0,
+        scale: 1.0,
+        size: pattern.patch_size() as f32,
+        wta_k: 2,
+        padded: !steered.fits(k.location, width, height),
+        b: describe(&smoothed, k.location, steered.offsets(steering::angle_bin(k.moment.angle)), steered.radius(), 2, border)
+    }))
 }
 
 //
 // ORB Calculations
 //
 
-/// 计算ORB特征点和描述子
-pub fn orb(img: &DynamicImage, n:usize) -> Result<Vec<Brief>, ImageError> {
-    let gray_img = img.to_luma8();
-    
-    let mut keypoints:Vec<FastKeypoint> = fast::fast(&gray_img, None, None)?;
+/// ORB在各层上检测角点使用的分段测试检测器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detector {
+    Fast(FastType), // 给定圆周类型的FAST，与OpenCV的ORB一致
+    Agast(SegmentMask) // 给定模板的AGAST
+}
 
-    let keypoints = adaptive_nonmax_suppression(&mut keypoints, n);
+impl Default for Detector {
+    fn default() -> Self {
+        Detector::Fast(FastType::TYPE_9_16)
+    }
+}
 
-    let blurred_img = blur(&gray_img, 3.0);
-    let brief_descriptors = brief(&blurred_img, &keypoints, None);
+/// ORB检测器和描述子的参数，默认值与OpenCV的 `ORB::create` 一致
+///
+/// ```ignore
+/// let orb = OrbConfig::default().n_features(1000).fast_threshold(10).build()?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbConfig {
+    pub n_features: usize, // 所有层的特征总数
+    pub scale_factor: f32, // 相邻层之间的缩放因子，必须大于1
+    pub n_levels: usize, // 金字塔层数
+    pub edge_threshold: u32, // 距离图像边界小于该值的关键点会被丢弃
+    pub first_level: usize, // 原图所在的层，之前的层为放大的图像
+    pub wta_k: u8, // 每次比较的点数，为2、3或4
+    pub score_type: ScoreType, // 关键点评分方式
+    pub patch_size: u32, // 采样图像块边长，同时决定计算方向时的半径
+    pub detector: Detector, // 角点检测器
+    pub fast_threshold: i32, // FAST阈值，使用AGAST时为AGAST阈值
+    pub smoothing: Smoothing, // 计算描述子前在各层上的平滑方式
+    pub steering: Steering, // 采样点按关键点方向旋转的方式
+    pub border_mode: BorderMode, // 采样范围超出所在层的关键点的处理方式
+    pub subpixel: Option<SubpixelMethod>, // 关键点的亚像素细化方法，`None` 时不细化
+    pub grid: Option<GridConfig> // 按网格均匀选取关键点的参数，`None` 时使用自适应非极大值抑制
+}
 
-    Ok(brief_descriptors)
+impl Default for OrbConfig {
+    fn default() -> Self {
+        OrbConfig {
+            n_features: DEFAULT_N_FEATURES,
+            scale_factor: pyramid::DEFAULT_SCALE_FACTOR,
+            n_levels: pyramid::DEFAULT_N_LEVELS,
+            edge_threshold: DEFAULT_EDGE_THRESHOLD,
+            first_level: 0,
+            wta_k: 2,
+            score_type: ScoreType::Harris,
+            patch_size: brief::PATCH_SIZE,
+            detector: Detector::default(),
+            fast_threshold: DEFAULT_FAST_THRESHOLD,
+            smoothing: Smoothing::default(),
+            steering: Steering::Discrete,
+            border_mode: BorderMode::Filter,
+            subpixel: None,
+            grid: None
+        }
+    }
 }
 
-/// 匹配两幅图像的关键点
-pub fn match_brief(img1_keypoints: &Vec<Brief>, img2_keypoints: &Vec<Brief>) -> Vec<(usize, usize)> {
-    let mut pair_indices = Vec::new();
-
-    // 遍历第一幅图像的所有关键点
-    for (i, kp1) in img1_keypoints.iter().enumerate() {
-        let mut best_match_index = 0;
-        let mut best_distance = usize::MAX;
-
-        // 遍历第二幅图像的所有关键点，找到与当前关键点最匹配的点
-        for (j, kp2) in img2_keypoints.iter().enumerate() {
-            let distance = kp1.distance(kp2);
-
-            // 如果找到更小的距离，则更新最佳匹配
-            if distance < best_distance {
-                best_distance = distance;
-                best_match_index = j;
+impl OrbConfig {
+    /// 设置所有层的特征总数
+    pub fn n_features(mut self, n_features: usize) -> Self {
+        self.n_features = n_features;
+        self
+    }
+
+    /// 设置相邻层之间的缩放因子
+    pub fn scale_factor(mut self, scale_factor: f32) -> Self {
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    /// 设置金字塔层数
+    pub fn n_levels(mut self, n_levels: usize) -> Self {
+        self.n_levels = n_levels;
+        self
+    }
+
+    /// 设置边界阈值
+    pub fn edge_threshold(mut self, edge_threshold: u32) -> Self {
+        self.edge_threshold = edge_threshold;
+        self
+    }
+
+    /// 设置原图所在的层
+    pub fn first_level(mut self, first_level: usize) -> Self {
+        self.first_level = first_level;
+        self
+    }
+
+    /// 设置每次比较的点数
+    pub fn wta_k(mut self, wta_k: u8) -> Self {
+        self.wta_k = wta_k;
+        self
+    }
+
+    /// 设置关键点评分方式
+    pub fn score_type(mut self, score_type: ScoreType) -> Self {
+        self.score_type = score_type;
+        self
+    }
+
+    /// 设置采样图像块边长
+    pub fn patch_size(mut self, patch_size: u32) -> Self {
+        self.patch_size = patch_size;
+        self
+    }
+
+    /// 设置角点检测器
+    pub fn detector(mut self, detector: Detector) -> Self {
+        self.detector = detector;
+        self
+    }
+
+    /// 使用给定圆周类型的FAST检测角点，等价于 `detector(Detector::Fast(fast_type))`
+    pub fn fast_type(self, fast_type: FastType) -> Self {
+        self.detector(Detector::Fast(fast_type))
+    }
+
+    /// 设置FAST阈值
+    pub fn fast_threshold(mut self, fast_threshold: i32) -> Self {
+        self.fast_threshold = fast_threshold;
+        self
+    }
+
+    /// 设置计算描述子前的平滑方式
+    pub fn smoothing(mut self, smoothing: Smoothing) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+
+    /// 设置采样点的旋转方式
+    pub fn steering(mut self, steering: Steering) -> Self {
+        self.steering = steering;
+        self
+    }
+
+    /// 设置采样范围超出所在层的关键点的处理方式
+    pub fn border_mode(mut self, border_mode: BorderMode) -> Self {
+        self.border_mode = border_mode;
+        self
+    }
+
+    /// 设置关键点的亚像素细化方法
+    pub fn subpixel(mut self, subpixel: Option<SubpixelMethod>) -> Self {
+        self.subpixel = subpixel;
+        self
+    }
+
+    /// 设置按网格均匀选取关键点的参数，`None` 时使用自适应非极大值抑制
+    pub fn grid(mut self, grid: Option<GridConfig>) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    /// 获取对应的金字塔配置
+    pub fn pyramid(&self) -> PyramidConfig {
+        PyramidConfig {
+            n_levels: self.n_levels,
+            scale_factor: self.scale_factor,
+            first_level: self.first_level
+        }
+    }
+
+    /// 校验配置并创建ORB检测器
+    pub fn build(self) -> Result<Orb, OrbError> {
+        Orb::new(self)
+    }
+}
+
+/// 可重复使用的ORB检测器和描述子提取器
+#[derive(Debug, Clone)]
+pub struct Orb {
+    config: OrbConfig, // 参数
+    steered: SteeredPattern // 按图像块边长缩放后的采样点，`WTA_K` 大于2时为依次展开的元组
+}
+
+impl Orb {
+    /// 校验配置并预先计算采样模式
+    ///
+    /// # 参数
+    ///
+    /// * `config` - ORB参数
+    ///
+    /// # 返回值
+    ///
+    /// 配置无效时返回 `OrbError::InvalidParameter`，缩放后的采样模式无效时返回 `OrbError::Pattern`
+    pub fn new(config: OrbConfig) -> Result<Orb, OrbError> {
+        if !(config.scale_factor.is_finite() && config.scale_factor > 1.0) {
+            return Err(OrbError::invalid_parameter("scale_factor", format!("must be greater than 1, got {}", config.scale_factor)));
+        }
+        if config.n_levels == 0 {
+            return Err(OrbError::invalid_parameter("n_levels", "must be at least 1"));
+        }
+        if config.first_level >= config.n_levels {
+            return Err(OrbError::invalid_parameter("first_level", format!("must be less than n_levels ({}), got {}", config.n_levels, config.first_level)));
+        }
+        if !(2..=4).contains(&config.wta_k) {
+            return Err(OrbError::invalid_parameter("wta_k", format!("must be 2, 3 or 4, got {}", config.wta_k)));
+        }
+        if config.fast_threshold < 0 {
+            return Err(OrbError::invalid_parameter("fast_threshold", format!("must not be negative, got {}", config.fast_threshold)));
+        }
+        config.smoothing.validate()?;
+        if let Some(grid) = &config.grid {
+            grid.validate()?;
+        }
+
+        let pattern = if config.patch_size == brief::PATCH_SIZE {
+            BriefPattern::default()
+        } else {
+            BriefPattern::default().rescaled(config.patch_size)?
+        };
+        let steered = if config.wta_k > 2 {
+            SteeredPattern::new(wta_tuples(&pattern, config.wta_k as usize))
+        } else {
+            SteeredPattern::from_pattern(&pattern)
+        };
+
+        Ok(Orb { config, steered })
+    }
+
+    /// 获取参数
+    pub fn config(&self) -> &OrbConfig {
+        &self.config
+    }
+
+    /// 检测关键点
+    ///
+    /// # 参数
+    ///
+    /// * `img` - 输入图像
+    ///
+    /// # 返回值
+    ///
+    /// 返回原图坐标系下的关键点，按金字塔层排列；图像的宽或高小于 `2 * edge_threshold + 1` 时返回 `OrbError::ImageTooSmall`
+    pub fn detect(&self, img: &DynamicImage) -> Result<Vec<KeyPoint>, OrbError> {
+        self.detect_with_mask(img, None)
+    }
+
+    /// 只在掩码允许的位置检测关键点
+    ///
+    /// 各层的关键点换算回原图坐标后检查掩码，不允许的关键点在分配给该层的名额选取之前丢弃
+    ///
+    /// # 参数
+    ///
+    /// * `img` - 输入图像
+    /// * `mask` - 原图坐标系下的检测掩码，`None` 时检测整幅图像
+    ///
+    /// # 返回值
+    ///
+    /// 与 `detect` 相同；掩码图像与输入图像大小不同时返回 `OrbError::InvalidParameter`
+    pub fn detect_with_mask(&self, img: &DynamicImage, mask: Option<&DetectionMask>) -> Result<Vec<KeyPoint>, OrbError> {
+        let pyramid = self.build_pyramid(img, mask)?;
+        Ok(self.detect_in_pyramid(&pyramid, mask)?.into_iter().map(|(keypoint, _)| keypoint).collect())
+    }
+
+    /// 为给定的关键点计算描述子
+    ///
+    /// # 参数
+    ///
+    /// * `img` - 输入图像
+    /// * `keypoints` - 原图坐标系下的关键点，通常来自 `detect`
+    ///
+    /// # 返回值
+    ///
+    /// 返回一一对应的关键点和描述子，所在层不存在的关键点，以及 `border_mode` 为 `Filter` 时采样范围超出所在层的关键点会被移除。关键点坐标是取整后的原图坐标，
+    /// 在放大的层（`first_level` 之前）中换算回该层时可能与检测位置相差一个像素
+    pub fn compute(&self, img: &DynamicImage, keypoints: &[KeyPoint]) -> Result<(Vec<KeyPoint>, Vec<Brief>), OrbError> {
+        error::check_image_size(img.width(), img.height(), 1)?;
+        let pyramid = ScalePyramid::new(&img.to_luma8(), &self.config.pyramid(), self.config.n_features);
+        let located = keypoints.iter()
+            .filter(|k| k.octave < pyramid.levels.len())
+            .map(|&k| (k, pyramid.levels[k.octave].from_level0(k.pt)))
+            .collect::<Vec<(KeyPoint, Point)>>();
+
+        self.compute_in_pyramid(&pyramid, located)
+    }
+
+    /// 检测关键点并计算描述子，只构建一次金字塔
+    ///
+    /// # 返回值
+    ///
+    /// 返回一一对应的关键点和描述子
+    pub fn detect_and_compute(&self, img: &DynamicImage) -> Result<(Vec<KeyPoint>, Vec<Brief>), OrbError> {
+        self.detect_and_compute_with_mask(img, None)
+    }
+
+    /// 只在掩码允许的位置检测关键点并计算描述子，掩码的含义与 `detect_with_mask` 相同
+    pub fn detect_and_compute_with_mask(&self, img: &DynamicImage, mask: Option<&DetectionMask>) -> Result<(Vec<KeyPoint>, Vec<Brief>), OrbError> {
+        let pyramid = self.build_pyramid(img, mask)?;
+        let located = self.detect_in_pyramid(&pyramid, mask)?;
+        self.compute_in_pyramid(&pyramid, located)
+    }
+
+    /// 检测关键点并计算描述子，返回带有方向、响应、所在层和直径的关键点以及连续存放的描述子
+    ///
+    /// # 返回值
+    ///
+    /// 与 `detect_and_compute` 相同的特征，描述子只保留描述子位
+    pub fn extract(&self, img: &DynamicImage) -> Result<OrbFeatures, OrbError> {
+        self.extract_with_mask(img, None)
+    }
+
+    /// 只在掩码允许的位置提取特征，掩码的含义与 `detect_with_mask` 相同
+    pub fn extract_with_mask(&self, img: &DynamicImage, mask: Option<&DetectionMask>) -> Result<OrbFeatures, OrbError> {
+        let (keypoints, descriptors) = self.detect_and_compute_with_mask(img, mask)?;
+        Features::new(keypoints, descriptors.iter().map(OrbDescriptor::from).collect())
+    }
+
+    /// 检查图像能容纳边界之内的至少一个像素、掩码与图像大小一致，并构建金字塔
+    fn build_pyramid(&self, img: &DynamicImage, mask: Option<&DetectionMask>) -> Result<ScalePyramid, OrbError> {
+        error::check_image_size(img.width(), img.height(), self.min_level_size())?;
+        if let Some(mask) = mask {
+            mask.validate(img.width(), img.height())?;
+        }
+        Ok(ScalePyramid::new(&img.to_luma8(), &self.config.pyramid(), self.config.n_features))
+    }
+
+    /// 可以检测关键点的最小层边长
+    fn min_level_size(&self) -> u32 {
+        2 * self.config.edge_threshold.max(FAST_BORDER) + 1
+    }
+
+    /// 在金字塔各层检测关键点，同时返回关键点在所在层中的坐标
+    fn detect_in_pyramid(&self, pyramid: &ScalePyramid, mask: Option<&DetectionMask>) -> Result<Vec<(KeyPoint, Point)>, OrbError> {
+        let levels = parallel::map(&pyramid.levels, |level| self.detect_in_level(level, mask))
+            .into_iter()
+            .collect::<Result<Vec<Vec<(KeyPoint, Point)>>, OrbError>>()?;
+
+        Ok(levels.into_iter().flatten().collect())
+    }
+
+    /// 在金字塔的一层中检测掩码允许的关键点
+    fn detect_in_level(&self, level: &PyramidLevel, mask: Option<&DetectionMask>) -> Result<Vec<(KeyPoint, Point)>, OrbError> {
+        let config = &self.config;
+        let border = config.edge_threshold as i32;
+        let min_size = self.min_level_size();
+
+        // 小于最小边长的层中没有可用的关键点
+        if level.n_features == 0 || level.image.width() < min_size || level.image.height() < min_size {
+            return Ok(vec![]);
+        }
+
+        let allowed = |(x, y): Point| mask.is_none_or(|mask| mask.allows(level.to_level0(x as f32, y as f32)));
+        let detect = |image: &GrayImage, threshold: i32| match config.detector {
+            Detector::Fast(fast_type) => fast::fast_corners(image, Some(fast_type), Some(threshold), Some(config.score_type), None),
+            Detector::Agast(mask) => agast::agast_corners(image, Some(mask), Some(threshold), Some(config.score_type), None)
+        };
+
+        let mut keypoints = match &config.grid {
+            Some(grid) => grid::detect_in_grid(&level.image, config.edge_threshold, level.n_features, grid, config.fast_threshold, detect, allowed)?,
+            None => {
+                let (width, height) = (level.image.width() as i32, level.image.height() as i32);
+                let mut keypoints = detect(&level.image, config.fast_threshold)?;
+                keypoints.retain(|k| {
+                    let (x, y) = k.location;
+                    x >= border && y >= border && x < width - border && y < height - border && allowed(k.location)
+                });
+                adaptive_nonmax_suppression(&keypoints, level.n_features, None)?
+            }
+        };
+        fast::compute_moments(&level.image, &mut keypoints, config.patch_size / 2, None);
+        if let Some(method) = config.subpixel {
+            fast::refine_subpixel(&level.image, &mut keypoints, method);
+        }
+
+        Ok(keypoints.into_iter()
+            .map(|k| {
+                let keypoint = KeyPoint {
+                    pt: level.to_level0(k.position.0, k.position.1),
+                    size: config.patch_size as f32 * level.scale,
+                    angle: k.moment.angle,
+                    response: k.score,
+                    octave: level.octave,
+                    class_id: -1
+                };
+                (keypoint, k.location)
+            })
+            .collect())
+    }
+
+    /// 为所在层坐标已知的关键点计算描述子，只平滑有关键点的层
+    ///
+    /// `border_mode` 为 `Filter` 时先丢弃采样范围超出所在层的关键点
+    fn compute_in_pyramid(&self, pyramid: &ScalePyramid, mut located: Vec<(KeyPoint, Point)>) -> Result<(Vec<KeyPoint>, Vec<Brief>), OrbError> {
+        let fits = |k: &KeyPoint, location: Point| {
+            let image = &pyramid.levels[k.octave].image;
+            self.steered.fits(location, image.width(), image.height())
+        };
+        if self.config.border_mode == BorderMode::Filter {
+            located.retain(|&(k, location)| fits(&k, location));
+        }
+
+        let mut used = vec![false; pyramid.levels.len()];
+        for (k, _) in &located {
+            used[k.octave] = true;
+        }
+
+        let smoothed = parallel::map_range(pyramid.levels.len(), |octave| {
+            match used[octave] {
+                true => SmoothedImage::new(&pyramid.levels[octave].image, self.config.smoothing).map(Some),
+                false => Ok(None)
+            }
+        }).into_iter().collect::<Result<Vec<Option<SmoothedImage>>, OrbError>>()?;
+
+        let descriptors = parallel::map(&located, |&(k, location)| {
+            let smoothed_img = smoothed[k.octave].as_ref().expect("level with keypoints is smoothed");
+            let offsets = self.steered.steer(k.angle, self.config.steering);
+            let b = describe(smoothed_img, location, &offsets, self.steered.radius(), self.config.wta_k as usize, self.config.border_mode);
+
+            Brief {
+                x: k.pt.0,
+                y: k.pt.1,
+                octave: k.octave,
+                scale: pyramid.levels[k.octave].scale,
+                size: k.size,
+                wta_k: self.config.wta_k,
+                padded: !fits(&k, location),
+                b
             }
+        });
+
+        Ok((located.into_iter().map(|(keypoint, _)| keypoint).collect(), descriptors))
+    }
+}
+
+impl FeatureDetector for Orb {
+    fn detect(&self, img: &DynamicImage) -> Result<Vec<KeyPoint>, OrbError> {
+        Orb::detect(self, img)
+    }
+}
+
+impl DescriptorExtractor for Orb {
+    type Descriptor = Brief;
+
+    fn compute(&self, img: &DynamicImage, keypoints: &[KeyPoint]) -> Result<(Vec<KeyPoint>, Vec<Brief>), OrbError> {
+        Orb::compute(self, img, keypoints)
+    }
+}
+
+/// 单尺度的BRIEF描述子提取器，可以与任意检测器组合
+///
+/// 所有关键点都在原图上采样，不考虑关键点所在的金字塔层；没有方向的关键点不做旋转。
+/// 默认丢弃采样范围超出图像的关键点，返回的关键点与描述子一一对应
+#[derive(Debug, Clone)]
+pub struct BriefExtractor {
+    pattern: BriefPattern, // 采样模式
+    steered: SteeredPattern, // 预先旋转的采样点
+    smoothing: Smoothing, // 计算描述子前的平滑方式
+    steering: Steering, // 采样点的旋转方式
+    border_mode: BorderMode // 采样范围超出图像的关键点的处理方式
+}
+
+impl BriefExtractor {
+    /// 创建BRIEF描述子提取器
+    ///
+    /// # 参数
+    ///
+    /// * `pattern` - 采样模式，默认为学习得到的rBRIEF模式
+    /// * `smoothing` - 计算描述子前的平滑方式，默认为标准差3的高斯模糊
+    /// * `steering` - 采样点的旋转方式，默认按12度量化的方向查表
+    /// * `border_mode` - 采样范围超出图像的关键点的处理方式，默认丢弃这些关键点
+    pub fn new(pattern: Option<BriefPattern>, smoothing: Option<Smoothing>, steering: Option<Steering>, border_mode: Option<BorderMode>) -> BriefExtractor {
+        let pattern = pattern.unwrap_or_default();
+        BriefExtractor {
+            steered: SteeredPattern::from_pattern(&pattern),
+            pattern,
+            smoothing: smoothing.unwrap_or_default(),
+            steering: steering.unwrap_or_default(),
+            border_mode: border_mode.unwrap_or_default()
         }
+    }
+}
+
+impl DescriptorExtractor for BriefExtractor {
+    type Descriptor = Brief;
+
+    fn compute(&self, img: &DynamicImage, keypoints: &[KeyPoint]) -> Result<(Vec<KeyPoint>, Vec<Brief>), OrbError> {
+        error::check_image_size(img.width(), img.height(), 1)?;
+        let gray_img = img.to_luma8();
+        let smoothed = SmoothedImage::new(&gray_img, self.smoothing)?;
+        let (width, height) = gray_img.dimensions();
+        let pixel = |k: &KeyPoint| (k.pt.0.round() as i32, k.pt.1.round() as i32);
+        let kept:Vec<KeyPoint> = keypoints.iter()
+            .copied()
+            .filter(|k| self.border_mode != BorderMode::Filter || self.steered.fits(pixel(k), width, height))
+            .collect();
+
+        let descriptors = parallel::map(&kept, |k| {
+            let location = pixel(k);
+            let offsets = self.steered.steer(k.angle, self.steering);
+            Brief {
+                x: k.pt.0,
+                y: k.pt.1,
+                octave: k.octave,
+                scale: 1.0,
+                size: self.pattern.patch_size() as f32,
+                wta_k: 2,
+                padded: !self.steered.fits(location, width, height),
+                b: describe(&smoothed, location, &offsets, self.steered.radius(), 2, self.border_mode)
+            }
+        });
+
+        Ok((kept, descriptors))
+    }
+}
+
+/// 计算ORB特征点和描述子，使用默认的8层、缩放因子1.2的图像金字塔
+pub fn orb(img: &DynamicImage, n:usize) -> Result<OrbFeatures, OrbError> {
+    orb_with_pyramid(img, n, &PyramidConfig::default())
+}
 
-        // 如果找到的匹配距离小于某个阈值，则认为是一个有效的匹配
-        if best_distance < 50 { // 这里的阈值可以根据实际情况调整
-            pair_indices.push((i, best_match_index));
+/// 只在掩码允许的位置计算ORB特征点和描述子，使用默认的图像金字塔
+///
+/// # 参数
+///
+/// * `img` - 输入图像
+/// * `n` - 所有层的特征总数
+/// * `mask` - 原图坐标系下的检测掩码，在所有层中一致地生效
+///
+/// # 返回值
+///
+/// 与 `orb` 相同；掩码图像与输入图像大小不同时返回 `OrbError::InvalidParameter`
+pub fn orb_with_mask(img: &DynamicImage, n:usize, mask: &DetectionMask) -> Result<OrbFeatures, OrbError> {
+    OrbConfig::default().n_features(n).build()?.extract_with_mask(img, Some(mask))
+}
+
+/// 在多尺度图像金字塔上计算ORB特征点和描述子
+///
+/// 其余参数使用 [`OrbConfig`] 的默认值，需要调整时请直接使用 [`Orb`]
+///
+/// # 参数
+///
+/// * `img` - 输入图像
+/// * `n` - 所有层的特征总数，按几何级数分配到各层
+/// * `pyramid` - 金字塔配置
+///
+/// # 返回值
+///
+/// 返回第0层坐标系下的关键点，带有方向、响应、所在层和图像块直径，以及一一对应的描述子；
+/// 金字塔配置无效时返回 `OrbError::InvalidParameter`
+pub fn orb_with_pyramid(img: &DynamicImage, n:usize, pyramid: &PyramidConfig) -> Result<OrbFeatures, OrbError> {
+    let orb = OrbConfig::default()
+        .n_features(n)
+        .n_levels(pyramid.n_levels)
+        .scale_factor(pyramid.scale_factor)
+        .first_level(pyramid.first_level)
+        .build()?;
+
+    orb.extract(img)
+}
+
+/// 匹配两幅图像的关键点
+///
+/// 为第一幅图像的每个关键点寻找汉明距离最小的关键点，只保留距离小于50的匹配。
+/// 需要k近邻、比率测试或交叉检验时请使用 [`crate::matcher::BFMatcher`]
+///
+/// # 参数
+///
+/// * `img1_keypoints` - 第一幅图像的描述子，如 `OrbFeatures::descriptors()` 或 `Brief`
+/// * `img2_keypoints` - 第二幅图像的描述子
+pub fn match_brief<T: Matchable + MaybeSync>(img1_keypoints: &[T], img2_keypoints: &[T]) -> Result<Vec<(usize, usize)>, OrbError> {
+    Ok(BFMatcher::new(Some(MAX_MATCH_DISTANCE), false)
+        .match_descriptors(img1_keypoints, img2_keypoints)?
+        .into_iter()
+        .map(|m| (m.query_idx, m.train_idx))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use brief::LEARNED_PATTERN;
+    use fast::Moment;
+
+    #[test]
+    fn brief_bits_follow_pattern_order() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let img = GrayImage::from_fn(64, 64, |_, _| image::Luma([rng.gen()]));
+        let (x, y) = (32, 30);
+        let keypoint = FastKeypoint { location: (x, y), position: (x as f32, y as f32), score: 0.0, nms_dist: 0, moment: Moment::default() };
+
+        let briefs = brief(&img, &[keypoint], None, None).unwrap();
+        let bytes = briefs[0].b.to_bytes();
+        let px = |(dx, dy): (f32, f32)| img.get_pixel((x + dx as i32) as u32, (y + dy as i32) as u32).0[0];
+
+        // 方向为0时第i位是第i个点对的比较结果，位于第i/8个字节的第i%8位
+        for (i, &(p0, p1)) in LEARNED_PATTERN.iter().enumerate() {
+            let test = px(p0) > px(p1);
+            assert_eq!(briefs[0].b.get(i), test, "bit {}", i);
+            assert_eq!((bytes[i / 8] >> (i % 8)) & 1 == 1, test, "byte bit {}", i);
         }
     }
 
-    pair_indices
-}
\ No newline at end of file
+    #[test]
+    fn agast_9_16_detector_matches_fast() {
+        // OAST_9_16与FAST-9-16的角点定义相同，两种检测器得到的特征应当完全一致
+        let mut rng = StdRng::seed_from_u64(3);
+        let blocks:Vec<u8> = (0..30 * 20).map(|_| rng.gen()).collect();
+        let img = DynamicImage::ImageLuma8(GrayImage::from_fn(180, 120, |x, y| image::Luma([blocks[(y / 6 * 30 + x / 6) as usize]])));
+
+        let (fast_keypoints, fast_briefs) = OrbConfig::default().build().unwrap().detect_and_compute(&img).unwrap();
+        let (agast_keypoints, agast_briefs) = OrbConfig::default()
+            .detector(Detector::Agast(SegmentMask::OAST_9_16))
+            .build()
+            .unwrap()
+            .detect_and_compute(&img)
+            .unwrap();
+
+        assert!(!fast_keypoints.is_empty());
+        assert_eq!(fast_keypoints, agast_keypoints);
+        assert!(fast_briefs.iter().zip(&agast_briefs).all(|(a, b)| a.b == b.b));
+    }
+}
@@ -0,0 +1,76 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+
+//! 启用 `parallel` 特性时使用rayon，否则退化为串行迭代。
+//! 两种实现都按输入顺序返回结果，因此输出与是否并行无关。
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// 对切片中的每个元素调用 `f`，按输入顺序收集结果
+#[cfg(feature = "parallel")]
+pub(crate) fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send
+{
+    items.par_iter().map(f).collect()
+}
+
+/// 对切片中的每个元素调用 `f`，按输入顺序收集结果
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    F: Fn(&T) -> R
+{
+    items.iter().map(f).collect()
+}
+
+/// 对 `0..n` 中的每个下标调用 `f`，按下标顺序收集结果
+#[cfg(feature = "parallel")]
+pub(crate) fn map_range<R, F>(n: usize, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(usize) -> R + Sync + Send
+{
+    (0..n).into_par_iter().map(f).collect()
+}
+
+/// 对 `0..n` 中的每个下标调用 `f`，按下标顺序收集结果
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn map_range<R, F>(n: usize, f: F) -> Vec<R>
+where
+    F: Fn(usize) -> R
+{
+    (0..n).map(f).collect()
+}
+
+/// 对切片中的每个元素调用 `f`
+#[cfg(feature = "parallel")]
+pub(crate) fn for_each_mut<T, F>(items: &mut [T], f: F)
+where
+    T: Send,
+    F: Fn(&mut T) + Sync + Send
+{
+    items.par_iter_mut().for_each(f);
+}
+
+/// 对切片中的每个元素调用 `f`
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn for_each_mut<T, F>(items: &mut [T], f: F)
+where
+    F: Fn(&mut T)
+{
+    items.iter_mut().for_each(f);
+}
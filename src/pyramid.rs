@@ -0,0 +1,162 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use image::GrayImage;
+use image::imageops::{resize, FilterType};
+
+use crate::{brief, common};
+use common::{Point, PointF};
+
+// 常量
+pub const DEFAULT_N_LEVELS:usize = 8; // 默认的金字塔层数
+pub const DEFAULT_SCALE_FACTOR:f32 = 1.2; // 默认的相邻层缩放因子
+const MIN_LEVEL_SIZE:u32 = brief::PATCH_SIZE; // 金字塔层的最小边长，更小的层无法容纳采样图像块
+
+/// 表示图像金字塔的配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PyramidConfig {
+    pub n_levels: usize, // 层数
+    pub scale_factor: f32, // 相邻层之间的缩放因子，必须大于1
+    pub first_level: usize // 原图所在的层，之前的层为放大的图像
+}
+
+impl Default for PyramidConfig {
+    fn default() -> Self {
+        PyramidConfig {
+            n_levels: DEFAULT_N_LEVELS,
+            scale_factor: DEFAULT_SCALE_FACTOR,
+            first_level: 0
+        }
+    }
+}
+
+/// 表示金字塔中的一层
+#[derive(Debug, Clone)]
+pub struct PyramidLevel {
+    pub image: GrayImage, // 该层的图像
+    pub octave: usize, // 层索引，第 `first_level` 层为原图
+    pub scale: f32, // 该层相对于原图的缩放比例，即 `scale_factor^(octave - first_level)`
+    pub n_features: usize // 该层分配到的特征数量
+}
+
+impl PyramidLevel {
+    /// 将该层中的坐标转换为原图中的坐标
+    pub fn to_level0(&self, x: f32, y: f32) -> PointF {
+        (x * self.scale, y * self.scale)
+    }
+
+    /// 将原图中的坐标转换为该层中最近的像素
+    pub fn from_level0(&self, pt: PointF) -> Point {
+        ((pt.0 / self.scale).round() as i32, (pt.1 / self.scale).round() as i32)
+    }
+}
+
+/// 表示多尺度图像金字塔
+#[derive(Debug, Clone)]
+pub struct ScalePyramid {
+    pub levels: Vec<PyramidLevel> // 从原图开始逐层缩小的各层
+}
+
+impl ScalePyramid {
+    /// 构建图像金字塔并按几何级数分配特征数量
+    ///
+    /// # 参数
+    ///
+    /// * `img` - 原始灰度图像
+    /// * `config` - 金字塔配置
+    /// * `n_features` - 所有层的特征总数
+    ///
+    /// # 返回值
+    ///
+    /// 返回金字塔，边长小于采样图像块的层会被丢弃
+    pub fn new(img: &GrayImage, config: &PyramidConfig, n_features: usize) -> ScalePyramid {
+        let n_levels = config.n_levels.max(1);
+        let first_level = config.first_level.min(n_levels - 1);
+        let level_scale = |level: usize| config.scale_factor.powi(level as i32 - first_level as i32);
+
+        // 原图之前的层直接由原图放大
+        let mut images:Vec<GrayImage> = (0..first_level)
+            .map(|level| {
+                let scale = level_scale(level);
+                let width = (img.width() as f32 / scale).round() as u32;
+                let height = (img.height() as f32 / scale).round() as u32;
+                resize(img, width, height, FilterType::Triangle)
+            })
+            .collect();
+        images.push(img.clone());
+
+        for level in first_level + 1..n_levels {
+            let scale = level_scale(level);
+            let width = (img.width() as f32 / scale).round() as u32;
+            let height = (img.height() as f32 / scale).round() as u32;
+            if width < MIN_LEVEL_SIZE || height < MIN_LEVEL_SIZE {
+                break;
+            }
+
+            // 从上一层缩放，与OpenCV的做法一致
+            let previous = &images[level - 1];
+            images.push(resize(previous, width, height, FilterType::Triangle));
+        }
+
+        let budgets = features_per_level(n_features, images.len(), config.scale_factor);
+        let levels = images.into_iter()
+            .zip(budgets)
+            .enumerate()
+            .map(|(octave, (image, n_features))| PyramidLevel {
+                image,
+                octave,
+                scale: level_scale(octave),
+                n_features
+            })
+            .collect();
+
+        ScalePyramid { levels }
+    }
+}
+
+/// 按几何级数将特征数量分配到各层
+///
+/// 第 `i` 层分配到的数量与 `scale_factor^-i` 成正比，剩余的数量全部分配给最后一层。
+///
+/// # 参数
+///
+/// * `n_features` - 特征总数
+/// * `n_levels` - 层数
+/// * `scale_factor` - 相邻层之间的缩放因子
+///
+/// # 返回值
+///
+/// 返回每层的特征数量，总和等于 `n_features`
+pub fn features_per_level(n_features: usize, n_levels: usize, scale_factor: f32) -> Vec<usize> {
+    if n_levels == 0 {
+        return vec![];
+    }
+
+    let factor = 1.0 / scale_factor as f64;
+    let mut desired = if (factor - 1.0).abs() < f64::EPSILON {
+        n_features as f64 / n_levels as f64
+    } else {
+        n_features as f64 * (1.0 - factor) / (1.0 - factor.powi(n_levels as i32))
+    };
+
+    let mut budgets = Vec::with_capacity(n_levels);
+    let mut assigned:usize = 0;
+    for _ in 0..n_levels - 1 {
+        let budget = (desired.round() as usize).min(n_features - assigned);
+        budgets.push(budget);
+        assigned += budget;
+        desired *= factor;
+    }
+    budgets.push(n_features - assigned);
+
+    budgets
+}
@@ -0,0 +1,228 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use std::borrow::Cow;
+use image::GrayImage;
+
+use crate::error;
+use error::OrbError;
+
+// 常量
+pub const DEFAULT_BOX_SIZE:u32 = 5; // ORB论文中子窗口的边长
+pub const DEFAULT_SIGMA:f32 = 3.0; // 默认的高斯标准差
+const GAUSSIAN_RADIUS_SIGMAS:f32 = 3.0; // 未指定核大小时，高斯核半径对应的标准差倍数
+
+/// 计算BRIEF描述子之前对图像的平滑方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Smoothing {
+    None, // 不平滑，直接比较像素
+    Gaussian {
+        sigma: f32, // 标准差，必须大于0
+        kernel_size: Option<u32> // 核的边长，必须为奇数，`None` 时取 `2 * round(3 * sigma) + 1`
+    },
+    Box {
+        size: u32 // 子窗口的边长，必须为奇数
+    }
+}
+
+impl Default for Smoothing {
+    /// 默认使用标准差为3的高斯模糊，需要ORB论文中的5x5子窗口时使用 `Smoothing::box_filter()`
+    fn default() -> Self {
+        Smoothing::Gaussian { sigma: DEFAULT_SIGMA, kernel_size: None }
+    }
+}
+
+impl Smoothing {
+    /// ORB论文中基于积分图的5x5子窗口
+    pub fn box_filter() -> Smoothing {
+        Smoothing::Box { size: DEFAULT_BOX_SIZE }
+    }
+
+    /// 校验平滑参数
+    ///
+    /// # 返回值
+    ///
+    /// 标准差不是正数或核边长不是奇数时返回 `OrbError::InvalidParameter`
+    pub fn validate(&self) -> Result<(), OrbError> {
+        match *self {
+            Smoothing::None => Ok(()),
+            Smoothing::Gaussian { sigma, kernel_size } => {
+                if !(sigma.is_finite() && sigma > 0.0) {
+                    return Err(OrbError::invalid_parameter("sigma", format!("must be positive, got {}", sigma)));
+                }
+                match kernel_size {
+                    Some(size) if size % 2 == 0 => Err(OrbError::invalid_parameter("kernel_size", format!("must be odd, got {}", size))),
+                    _ => Ok(())
+                }
+            },
+            Smoothing::Box { size } => {
+                if size % 2 == 0 {
+                    return Err(OrbError::invalid_parameter("size", format!("must be odd, got {}", size)));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 积分图，第 `(x, y)` 项为左上角 `x * y` 区域内的像素和
+///
+/// 和按 `u32` 回绕存储，窗口和等于四项的回绕差，只要窗口和本身不超过 `u32` 就是精确的
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegralImage {
+    width: u32, // 原图宽度
+    height: u32, // 原图高度
+    sums: Vec<u32> // 按行存储的 `(width + 1) * (height + 1)` 项前缀和
+}
+
+impl IntegralImage {
+    /// 计算图像的积分图
+    pub fn new(img: &GrayImage) -> IntegralImage {
+        let (width, height) = img.dimensions();
+        let stride = width as usize + 1;
+        let mut sums = vec![0u32; stride * (height as usize + 1)];
+
+        for (y, row) in img.as_raw().chunks_exact(width.max(1) as usize).take(height as usize).enumerate() {
+            let mut row_sum = 0u32;
+            for (x, &px) in row.iter().enumerate() {
+                row_sum = row_sum.wrapping_add(px as u32);
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1].wrapping_add(row_sum);
+            }
+        }
+
+        IntegralImage { width, height, sums }
+    }
+
+    /// 原图宽度
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// 原图高度
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 计算 `[x0, x1) x [y0, y1)` 区域内的像素和，区域必须位于图像内
+    pub fn sum(&self, x0: u32, y0: u32, x1: u32, y1: u32) -> u32 {
+        let stride = self.width as usize + 1;
+        let at = |x: u32, y: u32| self.sums[y as usize * stride + x as usize];
+        at(x1, y1).wrapping_sub(at(x0, y1)).wrapping_sub(at(x1, y0)).wrapping_add(at(x0, y0))
+    }
+}
+
+/// 按平滑方式处理后的图像，描述子在其上采样
+#[derive(Debug, Clone)]
+pub enum SmoothedImage<'a> {
+    Pixels(Cow<'a, GrayImage>), // 直接比较（可能已模糊的）像素
+    Box(IntegralImage, u32) // 比较以采样点为中心、给定边长的子窗口之和
+}
+
+impl<'a> SmoothedImage<'a> {
+    /// 按平滑方式处理图像，`Smoothing::None` 时不复制图像
+    ///
+    /// # 返回值
+    ///
+    /// 平滑参数无效时返回 `OrbError::InvalidParameter`
+    pub fn new(img: &'a GrayImage, smoothing: Smoothing) -> Result<SmoothedImage<'a>, OrbError> {
+        smoothing.validate()?;
+        Ok(match smoothing {
+            Smoothing::None => SmoothedImage::Pixels(Cow::Borrowed(img)),
+            Smoothing::Gaussian { sigma, kernel_size } => SmoothedImage::Pixels(Cow::Owned(gaussian_blur(img, sigma, kernel_size))),
+            Smoothing::Box { size } => SmoothedImage::Box(IntegralImage::new(img), size)
+        })
+    }
+
+    /// 图像宽度
+    pub fn width(&self) -> u32 {
+        match self {
+            SmoothedImage::Pixels(img) => img.width(),
+            SmoothedImage::Box(integral, _) => integral.width()
+        }
+    }
+
+    /// 图像高度
+    pub fn height(&self) -> u32 {
+        match self {
+            SmoothedImage::Pixels(img) => img.height(),
+            SmoothedImage::Box(integral, _) => integral.height()
+        }
+    }
+
+    /// 读取图像内 `(x, y)` 处的强度
+    ///
+    /// 子窗口超出图像时将窗口平移到图像内，因此所有窗口的面积相同，窗口和可以直接比较
+    pub fn value(&self, x: u32, y: u32) -> u32 {
+        match self {
+            SmoothedImage::Pixels(img) => img.as_raw()[y as usize * img.width() as usize + x as usize] as u32,
+            SmoothedImage::Box(integral, size) => {
+                let (x0, x1) = box_span(x, *size, integral.width());
+                let (y0, y1) = box_span(y, *size, integral.height());
+                integral.sum(x0, y0, x1, y1)
+            }
+        }
+    }
+}
+
+/// 计算以 `center` 为中心、边长为 `size` 的窗口在 `[0, len)` 内的范围，窗口超出时平移到范围内
+fn box_span(center: u32, size: u32, len: u32) -> (u32, u32) {
+    let size = size.min(len);
+    let start = center.saturating_sub(size / 2).min(len - size);
+    (start, start + size)
+}
+
+/// 生成归一化的一维高斯核
+fn gaussian_kernel(sigma: f32, size: u32) -> Vec<f32> {
+    let radius = (size / 2) as i32;
+    let weights:Vec<f32> = (-radius..=radius)
+        .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total:f32 = weights.iter().sum();
+    weights.into_iter().map(|w| w / total).collect()
+}
+
+/// 使用可分离的高斯核模糊图像，超出图像的像素取最近的边界像素
+///
+/// # 参数
+///
+/// * `img` - 输入图像
+/// * `sigma` - 标准差
+/// * `kernel_size` - 核的边长，`None` 时取 `2 * round(3 * sigma) + 1`
+pub fn gaussian_blur(img: &GrayImage, sigma: f32, kernel_size: Option<u32>) -> GrayImage {
+    let size = kernel_size.unwrap_or(2 * (GAUSSIAN_RADIUS_SIGMAS * sigma).round() as u32 + 1);
+    let kernel = gaussian_kernel(sigma, size);
+    let radius = (size / 2) as i32;
+    let (width, height) = (img.width() as i32, img.height() as i32);
+    if width == 0 || height == 0 {
+        return img.clone();
+    }
+
+    let pixels = img.as_raw();
+    let mut horizontal = vec![0f32; pixels.len()];
+    for y in 0..height {
+        let row = &pixels[(y * width) as usize..((y + 1) * width) as usize];
+        for x in 0..width {
+            horizontal[(y * width + x) as usize] = kernel.iter()
+                .enumerate()
+                .map(|(i, w)| w * row[(x + i as i32 - radius).clamp(0, width - 1) as usize] as f32)
+                .sum();
+        }
+    }
+
+    GrayImage::from_fn(width as u32, height as u32, |x, y| {
+        let value:f32 = kernel.iter()
+            .enumerate()
+            .map(|(i, w)| w * horizontal[((y as i32 + i as i32 - radius).clamp(0, height - 1) * width + x as i32) as usize])
+            .sum();
+        image::Luma([value.round().clamp(0.0, 255.0) as u8])
+    })
+}
@@ -0,0 +1,185 @@
+#![allow(dead_code)] // 允许未使用的代码
+#![allow(unused_variables)] // 允许未使用的变量
+#![allow(unused_imports)] // 允许未使用的导入
+#![allow(unused_mut)] // 允许未使用的可变变量
+#![allow(unused_assignments)] // 允许未使用的赋值
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // 在非调试模式下设置Windows子系统
+#![allow(rustdoc::missing_crate_level_docs)] // 允许缺少crate级别的文档
+#![allow(unsafe_code)] // 允许使用unsafe代码
+#![allow(clippy::undocumented_unsafe_blocks)] // 允许未文档化的unsafe块
+#![allow(unused_must_use)] // 允许未使用的must_use结果
+#![allow(non_snake_case)] // 允许非蛇形命名
+
+use std::borrow::Cow;
+
+use crate::{common, brief, smoothing};
+use common::Point;
+use brief::BriefPattern;
+use smoothing::SmoothedImage;
+
+// 常量
+pub const ANGLE_BINS:usize = 30; // 离散方向的数量，与ORB论文一致
+pub const ANGLE_INCREMENT:f32 = 360.0 / ANGLE_BINS as f32; // 相邻离散方向之间的角度，单位为度
+
+/// 表示描述子采样点按关键点方向旋转的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Steering {
+    #[default]
+    Discrete, // 方向量化为12度的倍数，直接使用预先计算的查找表
+    Continuous // 按关键点的精确方向旋转，每个关键点都重新计算三角函数和取整
+}
+
+/// 采样范围超出图像的关键点的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BorderMode {
+    #[default]
+    Filter, // 丢弃采样范围超出图像的关键点，与OpenCV的 `edgeThreshold` 一致
+    Replicate, // 保留关键点，图像外的采样点取最近的边界像素
+    Reflect // 保留关键点，图像外的采样点以边界像素为轴镜像，不重复边界像素
+}
+
+/// 按离散方向预先旋转的采样点查找表
+///
+/// 采样点在创建时按 `ANGLE_BINS` 个方向旋转并取整为像素偏移，
+/// 计算描述子时只需按方向选出一组偏移并读取对应的像素
+#[derive(Debug, Clone, PartialEq)]
+pub struct SteeredPattern {
+    points: Vec<(f32, f32)>, // 未旋转的采样点，相对于关键点中心
+    table: Vec<(i32, i32)>, // 按方向依次存放的整数偏移，每个方向占 `points.len()` 项
+    radius: i32 // 任意方向下偏移坐标绝对值的上界
+}
+
+impl SteeredPattern {
+    /// 预先计算所有离散方向下的采样偏移
+    ///
+    /// # 参数
+    ///
+    /// * `points` - 相对于关键点中心的采样点
+    pub fn new(points: Vec<(f32, f32)>) -> SteeredPattern {
+        let mut table = Vec::with_capacity(ANGLE_BINS * points.len());
+        for bin in 0..ANGLE_BINS {
+            let (sin_a, cos_a) = (bin as f32 * ANGLE_INCREMENT).to_radians().sin_cos();
+            table.extend(points.iter().map(|&p| rotate(p, sin_a, cos_a)));
+        }
+
+        // 旋转不改变到中心的距离，取整后的坐标不会超过距离向上取整
+        let radius = points.iter()
+            .map(|&(x, y)| (x * x + y * y).sqrt().ceil() as i32)
+            .max()
+            .unwrap_or(0);
+
+        SteeredPattern { points, table, radius }
+    }
+
+    /// 为点对模式创建查找表，第 `i` 个点对对应第 `2i` 和 `2i + 1` 个采样点
+    pub fn from_pattern(pattern: &BriefPattern) -> SteeredPattern {
+        SteeredPattern::new(pattern.pairs().iter().flat_map(|&(p0, p1)| [p0, p1]).collect())
+    }
+
+    /// 采样点的数量
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// 是否没有采样点
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// 任意方向下偏移坐标绝对值的上界
+    pub fn radius(&self) -> i32 {
+        self.radius
+    }
+
+    /// 以 `location` 为中心、任意方向下的采样范围是否完全位于 `width x height` 的图像内
+    pub fn fits(&self, location: Point, width: u32, height: u32) -> bool {
+        let (x, y) = location;
+        x >= self.radius && y >= self.radius && x + self.radius < width as i32 && y + self.radius < height as i32
+    }
+
+    /// 获取第 `bin` 个离散方向下的采样偏移
+    ///
+    /// # 参数
+    ///
+    /// * `bin` - 方向下标，必须小于 `ANGLE_BINS`
+    pub fn offsets(&self, bin: usize) -> &[(i32, i32)] {
+        let n = self.points.len();
+        &self.table[bin * n..(bin + 1) * n]
+    }
+
+    /// 获取按关键点方向旋转后的采样偏移
+    ///
+    /// # 参数
+    ///
+    /// * `angle` - 关键点方向，单位为度；负数表示没有方向，按0度处理
+    /// * `steering` - 旋转方式，`Discrete` 时不分配内存
+    pub fn steer(&self, angle: f32, steering: Steering) -> Cow<'_, [(i32, i32)]> {
+        match steering {
+            Steering::Discrete => Cow::Borrowed(self.offsets(angle_bin(angle))),
+            Steering::Continuous => {
+                let angle = if angle < 0.0 { 0.0 } else { angle };
+                let (sin_a, cos_a) = angle.to_radians().sin_cos();
+                Cow::Owned(self.points.iter().map(|&p| rotate(p, sin_a, cos_a)).collect())
+            }
+        }
+    }
+}
+
+/// 将角度量化为最近的离散方向
+///
+/// # 返回值
+///
+/// 返回 `[0, ANGLE_BINS)` 内的方向下标，负数和非有限的角度返回0
+pub fn angle_bin(angle: f32) -> usize {
+    if !(angle.is_finite() && angle >= 0.0) {
+        return 0;
+    }
+
+    (angle / ANGLE_INCREMENT).round() as usize % ANGLE_BINS
+}
+
+/// 将采样点旋转后取整为像素偏移
+fn rotate((x, y): (f32, f32), sin_a: f32, cos_a: f32) -> (i32, i32) {
+    (
+        (x * cos_a - y * sin_a).round() as i32,
+        (x * sin_a + y * cos_a).round() as i32
+    )
+}
+
+/// 按边界处理方式将坐标映射到 `[0, len)` 内，`Filter` 按 `Replicate` 处理
+fn border_index(i: i32, len: i32, border: BorderMode) -> i32 {
+    match border {
+        BorderMode::Reflect if len > 1 => {
+            let period = 2 * (len - 1);
+            let i = i.rem_euclid(period);
+            if i < len { i } else { period - i }
+        },
+        _ => i.clamp(0, len - 1)
+    }
+}
+
+/// 创建按偏移读取强度的采样函数
+///
+/// 采样范围完全位于图像内时不做边界检查，否则按 `border` 映射图像外的采样点
+///
+/// # 参数
+///
+/// * `img` - 平滑后的图像
+/// * `location` - 关键点位置
+/// * `offsets` - 旋转后的采样偏移
+/// * `radius` - 偏移坐标绝对值的上界
+/// * `border` - 图像外采样点的处理方式，`Filter` 时应事先丢弃超出图像的关键点
+pub(crate) fn sampler<'a>(img: &'a SmoothedImage, location: Point, offsets: &'a [(i32, i32)], radius: i32, border: BorderMode) -> impl Fn(usize) -> u32 + 'a {
+    let (width, height) = (img.width() as i32, img.height() as i32);
+    let (x, y) = location;
+    let inside = x >= radius && y >= radius && x + radius < width && y + radius < height;
+
+    move |i| {
+        let (dx, dy) = offsets[i];
+        if inside {
+            img.value((x + dx) as u32, (y + dy) as u32)
+        } else {
+            img.value(border_index(x + dx, width, border) as u32, border_index(y + dy, height, border) as u32)
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! 集成测试共用的合成角点图像
+
+use image::{GrayImage, Luma};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+// 常量
+pub const THRESHOLD:i32 = 20; // FAST阈值
+const IMAGE_SIZE:u32 = 64; // 合成图像边长
+
+/// 以 `(cx, cy)` 为顶点、张角为 `angle` 弧度的亮色楔形
+pub fn wedge(cx: f32, cy: f32, direction: f32, angle: f32) -> GrayImage {
+    GrayImage::from_fn(IMAGE_SIZE, IMAGE_SIZE, |x, y| {
+        let theta = (y as f32 - cy).atan2(x as f32 - cx) - direction;
+        let theta = (theta + std::f32::consts::PI).rem_euclid(2.0 * std::f32::consts::PI) - std::f32::consts::PI;
+        Luma([if theta.abs() <= angle / 2.0 { 200 } else { 50 }])
+    })
+}
+
+/// 楔形、随机块和噪声组成的45张合成图像
+pub fn synthetic_images() -> Vec<(String, GrayImage)> {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut images:Vec<(String, GrayImage)> = vec![];
+
+    // 不同方向和张角的楔形，锐角顶点是角点，接近180度的楔形只有边缘
+    for direction in [0.0f32, 0.4, 1.3, 2.5, 4.0] {
+        for degrees in [30.0f32, 60.0, 90.0, 120.0, 170.0] {
+            images.push((format!("wedge {}° at {:.1}", degrees, direction), wedge(31.5, 32.3, direction, degrees.to_radians())));
+        }
+    }
+    // 随机块和噪声，覆盖跨过圆周起点的弧段
+    for i in 0..10 {
+        let cell = rng.gen_range(2..6);
+        let values:Vec<u8> = (0..(IMAGE_SIZE / cell + 1).pow(2)).map(|_| rng.gen()).collect();
+        let stride = IMAGE_SIZE / cell + 1;
+        images.push((format!("blocks {}", i), GrayImage::from_fn(IMAGE_SIZE, IMAGE_SIZE, |x, y| Luma([values[((y / cell) * stride + x / cell) as usize]]))));
+        images.push((format!("noise {}", i), GrayImage::from_fn(IMAGE_SIZE, IMAGE_SIZE, |_, _| Luma([rng.gen()]))));
+    }
+    images
+}
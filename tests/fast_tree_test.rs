@@ -0,0 +1,62 @@
+//! 在合成角点图像上训练FAST决策树，对比 `FastTree::detect` 与 `fast::fast`
+
+mod common;
+
+use image::GrayImage;
+
+use bye_orb_rs::fast::{self, FastType, FastKeypoint, ScoreType};
+use bye_orb_rs::fast_tree;
+use common::{THRESHOLD, synthetic_images};
+
+// 常量
+const DETECT_THRESHOLDS:[i32; 3] = [10, THRESHOLD, 50]; // 检测时使用的阈值，决策树可以使用与训练不同的阈值
+
+/// 按行优先顺序排列的角点位置和分段测试得分
+fn corners(keypoints: Vec<FastKeypoint>) -> Vec<((i32, i32), f32)> {
+    let mut corners:Vec<((i32, i32), f32)> = keypoints.into_iter().map(|k| (k.location, k.score)).collect();
+    corners.sort_by_key(|&((x, y), _)| (y, x));
+    corners
+}
+
+#[test]
+fn trained_tree_matches_fast() {
+    let images = synthetic_images();
+    let training:Vec<GrayImage> = images.iter().map(|(_, img)| img.clone()).collect();
+
+    for fast_type in [FastType::TYPE_5_8, FastType::TYPE_7_12, FastType::TYPE_9_16] {
+        let tree = fast_tree::train(&training, Some(fast_type), Some(THRESHOLD)).unwrap();
+        assert_eq!(tree.fast_type(), fast_type);
+
+        for (name, img) in &images {
+            for threshold in DETECT_THRESHOLDS {
+                let expected = fast::fast(img, Some(fast_type), Some(threshold), Some(ScoreType::Fast), Some(false)).unwrap();
+                let detected = tree.detect(img, Some(threshold), Some(ScoreType::Fast), Some(false)).unwrap();
+                assert_eq!(corners(detected), corners(expected), "{:?} tree disagrees with fast::fast on {} at threshold {}", fast_type, name, threshold);
+            }
+        }
+    }
+}
+
+#[test]
+fn generated_source_is_balanced() {
+    let training:Vec<GrayImage> = synthetic_images().into_iter().map(|(_, img)| img).collect();
+    let tree = fast_tree::train(&training, Some(FastType::TYPE_7_12), None).unwrap();
+    let source = tree.to_rust_source("is_corner_7_12");
+
+    assert!(source.contains("pub fn is_corner_7_12(pixel: &dyn Fn(usize) -> i32, threshold: i32) -> bool {"));
+    assert!(source.contains("let p = pixel("));
+
+    // 括号必须成对且正确嵌套
+    let mut open = vec![];
+    for c in source.chars() {
+        match c {
+            '(' | '[' | '{' => open.push(c),
+            ')' | ']' | '}' => {
+                let expected = match c { ')' => '(', ']' => '[', _ => '{' };
+                assert_eq!(open.pop(), Some(expected), "unbalanced '{}' in generated source", c);
+            },
+            _ => {}
+        }
+    }
+    assert!(open.is_empty(), "unclosed {:?} in generated source", open);
+}
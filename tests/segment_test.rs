@@ -0,0 +1,87 @@
+//! 在合成角点图像上对比 `fast::fast`、`agast::agast` 与逐像素的参考分段测试
+
+mod common;
+
+use image::GrayImage;
+
+use bye_orb_rs::fast::{self, FastType, FastKeypoint, ScoreType};
+use bye_orb_rs::agast::{self, SegmentMask};
+use common::{THRESHOLD, synthetic_images, wedge};
+
+/// 参考实现使用的圆周，按顺时针排列，与FAST论文一致
+fn circle(fast_type: FastType) -> (Vec<(i32, i32)>, usize) {
+    match fast_type {
+        FastType::TYPE_5_8 => (vec![(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)], 5),
+        FastType::TYPE_7_12 => (vec![(0, -2), (1, -2), (2, -1), (2, 0), (2, 1), (1, 2), (0, 2), (-1, 2), (-2, 1), (-2, 0), (-2, -1), (-1, -2)], 7),
+        FastType::TYPE_9_16 => (vec![(0, -3), (1, -3), (2, -2), (3, -1), (3, 0), (3, 1), (2, 2), (1, 3), (0, 3), (-1, 3), (-2, 2), (-3, 1), (-3, 0), (-3, -1), (-2, -2), (-1, -3)], 9)
+    }
+}
+
+/// 逐个起点检查长度为 `n` 的弧段是否全部更亮或全部更暗
+fn reference_corners(img: &GrayImage, offsets: &[(i32, i32)], n: usize) -> Vec<(i32, i32)> {
+    let border = offsets.iter().map(|&(dx, _)| dx.abs()).max().unwrap() + 1;
+    let px = |x: i32, y: i32| img.get_pixel(x as u32, y as u32).0[0] as i32;
+
+    let mut corners = vec![];
+    for y in border..img.height() as i32 - border {
+        for x in border..img.width() as i32 - border {
+            let center = px(x, y);
+            let is_corner = (0..offsets.len()).any(|start| {
+                let arc = (0..n).map(|i| offsets[(start + i) % offsets.len()]);
+                let values:Vec<i32> = arc.map(|(dx, dy)| px(x + dx, y + dy)).collect();
+                values.iter().all(|&v| v > center + THRESHOLD) || values.iter().all(|&v| v < center - THRESHOLD)
+            });
+            if is_corner {
+                corners.push((x, y));
+            }
+        }
+    }
+    corners
+}
+
+/// 在一组合成图像上对比检测结果，任何一张图像不一致时测试失败
+fn compare<F>(label: &str, offsets: &[(i32, i32)], n: usize, detect: F)
+where
+    F: Fn(&GrayImage) -> Vec<FastKeypoint>
+{
+    let images = synthetic_images();
+    let mut mismatches = vec![];
+    for (name, img) in &images {
+        let mut detected:Vec<(i32, i32)> = detect(img).into_iter().map(|k| k.location).collect();
+        detected.sort_by_key(|&(x, y)| (y, x));
+        let expected = reference_corners(img, offsets, n);
+
+        if detected != expected {
+            mismatches.push(format!("{}: detected {} corners, reference {}", name, detected.len(), expected.len()));
+        }
+    }
+
+    assert!(mismatches.is_empty(), "{} disagrees with the reference on {}/{} images:\n{}", label, mismatches.len(), images.len(), mismatches.join("\n"));
+}
+
+#[test]
+fn fast_matches_reference() {
+    for fast_type in [FastType::TYPE_5_8, FastType::TYPE_7_12, FastType::TYPE_9_16] {
+        let (offsets, n) = circle(fast_type);
+        compare(&format!("{:?}", fast_type), &offsets, n, |img| {
+            fast::fast(img, Some(fast_type), Some(THRESHOLD), Some(ScoreType::Fast), Some(false)).unwrap()
+        });
+    }
+}
+
+#[test]
+fn agast_matches_reference() {
+    for mask in [SegmentMask::AGAST_5_8, SegmentMask::AGAST_7_12d, SegmentMask::AGAST_7_12s, SegmentMask::OAST_9_16] {
+        compare(&format!("{:?}", mask), &mask.offsets(), mask.arc_length(), |img| {
+            agast::agast(img, Some(mask), Some(THRESHOLD), Some(ScoreType::Fast), Some(false)).unwrap()
+        });
+    }
+}
+
+#[test]
+fn straight_edge_has_no_corners() {
+    // 直线边缘上没有长度为9的弧段
+    let edge = wedge(31.5, 32.3, 0.0, std::f32::consts::PI);
+    let edge_corners = fast::fast(&edge, Some(FastType::TYPE_9_16), Some(THRESHOLD), None, Some(false)).unwrap();
+    assert!(edge_corners.is_empty(), "straight edge must not produce FAST-9 corners, got {}", edge_corners.len());
+}